@@ -2,13 +2,15 @@
 //! Enum representing possible actions creatures can take
 //!
 
+use core::world::dungeon::map::Pos;
+
 ///
 /// All actions are meant to be an intended state for a creature to be in
 ///
 /// This is also used by the main game engine to percieve what the player is doing
 /// at any given moment in time and make choices on what to do based on it
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Actions {
   // Creature moved
   Move,
@@ -24,8 +26,40 @@ pub enum Actions {
   Blink,
   // Creature Talked
   Talk,
+  // Creature attacked whatever was at a position
+  Attack(Pos),
+  // Creature picked up whatever was at its feet
+  PickUp,
+  // Creature dropped something it was carrying
+  Drop,
+  // Creature used something it was carrying, like eating food or drinking a potion
+  Apply,
+  // Creature threw something it was carrying
+  Throw,
+  // Creature put something on
+  Equip,
+  // Creature took something off
+  Unequip,
   // Creature died
   Die,
   // Unknown action (Creature did something weird)
   Unknown
+}
+
+impl Actions {
+
+  ///
+  /// How much energy the action takes. A creature at normal speed regains 100 energy in the time it takes to move once
+  ///
+  pub fn cost(&self) -> isize {
+    match *self {
+      Actions::Wait => 50,
+      Actions::DownStair | Actions::UpStair => 150,
+      Actions::Move | Actions::Blink | Actions::Talk | Actions::Attack(_) | Actions::PickUp | Actions::Drop | Actions::Apply | Actions::Throw |
+      Actions::Equip | Actions::Unequip | Actions::Unknown => 100,
+      // The dead don't need any more time
+      Actions::Die => 0
+    }
+  }
+
 }
\ No newline at end of file
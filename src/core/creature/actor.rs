@@ -4,13 +4,15 @@
 
 use core::world::dungeon::map::Pos;
 use core::renderer::{Renderable, RGB};
+use core::save;
 
 ///
 /// Actor struct. Holds necessary properties that extend from `Renderable`
 ///
-#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
 pub struct Actor {
-  name: &'static str,
+  #[serde(deserialize_with = "save::deserialize_static_str")]
+  name: save::StaticStr,
   glyph: char,
   pub pos: Pos,
   fg: RGB,
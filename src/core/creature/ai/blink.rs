@@ -2,15 +2,16 @@ extern crate rand;
 
 use self::rand::Rng;
 
-use core::world::dungeon::map::{self, Pos, tile, Tile};
+use core::world::dungeon::map::{Pos, tile};
 
-use super::{AI, RANDOM_TRIES};
-use core::creature::{Actions, Creature, Actor, Stats};
+use super::{AI, Context, SavedAI, RANDOM_TRIES};
+use core::creature::{Actions, Actor, Stats};
+use core::rng::GameRng;
 
 ///
 /// BlinkAI makes monster teleport around the map periodically
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct BlinkAI;
 
 impl BlinkAI {
@@ -18,8 +19,7 @@ impl BlinkAI {
   ///
   /// Get a random tile nearby
   /// 
-  pub fn blink(&mut self, me: &mut Actor) -> Pos {
-    let mut rng = rand::thread_rng();
+  pub fn blink(&mut self, me: &mut Actor, rng: &mut GameRng) -> Pos {
     let mut x = me.pos.x;
     let mut y = me.pos.y;
     x += rng.gen_range(-8, 8);
@@ -41,10 +41,8 @@ impl AI for BlinkAI {
   ///
   /// Walk around randomly, and occasionally blink
   ///
-  fn take_turn(&mut self, map: &map::Grid<Tile>, _player: &Creature, me: &mut Actor, _stats: &mut Stats) -> Actions {
+  fn take_turn(&mut self, ctx: &mut Context, me: &mut Actor, _stats: &mut Stats) -> Actions {
 
-    let mut rng = rand::thread_rng();
-    
     let mut x = me.pos.x;
     let mut y = me.pos.y;
     let mut count : usize = 0;
@@ -58,7 +56,7 @@ impl AI for BlinkAI {
 
       // Decide on walking or blinking
       let dice : usize;
-      dice = rng.gen_range(1, 6);
+      dice = ctx.rng.gen_range(1, 6);
 
       // Match dice for movement
       match dice {
@@ -68,7 +66,7 @@ impl AI for BlinkAI {
         4 => y -= 1,
         // Blink
         5 => {
-          let bpos = self.blink(me);
+          let bpos = self.blink(me, ctx.rng);
           x = bpos.x;
           y = bpos.y;
           state = Actions::Blink;
@@ -85,14 +83,14 @@ impl AI for BlinkAI {
       if y < 0 {
         y = 0;
       }
-      if y >= (map[0].len() - 1) as isize {
-        y = (map[0].len() - 1) as isize;
+      if y >= (ctx.map[0].len() - 1) as isize {
+        y = (ctx.map[0].len() - 1) as isize;
       }
-      if x >= (map.len() - 1) as isize {
-        x = (map.len() - 1) as isize;
+      if x >= (ctx.map.len() - 1) as isize {
+        x = (ctx.map.len() - 1) as isize;
       }
 
-      if tile::walkable(&map[x as usize][y as usize]) {
+      if tile::walkable(&ctx.map[x as usize][y as usize]) && ctx.occupancy.is_free(Pos::new(x, y)) {
         break;
       // If we make a lot of attempts and still can't find a tile::walkable tile, just stop
       } else if count > RANDOM_TRIES {
@@ -119,4 +117,11 @@ impl AI for BlinkAI {
     Box::new((*self).clone())
   }
 
+  ///
+  /// Allow Box<AI> saving
+  ///
+  fn save(&self) -> SavedAI {
+    SavedAI::Blink((*self).clone())
+  }
+
 }
\ No newline at end of file
@@ -0,0 +1,133 @@
+//!
+//! Everything an AI can perceive when it takes a turn
+//!
+//! Instead of handing AIs the grid and the player and nothing else, the world builds a `Context` for the floor each time a
+//! creature acts. It covers the terrain (which also carries the sound on each tile), the player, every other creature,
+//! the items lying around, who is standing where, the floor's desire maps and scent, the floor's shop, which floor this
+//! is, and the world's rng.
+//!
+
+extern crate rand;
+use self::rand::Rng;
+
+use core::creature::{Actions, Actor, Creature};
+use core::item::Item;
+use core::rng::GameRng;
+use core::world::desire::{Desire, DesireMaps};
+use core::world::dungeon::map::{self, path, Pos, tile, Tile};
+use core::world::dungeon::map::construct::Shop;
+use core::world::occupancy::Occupancy;
+use core::world::scent::ScentMap;
+
+///
+/// What an AI can see of the floor it's on
+///
+pub struct Context<'a> {
+  // Terrain, along with the sound on each tile
+  pub map: &'a map::Grid<Tile>,
+  pub player: &'a Creature,
+  // Every creature on the floor, including the one taking its turn. Dead creatures that haven't been cleared away yet are
+  // still in here, so use `others()` to skip them
+  pub creatures: &'a [Box<Creature>],
+  pub items: &'a [Item],
+  pub occupancy: &'a Occupancy,
+  // How far everything worth walking to is, built once a turn
+  pub desire: &'a DesireMaps,
+  // What every tile smells of
+  pub scent: &'a ScentMap,
+  // The floor's shop, if it has one, along with whatever the shopkeeper holds against the player
+  pub shop: Option<&'a Shop>,
+  // How deep the floor is. Floor 0 is the first floor
+  pub floor_num: usize,
+  // Index of the creature taking its turn in `creatures`, if it's on the floor at all
+  pub me: Option<usize>,
+  // Any randomness has to come from here so that turns can be reproduced from a seed
+  pub rng: &'a mut GameRng
+}
+
+impl<'a> Context<'a> {
+
+  ///
+  /// Get every living creature on the floor besides the one taking its turn, along with its index
+  ///
+  pub fn others(&self) -> Vec<(usize, &'a Creature)> {
+    let creatures = self.creatures;
+    creatures.iter()
+      .enumerate()
+      .filter(|&(idx, creature)| Some(idx) != self.me && creature.state != Actions::Die)
+      .map(|(idx, creature)| (idx, &**creature))
+      .collect()
+  }
+
+  ///
+  /// Get every other living creature within `radius` of `pos`
+  ///
+  pub fn creatures_near(&self, pos: Pos, radius: f32) -> Vec<(usize, &'a Creature)> {
+    self.others().into_iter().filter(|&(_, creature)| (creature.actor.pos ^ pos) <= radius).collect()
+  }
+
+  ///
+  /// Get every item within `radius` of `pos`
+  ///
+  pub fn items_near(&self, pos: Pos, radius: f32) -> Vec<&'a Item> {
+    let items = self.items;
+    items.iter().filter(|item| (item.pos ^ pos) <= radius).collect()
+  }
+
+  ///
+  /// Check if `pos` is on the map
+  ///
+  pub fn in_bounds(&self, pos: Pos) -> bool {
+    pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.map.len() && (pos.y as usize) < self.map[0].len()
+  }
+
+  ///
+  /// Check if a creature could step onto `pos` right now, without bumping into anybody
+  ///
+  pub fn is_open(&self, pos: Pos) -> bool {
+    self.in_bounds(pos) && tile::walkable(&self.map[pos.x as usize][pos.y as usize]) && self.occupancy.is_free(pos)
+  }
+
+  ///
+  /// Walk `me` somewhere random that's open, or wait if there's nowhere to go
+  ///
+  pub fn wander(&mut self, me: &mut Actor) -> Actions {
+    let open : Vec<Pos> = path::DIRECTIONS.iter()
+      .map(|dir| me.pos + *dir)
+      .filter(|pos| self.is_open(*pos))
+      .collect();
+    match self.rng.choose(&open).cloned() {
+      Some(pos) => {
+        me.pos = pos;
+        Actions::Move
+      },
+      None => Actions::Wait
+    }
+  }
+
+  ///
+  /// Find the best step from `from` given how much each desire matters, or `None` if staying put is best. See `DesireMaps`
+  ///
+  pub fn downhill(&self, from: Pos, weights: &[(Desire, f32)]) -> Option<Pos> {
+    self.desire.downhill(self.map, self.occupancy, from, weights)
+  }
+
+  ///
+  /// How loud it is at `pos`. Nothing can be heard off the map
+  ///
+  pub fn sound_at(&self, pos: Pos) -> usize {
+    if self.in_bounds(pos) {
+      self.map[pos.x as usize][pos.y as usize].sound
+    } else {
+      0
+    }
+  }
+
+  ///
+  /// How strongly `pos` smells of `scent`. Nothing can be smelled off the map
+  ///
+  pub fn scent_at(&self, pos: Pos, scent: &tile::Scent) -> u8 {
+    self.scent.get(pos, scent)
+  }
+
+}
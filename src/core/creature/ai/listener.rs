@@ -0,0 +1,201 @@
+use core::world::dungeon::map::{path, Pos};
+
+use super::{AI, Context, SavedAI};
+use core::creature::{Actions, Actor, Stats};
+
+///
+/// How quiet something can be and still be heard unless a listener is told otherwise
+///
+pub const DEFAULT_HEARING : usize = 4;
+
+///
+/// How many times louder than its hearing a noise has to be to wake a listener up
+///
+pub const WAKE_FACTOR : usize = 3;
+
+///
+/// How many turns a listener spends looking for where a noise came from unless it's told otherwise
+///
+pub const DEFAULT_PATIENCE : usize = 15;
+
+///
+/// How far away a listener looks for the source of a noise it heard
+///
+const SOURCE_RADIUS : isize = 6;
+
+///
+/// How aware a listener is of its surroundings
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Awareness {
+  // Only wakes up for loud noises
+  Asleep,
+  // Wanders around, listening
+  Awake,
+  // Heard something and is off to find it
+  Alert
+}
+
+///
+/// ListenerAI wakes up to noises, and goes to find where they came from
+///
+/// Noises are heard if the sound on the listener's tile is at least `hearing`. Asleep, they have to be `WAKE_FACTOR` times
+/// louder than that. Once it's heard something, the listener heads for the loudest tile around it and keeps going until it
+/// gets there or runs out of `patience`, at which point it goes back to wandering. Hearing something else along the way
+/// sends it off after that instead.
+///
+/// Listeners can hear their own footsteps too, so they remember how much noise they made last turn and don't count it.
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ListenerAI {
+  pub hearing: usize,
+  pub patience: usize,
+  pub awareness: Awareness,
+  // Where the last noise came from
+  target: Option<Pos>,
+  // Turns left before giving up on finding the noise
+  searching: usize,
+  // How loud the listener's own last move was
+  footsteps: usize
+}
+
+impl ListenerAI {
+
+  ///
+  /// Return a new `ListenerAI` that's awake and has normal hearing
+  ///
+  pub fn new() -> Self {
+    ListenerAI::with_hearing(DEFAULT_HEARING, DEFAULT_PATIENCE, Awareness::Awake)
+  }
+
+  ///
+  /// Return a new `ListenerAI` with the given hearing, patience and awareness
+  ///
+  pub fn with_hearing(hearing: usize, patience: usize, awareness: Awareness) -> Self {
+    ListenerAI {
+      hearing: hearing,
+      patience: patience,
+      awareness: awareness,
+      target: None,
+      searching: 0,
+      footsteps: 0
+    }
+  }
+
+  ///
+  /// Get where a noise loud enough to notice came from, if there was one
+  ///
+  fn listen(&self, ctx: &Context, me: Pos) -> Option<Pos> {
+
+    let threshold = match self.awareness {
+      Awareness::Asleep => self.hearing * WAKE_FACTOR,
+      _ => self.hearing
+    };
+
+    if ctx.sound_at(me).saturating_sub(self.footsteps) < threshold.max(1) {
+      return None;
+    }
+
+    // Sound is loudest right where it's made, so follow it back. If the listener just moved, its own tile is only loud
+    // because of its footsteps
+    let mut loudest = None;
+    for x in (me.x - SOURCE_RADIUS)..(me.x + SOURCE_RADIUS + 1) {
+      for y in (me.y - SOURCE_RADIUS)..(me.y + SOURCE_RADIUS + 1) {
+        let pos = Pos::new(x, y);
+        if (pos != me || self.footsteps == 0) && loudest.map_or(true, |loudest| ctx.sound_at(pos) > ctx.sound_at(loudest)) {
+          loudest = Some(pos);
+        }
+      }
+    }
+
+    return loudest;
+
+  }
+
+  ///
+  /// Sleep, wander or chase noises depending on how aware the listener is
+  ///
+  fn act(&mut self, ctx: &mut Context, me: &mut Actor) -> Actions {
+
+    match self.awareness {
+
+      Awareness::Asleep => return Actions::Wait,
+
+      Awareness::Awake => return ctx.wander(me),
+
+      Awareness::Alert => {
+
+        let target = self.target.unwrap_or(me.pos);
+
+        // Found it, or got bored of looking
+        if target == me.pos || self.searching == 0 {
+          self.awareness = Awareness::Awake;
+          self.target = None;
+          return Actions::Wait;
+        }
+
+        self.searching -= 1;
+
+        // Whatever made the noise might be standing there, in which case walking into it is an attack
+        match path::astar(ctx.map, Some(ctx.occupancy), me.pos, target) {
+          Some(ref steps) if !steps.is_empty() => {
+            me.pos = steps[0];
+            return Actions::Move;
+          },
+          // Can't get there, so there's no point trying
+          _ => {
+            self.searching = 0;
+            return Actions::Wait;
+          }
+        }
+
+      }
+
+    }
+
+  }
+
+}
+
+impl AI for ListenerAI {
+
+  ///
+  /// Sleep, wander or chase noises depending on what it's heard
+  ///
+  fn take_turn(&mut self, ctx: &mut Context, me: &mut Actor, stats: &mut Stats) -> Actions {
+
+    if let Some(source) = self.listen(ctx, me.pos) {
+      self.awareness = Awareness::Alert;
+      self.target = Some(source);
+      self.searching = self.patience;
+    }
+
+    let state = self.act(ctx, me);
+
+    // The context has the listener as it was before it moved, with everything it has on. Listeners that aren't on the
+    // floor can only go by how heavy they are
+    self.footsteps = match ctx.me {
+      _ if state != Actions::Move => 0,
+      Some(idx) => ctx.creatures[idx].footsteps(),
+      None => stats.weight
+    };
+
+    return state;
+
+  }
+
+  ///
+  /// Allow Box<AI> cloning
+  ///
+  fn box_clone(&self) -> Box<AI> {
+    Box::new((*self).clone())
+  }
+
+  ///
+  /// Allow Box<AI> saving
+  ///
+  fn save(&self) -> SavedAI {
+    SavedAI::Listener((*self).clone())
+  }
+
+}
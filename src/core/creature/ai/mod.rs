@@ -6,6 +6,9 @@
 // Ai behaviors are inherited from specific objects that have the AI trait
 //
 
+extern crate serde;
+use self::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 // How many times should AI randomly try stuff
 // Since there will probably be a lot of AI, and since each one might be doing stuff randomly,
 // the larger this gets, the more it impacts performance in the absolute worst case
@@ -15,12 +18,24 @@ pub const RANDOM_TRIES : usize = 10;
 // NOTE: Probably going to get rid of this at some point
 pub const TALK_DISTANCE: f32 = 20.0;
 
+pub mod context;
+pub use self::context::Context;
+
 pub mod blink;
 pub use self::blink::BlinkAI;
 
+pub mod listener;
+pub use self::listener::ListenerAI;
+
 pub mod player;
 pub use self::player::PlayerAI;
 
+pub mod scavenger;
+pub use self::scavenger::ScavengerAI;
+
+pub mod shopkeeper;
+pub use self::shopkeeper::ShopkeeperAI;
+
 pub mod simple;
 pub use self::simple::SimpleAI;
 
@@ -35,7 +50,7 @@ pub use self::tracker::TrackerAI;
 
 use core::world::dungeon::map::{self, Tile};
 
-use core::creature::{Actions, Creature, Actor, Stats};
+use core::creature::{Actions, Actor, Stats};
 
 // As AI becomes more complex it might be a good idea to put 'general' functions in this file to help guide and maintain
 // certain 'motifs' of AI such as boundary checking, creature overlap checking, etc.
@@ -59,14 +74,19 @@ use core::creature::{Actions, Creature, Actor, Stats};
 pub trait AI {
 
   ///
-  /// Make the AI take it's turn based on map, player, and itself
+  /// Make the AI take it's turn based on what it can perceive of the floor, and itself
+  /// 
+  /// Any randomness the AI needs has to come from `ctx.rng` so that turns can be reproduced from a seed
+  ///
+  /// `ctx.occupancy` says who is standing where. Walking into someone is allowed, the world decides what happens when they
+  /// collide, but most AIs would rather walk around each other
   /// 
   /// NOTE: AIs are basically just state deciders at this point but more complex AIs have to be state machines in of themselves
-  /// in order to create complex behaviors. At some point they should take in a state, a vector of all creatures on the floor
-  /// (for monster infighting, fight-flight) and maybe even some sort of "mood" though that would be a part of the `Creature`. I am
+  /// in order to create complex behaviors. The context has every creature and item on the floor for things like monster
+  /// infighting, fight-flight and scavenging, but there's no "mood" yet, though that would be a part of the `Creature`. I am
   /// completely considering adding randomized personalities to monsters to create even more combinations of behavior.
   ///
-  fn take_turn(&mut self, map: &map::Grid<Tile>, player: &Creature, me: &mut Actor, stats: &mut Stats) -> Actions;
+  fn take_turn(&mut self, ctx: &mut Context, me: &mut Actor, stats: &mut Stats) -> Actions;
 
   ///
   /// Determine if the AI has gone out of bounds with respect to the given map
@@ -81,6 +101,11 @@ pub trait AI {
   /// 
   fn box_clone(&self) -> Box<AI>;
 
+  ///
+  /// Allow boxed trait objects to be saved
+  ///
+  fn save(&self) -> SavedAI;
+
 }
 
 ///
@@ -93,4 +118,85 @@ impl Clone for Box<AI> {
   fn clone(&self) -> Box<AI> {
     self.box_clone()
   }
-}
\ No newline at end of file
+}
+
+///
+/// Every concrete `AI`, as something serde can handle
+///
+/// Trait objects can't be deserialized because serde has no way of knowing what type is behind the box. So instead, each `AI`
+/// puts a copy of itself into this enum with `save()`, and the enum (which serde understands perfectly well) is what ends up
+/// on disk. Loading goes the other way with `load()`.
+///
+/// Whenever you add a new AI, give it a variant here.
+///
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SavedAI {
+  Blink(BlinkAI),
+  Listener(ListenerAI),
+  Player(PlayerAI),
+  Scavenger(ScavengerAI),
+  Shopkeeper(ShopkeeperAI),
+  Simple(SimpleAI),
+  Smeller(SmellerAI),
+  Talker(TalkerAI),
+  Tracker(TrackerAI)
+}
+
+impl SavedAI {
+
+  ///
+  /// Turn the saved AI back into a trait object
+  ///
+  pub fn load(self) -> Box<AI> {
+    match self {
+      SavedAI::Blink(ai) => Box::new(ai),
+      SavedAI::Listener(ai) => Box::new(ai),
+      SavedAI::Player(ai) => Box::new(ai),
+      SavedAI::Scavenger(ai) => Box::new(ai),
+      SavedAI::Shopkeeper(ai) => Box::new(ai),
+      SavedAI::Simple(ai) => Box::new(ai),
+      SavedAI::Smeller(ai) => Box::new(ai),
+      SavedAI::Talker(ai) => Box::new(ai),
+      SavedAI::Tracker(ai) => Box::new(ai)
+    }
+  }
+
+}
+
+///
+/// Get a fresh AI from the name monster definitions use for it
+///
+/// The player's AI isn't in here, since nothing but the player should be using it. Whenever you add a new AI that monsters
+/// can have, give it a name here.
+///
+pub fn from_name(name: &str) -> Option<Box<AI>> {
+  match name {
+    "blink" => Some(Box::new(BlinkAI::new())),
+    "listener" => Some(Box::new(ListenerAI::new())),
+    "scavenger" => Some(Box::new(ScavengerAI::new())),
+    "shopkeeper" => Some(Box::new(ShopkeeperAI::new())),
+    "simple" => Some(Box::new(SimpleAI::new())),
+    "smeller" => Some(Box::new(SmellerAI::new())),
+    "talk" => Some(Box::new(TalkerAI::new())),
+    "tracker" => Some(Box::new(TrackerAI::new())),
+    _ => None
+  }
+}
+
+///
+/// Serialize boxed trait objects via save()
+///
+impl Serialize for Box<AI> {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+    self.save().serialize(serializer)
+  }
+}
+
+///
+/// Deserialize boxed trait objects via `SavedAI`
+///
+impl<'de> Deserialize<'de> for Box<AI> {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+    SavedAI::deserialize(deserializer).map(|ai| ai.load())
+  }
+}
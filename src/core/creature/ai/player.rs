@@ -1,12 +1,10 @@
-use core::world::dungeon::map::{self, Tile};
-
-use super::AI;
-use core::creature::{Actions, Creature, Actor, Stats};
+use super::{AI, Context, SavedAI};
+use core::creature::{Actions, Actor, Stats};
 
 ///
 /// PlayerAI does nothing
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct PlayerAI;
 
 impl PlayerAI {
@@ -20,7 +18,7 @@ impl AI for PlayerAI {
   ///
   /// Do nothing
   ///
-  fn take_turn(&mut self, _map: &map::Grid<Tile>, _player: &Creature, _me: &mut Actor, _stats: &mut Stats) -> Actions {
+  fn take_turn(&mut self, _ctx: &mut Context, _me: &mut Actor, _stats: &mut Stats) -> Actions {
 
     return Actions::Unknown;
 
@@ -33,4 +31,11 @@ impl AI for PlayerAI {
     Box::new((*self).clone())
   }
 
+  ///
+  /// Allow Box<AI> saving
+  ///
+  fn save(&self) -> SavedAI {
+    SavedAI::Player((*self).clone())
+  }
+
 }
\ No newline at end of file
@@ -0,0 +1,78 @@
+use core::world::desire::Desire;
+
+use super::{AI, Context, SavedAI};
+use core::creature::{Actions, Actor, Stats};
+
+///
+/// How close the player has to be before a scavenger would rather get away from them than get at what's lying around
+///
+pub const WARY_DISTANCE : f32 = 5.0;
+
+///
+/// How much more a scavenger wants to get away from the player than to get at what's lying around, once they're close
+///
+const FEAR : f32 = 2.0;
+
+///
+/// AI that picks over whatever is lying around on the floor, and runs from the player if they come too close
+///
+/// Scavengers go wherever the floor's desire maps lead them, so they find their way to items and away from the player
+/// without any pathfinding of their own. Standing on something is as good as it gets, so they stay put until something
+/// scares them off. Floors with nothing left to pick over leave them wandering around
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ScavengerAI;
+
+impl ScavengerAI {
+  #[inline]
+  pub fn new() -> Self {
+    ScavengerAI {}
+  }
+}
+
+impl AI for ScavengerAI {
+
+  ///
+  /// Head for items, or away from the player if they're close
+  ///
+  fn take_turn(&mut self, ctx: &mut Context, me: &mut Actor, _stats: &mut Stats) -> Actions {
+
+    let wary = (me.pos ^ ctx.player.actor.pos) <= WARY_DISTANCE;
+    let weights = if wary {
+      vec![(Desire::Items, 1.0), (Desire::Flee, FEAR)]
+    } else {
+      vec![(Desire::Items, 1.0)]
+    };
+
+    // Scavengers don't pick fights, so they won't step into the player even if the way downhill goes through them
+    match ctx.downhill(me.pos, &weights) {
+      Some(pos) if ctx.is_open(pos) => {
+        me.pos = pos;
+        return Actions::Move;
+      },
+      _ => {}
+    }
+
+    if !wary && !ctx.items_near(me.pos, 0.0).is_empty() {
+      return Actions::Wait;
+    }
+
+    return ctx.wander(me);
+
+  }
+
+  ///
+  /// Allow Box<AI> cloning
+  ///
+  fn box_clone(&self) -> Box<AI> {
+    Box::new((*self).clone())
+  }
+
+  ///
+  /// Allow Box<AI> saving
+  ///
+  fn save(&self) -> SavedAI {
+    SavedAI::Scavenger((*self).clone())
+  }
+
+}
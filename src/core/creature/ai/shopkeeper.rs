@@ -0,0 +1,90 @@
+use core::world::dungeon::map::{path, Pos};
+
+use super::{AI, Context, SavedAI};
+use core::creature::{Actions, Actor, Stats};
+
+///
+/// AI that minds a shop
+///
+/// Shopkeepers stand by the door of their shop and leave the buying and selling to the world. Anyone who hurts them, or
+/// walks out with something they haven't paid for, gets chased down for good. The shop keeps track of that, so the
+/// shopkeeper minding the floor's shop reads it from the context. Shopkeepers without a shop only hold being hurt against
+/// the player
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ShopkeeperAI {
+  // Where to stand. Shopkeepers without one stand wherever they first find themselves
+  pub post: Option<Pos>
+}
+
+impl ShopkeeperAI {
+
+  ///
+  /// Return a new AI
+  ///
+  pub fn new() -> Self {
+    ShopkeeperAI {
+      post: None
+    }
+  }
+
+  ///
+  /// Return a new AI minding the shop from `post`
+  ///
+  pub fn at(post: Pos) -> Self {
+    ShopkeeperAI {
+      post: Some(post)
+    }
+  }
+
+}
+
+impl AI for ShopkeeperAI {
+
+  ///
+  /// Stay at the post, unless there's a score to settle
+  ///
+  fn take_turn(&mut self, ctx: &mut Context, me: &mut Actor, stats: &mut Stats) -> Actions {
+
+    // Nobody hits a shopkeeper and gets away with it
+    let angry = match ctx.shop {
+      Some(shop) if shop.keeper.is_some() && shop.keeper == ctx.me => shop.angry,
+      _ => stats.hp < stats.max_hp
+    };
+
+    let target = if angry {
+      ctx.player.actor.pos
+    } else {
+      *self.post.get_or_insert(me.pos)
+    };
+
+    if me.pos == target {
+      return Actions::Wait;
+    }
+
+    match path::astar(ctx.map, Some(ctx.occupancy), me.pos, target) {
+      // Walking into the player is an attack, which is only alright once they've made an enemy of us
+      Some(ref steps) if !steps.is_empty() && (angry || steps[0] != ctx.player.actor.pos) => {
+        me.pos = steps[0];
+        return Actions::Move;
+      },
+      _ => return Actions::Wait
+    }
+
+  }
+
+  ///
+  /// Allow Box<AI> cloning
+  ///
+  fn box_clone(&self) -> Box<AI> {
+    Box::new((*self).clone())
+  }
+
+  ///
+  /// Allow Box<AI> saving
+  ///
+  fn save(&self) -> SavedAI {
+    SavedAI::Shopkeeper((*self).clone())
+  }
+
+}
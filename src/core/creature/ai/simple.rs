@@ -2,10 +2,10 @@ extern crate rand;
 
 use self::rand::Rng;
 
-use core::world::dungeon::map::{self, tile, Tile};
+use core::world::dungeon::map::{Pos, tile};
 
-use super::{AI, RANDOM_TRIES};
-use core::creature::{Actions, Creature, Actor, Stats};
+use super::{AI, Context, SavedAI, RANDOM_TRIES};
+use core::creature::{Actions, Actor, Stats};
 
 ///
 /// SimpleAI is literally just an AI that walks around randomly
@@ -13,7 +13,7 @@ use core::creature::{Actions, Creature, Actor, Stats};
 /// NOTE: There is really no intention to keep this AI around... Maybe as a confused AI?
 /// Definitely will be replaced/refactored.
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct SimpleAI;
 
 impl SimpleAI {
@@ -27,9 +27,8 @@ impl AI for SimpleAI {
   ///
   /// Walk around randomly
   ///
-  fn take_turn(&mut self, map: &map::Grid<Tile>, _player: &Creature, me: &mut Actor, _stats: &mut Stats) -> Actions {
+  fn take_turn(&mut self, ctx: &mut Context, me: &mut Actor, _stats: &mut Stats) -> Actions {
 
-    let mut rng = rand::thread_rng();
     let mut dice : usize;
     let mut state = Actions::Move;
     
@@ -43,7 +42,7 @@ impl AI for SimpleAI {
       count += 1;
       x = me.pos.x as usize;
       y = me.pos.y as usize;
-      dice = rng.gen_range(1, 5);
+      dice = ctx.rng.gen_range(1, 5);
 
       if x == 0 || y == 0 {
         x += 1;
@@ -62,7 +61,7 @@ impl AI for SimpleAI {
       // Since the only thing this thing can do is move, there is no need to match the dice again to determine state
       
       // If we find a good tile, great, otherwise keep trying until we get tired of it
-      if tile::walkable(&map[x][y]) {
+      if tile::walkable(&ctx.map[x][y]) && ctx.occupancy.is_free(Pos::new(x as isize, y as isize)) {
         break;
       } else if count > RANDOM_TRIES {
         x = me.pos.x as usize;
@@ -87,4 +86,11 @@ impl AI for SimpleAI {
     Box::new((*self).clone())
   }
 
+  ///
+  /// Allow Box<AI> saving
+  ///
+  fn save(&self) -> SavedAI {
+    SavedAI::Simple((*self).clone())
+  }
+
 }
\ No newline at end of file
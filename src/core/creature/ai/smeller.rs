@@ -1,142 +1,134 @@
-extern crate rand;
+use core::world::dungeon::map::{path, tile, Pos};
+use core::world::occupancy::Occupant;
 
-use self::rand::Rng;
+use super::{AI, Context, SavedAI};
+use core::creature::{Actions, Actor, Stats};
 
-use core::world::dungeon::map::{self, tile, Tile};
-
-use super::{AI, RANDOM_TRIES};
-use core::creature::{Actions, Creature, Actor, Stats};
+///
+/// How far a smeller can smell unless it's told otherwise
+///
+pub const DEFAULT_SMELL_RADIUS : usize = 2;
 
 ///
-/// SmellerAI is an AI that follows insect smells
+/// SmellerAI follows scents it likes and keeps away from scents it doesn't
 ///
-/// NOTE: This is a proof of concept AI
+/// Every tile within `radius` gets a score, which is how strongly it smells of everything in `attract` less how strongly
+/// it smells of everything in `repel`. The smeller then steps to whichever tile around it (or stays where it is) that gets
+/// it closest to the best smelling tile and furthest from the worst smelling one. Tiles past the edge of the map are never
+/// smelled, so the edges don't need any special treatment.
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
-pub struct SmellerAI;
+/// If it can't smell anything it cares about, it just wanders around.
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SmellerAI {
+  pub attract: Vec<tile::Scent>,
+  pub repel: Vec<tile::Scent>,
+  pub radius: usize
+}
 
 impl SmellerAI {
+
+  ///
+  /// Return a new `SmellerAI` that follows insects around, like ant eaters do
+  ///
   pub fn new() -> Self {
-    SmellerAI {}
+    SmellerAI::with_scents(vec![tile::Scent::Insectoid], vec![], DEFAULT_SMELL_RADIUS)
   }
-}
 
-impl AI for SmellerAI {
-  
   ///
-  /// Walk around randomly until it picks up a scent
+  /// Return a new `SmellerAI` that likes and dislikes the given scents
   ///
-  fn take_turn(&mut self, map: &map::Grid<Tile>, _player: &Creature, me: &mut Actor, _stats: &mut Stats) -> Actions {
-
-    let mut state = Actions::Wait;
-    
-    let mut x = me.pos.x.clone() as usize;
-    let mut y = me.pos.y.clone() as usize;
-
-    let mut tx : isize = -1;
-    let mut ty : isize = -1;
-    let mut scent_val : isize = 0;
-
-    // Represents how far the ai can smell
-    let mut scent_range = 2;
-  
-    // Avoid OOB Errors
-    if x < 2 || x > map.len() - 2 || y < 2 || y > map[0].len() - 2 {
-      // NOTE: scent_range one 1 has weird behaviors...
-      scent_range = 1;
+  pub fn with_scents(attract: Vec<tile::Scent>, repel: Vec<tile::Scent>, radius: usize) -> Self {
+    SmellerAI {
+      attract: attract,
+      repel: repel,
+      radius: radius
     }
+  }
+
+  ///
+  /// How much the smeller likes the smell of `pos`
+  ///
+  fn score(&self, ctx: &Context, pos: Pos) -> isize {
+    let good : isize = self.attract.iter().map(|scent| ctx.scent_at(pos, scent) as isize).sum();
+    let bad : isize = self.repel.iter().map(|scent| ctx.scent_at(pos, scent) as isize).sum();
+    return good - bad;
+  }
 
-    for sx in x-scent_range..x+scent_range {
-      for sy in y-scent_range..y+scent_range {
-        // Scents[1] refers to the insectoid smell via the c-like enum
-        // not the best solution
-        if map[sx][sy].scents[1].val as isize > scent_val && (sx, sy) != (x, y) { 
-          tx = sx as isize; 
-          ty = sy as isize; 
-          scent_val = map[sx][sy].scents[1].val as isize;
+  ///
+  /// Find every tile within smelling range that smells of anything the smeller cares about, along with its score
+  ///
+  fn smell(&self, ctx: &Context, me: Pos) -> Vec<(Pos, isize)> {
+    let radius = self.radius as isize;
+    let mut smells = vec![];
+    for x in (me.x - radius)..(me.x + radius + 1) {
+      for y in (me.y - radius)..(me.y + radius + 1) {
+        let pos = Pos::new(x, y);
+        if ctx.in_bounds(pos) {
+          let score = self.score(ctx, pos);
+          if score != 0 {
+            smells.push((pos, score));
+          }
         }
       }
     }
+    return smells;
+  }
 
-    // If a scent has been picked up behave like a tracker and move towards that tile
-    if scent_val > 0 && scent_val < 80{
+  ///
+  /// Check if the smeller could step onto `pos`. The player's tile counts, since walking into them is an attack
+  ///
+  fn can_step(ctx: &Context, pos: Pos) -> bool {
+    ctx.in_bounds(pos)
+      && tile::walkable(&ctx.map[pos.x as usize][pos.y as usize])
+      && ctx.occupancy.get(pos).map_or(true, |occupant| occupant == Occupant::Player)
+  }
 
-      // Move x
-      if x < tx as usize {
-        x += 1;
-        state = Actions::Move;
-      } else if x > tx as usize {
-        x -= 1;
-        state = Actions::Move;
-      }
+}
 
-      // Check
-      if !tile::walkable(&map[x][y]) {
-        x = tx as usize;
-        state = Actions::Move;
-      }
+impl AI for SmellerAI {
 
-      // Move y
-      if y < ty as usize{
-        y += 1;
-      } else if y > ty as usize {
-        y -= 1;
-      }
+  ///
+  /// Follow the gradient of whatever it likes the smell of, or walk around randomly until it picks up a scent
+  ///
+  fn take_turn(&mut self, ctx: &mut Context, me: &mut Actor, _stats: &mut Stats) -> Actions {
 
-      // Check
-      if !tile::walkable(&map[x][y]) {
-        y = ty as usize;
-        state = Actions::Move;
-      }
+    let smells = self.smell(ctx, me.pos);
 
-      me.pos.x = x as isize;
-      me.pos.y = y as isize;
-      
-    } else {
-      
-      // Otherwise behave like a simple ai and walk around randomly
-      loop {
-
-        let mut rng = rand::thread_rng();
-        let dice : usize;
-        state = Actions::Move;
-        
-        let mut count : usize = 0;
-
-        count += 1;
-        x = me.pos.x as usize;
-        y = me.pos.y as usize;
-        dice = rng.gen_range(1, 5);
-
-        // Match dice for movement
-        match dice {
-          1 => x += 1,
-          2 => x -= 1,
-          3 => y += 1,
-          4 => y -= 1,
-          _ => unreachable!("SimpleAI - Unreachable dice state reached in movement")
-        }
+    if smells.is_empty() {
+      return ctx.wander(me);
+    }
 
-        // Since the only thing this thing can do is move, there is no need to match the dice again to determine state
-        
-        // If we find a good tile, great, otherwise keep trying until we get tired of it
-        if tile::walkable(&map[x][y]) {
-          break;
-        } else if count > RANDOM_TRIES {
-          x = me.pos.x as usize;
-          y = me.pos.y as usize;
-          state = Actions::Wait;
-          break; 
+    // Strongest smells first, and the closest of those if there's a tie
+    let near = |pos: Pos| -> isize { -((pos.x - me.pos.x).pow(2) + (pos.y - me.pos.y).pow(2)) };
+    let target = smells.iter().filter(|&&(_, score)| score > 0).max_by_key(|&&(pos, score)| (score, near(pos))).map(|&(pos, _)| pos);
+    let threat = smells.iter().filter(|&&(_, score)| score < 0).max_by_key(|&&(pos, score)| (-score, near(pos))).map(|&(pos, _)| pos);
+
+    // How good it is to stand at `pos`
+    let pull = |pos: Pos| -> f32 {
+      threat.map_or(0.0, |threat| pos ^ threat) - target.map_or(0.0, |target| pos ^ target)
+    };
+
+    let mut best = me.pos;
+    let mut best_pull = pull(me.pos);
+
+    for dir in path::DIRECTIONS.iter() {
+      let next = me.pos + *dir;
+      if SmellerAI::can_step(ctx, next) {
+        let next_pull = pull(next);
+        if next_pull > best_pull {
+          best = next;
+          best_pull = next_pull;
         }
-
       }
-      
-      me.pos.x = x as isize;
-      me.pos.y = y as isize;
+    }
 
+    if best == me.pos {
+      return Actions::Wait;
     }
 
-    return state;
+    me.pos = best;
+    return Actions::Move;
 
   }
 
@@ -147,4 +139,11 @@ impl AI for SmellerAI {
     Box::new((*self).clone())
   }
 
-}
\ No newline at end of file
+  ///
+  /// Allow Box<AI> saving
+  ///
+  fn save(&self) -> SavedAI {
+    SavedAI::Smeller((*self).clone())
+  }
+
+}
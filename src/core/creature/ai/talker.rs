@@ -3,16 +3,15 @@ extern crate rand;
 use self::rand::Rng;
 
 use core::log;
-use core::world::dungeon::map::{self, Tile};
 use core::renderer::Renderable;
 
-use super::{AI, TALK_DISTANCE};
-use core::creature::{Actions, Creature, Actor, Stats};
+use super::{AI, Context, SavedAI, TALK_DISTANCE};
+use core::creature::{Actions, Actor, Stats};
 
 ///
 /// AI that talks to the player
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct TalkerAI;
 
 impl TalkerAI {
@@ -27,17 +26,16 @@ impl AI for TalkerAI {
   ///
   /// Talk to player if near
   ///
-  fn take_turn(&mut self, _map: &map::Grid<Tile>, player: &Creature, me: &mut Actor, _stats: &mut Stats) -> Actions {
+  fn take_turn(&mut self, ctx: &mut Context, me: &mut Actor, _stats: &mut Stats) -> Actions {
 
     let mut state = Actions::Wait;
 
     // ^ is overridden to be the distance formula, this isn't xor
-    let distance = me.pos ^ player.actor.pos;
+    let distance = me.pos ^ ctx.player.actor.pos;
 
     if distance < TALK_DISTANCE {
 
-      let mut rng = rand::thread_rng();
-      let dice : i32 = rng.gen_range(1, 15);
+      let dice : i32 = ctx.rng.gen_range(1, 15);
 
       // Match dice for voiceline
       match dice {
@@ -68,4 +66,11 @@ impl AI for TalkerAI {
     Box::new((*self).clone())
   }
 
+  ///
+  /// Allow Box<AI> saving
+  ///
+  fn save(&self) -> SavedAI {
+    SavedAI::Talker((*self).clone())
+  }
+
 }
\ No newline at end of file
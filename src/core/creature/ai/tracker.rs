@@ -1,12 +1,17 @@
-use core::world::dungeon::map::{self, tile, Tile};
+use core::world::dungeon::map::path;
 
-use super::AI;
-use core::creature::{Actions, Creature, Actor, Stats};
+use super::{AI, Context, SavedAI};
+use core::creature::{Actions, Actor, Stats};
 
 ///
-/// AI that tracks player
+/// How close the player has to be for a tracker to notice them
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+const TRACK_DISTANCE : f32 = 20.0;
+
+///
+/// AI that tracks player, finding a path around anything in the way
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct TrackerAI;
 
 impl TrackerAI {
@@ -21,51 +26,23 @@ impl AI for TrackerAI {
   ///
   /// Track player and follow if near
   ///
-  fn take_turn(&mut self, map: &map::Grid<Tile>, player: &Creature, me: &mut Actor, _stats: &mut Stats) -> Actions {
-
-    let mut state = Actions::Wait;
+  fn take_turn(&mut self, ctx: &mut Context, me: &mut Actor, _stats: &mut Stats) -> Actions {
 
     // ^ is overridden to be the distance formula, this isn't xor
-    let distance = me.pos ^ player.actor.pos;
-    let mut x = me.pos.x;
-    let mut y = me.pos.y;
-
-    if distance < 20.0 && distance > 2.00 {
-
-      // Move x
-      if x < player.actor.pos.x {
-        x += 1;
-        state = Actions::Move;
-      } else if x > player.actor.pos.x {
-        x -= 1;
-        state = Actions::Move;
-      }
-
-      // Check
-      if !tile::walkable(&map[x as usize][y as usize]) {
-        x = me.pos.x;
-      }
-
-      // Move y
-      if y < player.actor.pos.y {
-        y += 1;
-        state = Actions::Move;
-      } else if y > player.actor.pos.y {
-        y -= 1;
-        state = Actions::Move;
-      }
-
-      // Check
-      if !tile::walkable(&map[x as usize][y as usize]) {
-        y = me.pos.y
-      }
+    let distance = me.pos ^ ctx.player.actor.pos;
 
+    if distance >= TRACK_DISTANCE {
+      return Actions::Wait;
     }
-    
-    me.pos.x = x as isize;
-    me.pos.y = y as isize;
 
-    return state;
+    // Follow the path around whatever is in the way. Closing all the way in is fine, walking into the player is an attack
+    match path::astar(ctx.map, Some(ctx.occupancy), me.pos, ctx.player.actor.pos) {
+      Some(ref steps) if !steps.is_empty() => {
+        me.pos = steps[0];
+        return Actions::Move;
+      },
+      _ => return Actions::Wait
+    }
 
   }
 
@@ -76,4 +53,11 @@ impl AI for TrackerAI {
     Box::new((*self).clone())
   }
 
+  ///
+  /// Allow Box<AI> saving
+  ///
+  fn save(&self) -> SavedAI {
+    SavedAI::Tracker((*self).clone())
+  }
+
 }
\ No newline at end of file
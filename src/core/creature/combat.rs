@@ -0,0 +1,128 @@
+//!
+//! Melee combat between creatures
+//!
+//! Attacks roll a d20 plus the attacker's attack against 10 plus the defender's defense. Hits deal whatever the attacker's
+//! weapon rolls, less half of the defender's defense, but always at least 1. Without a weapon they deal somewhere between
+//! 1 and the attacker's attack plus one instead. Thrown things hit the same way, and deal whatever their dice roll.
+//!
+
+extern crate rand;
+use self::rand::Rng;
+
+use std::cmp;
+
+use core::creature::{Actions, Creature, Stats};
+use core::item::{Dice, ItemProperty};
+use core::renderer::Renderable;
+
+///
+/// What happened when one creature attacked another
+///
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Outcome {
+  Miss,
+  // Damage dealt
+  Hit(usize),
+  // Damage dealt by the killing blow
+  Kill(usize)
+}
+
+///
+/// Roll an attack from `attacker` against `defender`, taking any damage out of the defender's hp. Hits deal `damage` if
+/// there are dice to roll, and whatever the attacker can manage with their bare hands if there aren't
+///
+pub fn strike<R: Rng>(attacker: &Stats, damage: Option<&Dice>, defender: &mut Stats, rng: &mut R) -> Outcome {
+
+  if rng.gen_range(1, 21) + attacker.attack < 10 + defender.defense {
+    return Outcome::Miss;
+  }
+
+  let rolled = match damage {
+    Some(dice) => dice.roll(rng),
+    None => rng.gen_range(1, attacker.attack + 2)
+  };
+
+  let damage = cmp::max(1, rolled as isize - (defender.defense / 2) as isize) as usize;
+  defender.hp = defender.hp.saturating_sub(damage);
+
+  if defender.hp == 0 {
+    return Outcome::Kill(damage);
+  } else {
+    return Outcome::Hit(damage);
+  }
+
+}
+
+///
+/// Roll an unarmed attack from `attacker` against `defender`, taking any damage out of the defender's hp
+///
+pub fn roll<R: Rng>(attacker: &Stats, defender: &mut Stats, rng: &mut R) -> Outcome {
+  strike(attacker, None, defender, rng)
+}
+
+///
+/// Roll a throw from `thrower` against `defender`. Hitting works the same as in melee, but the damage comes from whatever
+/// was thrown
+///
+pub fn hurl<R: Rng>(thrower: &Stats, damage: &Dice, defender: &mut Stats, rng: &mut R) -> Outcome {
+  strike(thrower, Some(damage), defender, rng)
+}
+
+///
+/// Have one creature attack another, marking the defender as dead if it was killed
+///
+pub fn melee<R: Rng>(attacker: &mut Creature, defender: &mut Creature, rng: &mut R) -> Outcome {
+
+  attacker.state = Actions::Attack(defender.actor.pos);
+
+  // Fight with everything they have on, but only the defender's actual health takes the damage
+  let mut defense = defender.effective_stats();
+  let outcome = strike(&attacker.effective_stats(), weapon_damage(attacker).as_ref(), &mut defense, rng);
+  defender.stats.hp = defense.hp;
+
+  if let Outcome::Kill(_) = outcome {
+    defender.state = Actions::Die;
+  }
+
+  return outcome;
+
+}
+
+///
+/// Get the dice a creature's wielded weapon hits for, if it's wielding one
+///
+pub fn weapon_damage(creature: &Creature) -> Option<Dice> {
+  match creature.equipment.weapon {
+    Some(ref item) => match item.property {
+      ItemProperty::Weapon(ref damage) => Some(*damage),
+      _ => None
+    },
+    None => None
+  }
+}
+
+///
+/// Refer to a creature in a sentence. Names that start with a capital are proper nouns, like "Kurt"
+///
+pub fn the(creature: &Creature) -> String {
+  let name = creature.actor.get_id();
+  if name.starts_with(char::is_uppercase) {
+    name.to_string()
+  } else {
+    format!("the {}", name)
+  }
+}
+
+///
+/// Refer to a creature in a sentence the first time it comes up, like "a rat" or "an ant". Proper nouns stay as they are
+///
+pub fn a(creature: &Creature) -> String {
+  let name = creature.actor.get_id();
+  if name.starts_with(char::is_uppercase) {
+    name.to_string()
+  } else if name.starts_with(|c: char| "aeiou".contains(c)) {
+    format!("an {}", name)
+  } else {
+    format!("a {}", name)
+  }
+}
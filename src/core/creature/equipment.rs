@@ -0,0 +1,168 @@
+//!
+//! What a creature has on, as opposed to what it's just carrying around
+//!
+//! A creature has a slot for each kind of thing it can use at once: a weapon, armor, a ring, and a light. Equipped items
+//! never touch the creature's base `Stats`. Instead, `Equipment::apply()` works out what the stats are with everything
+//! on, so taking something off can never leave a creature stronger or weaker than it started.
+//!
+
+use std::fmt;
+
+use core::creature::Stats;
+use core::item::{Item, ItemProperty, Ring};
+
+///
+/// Places to put things on
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Slot {
+  Weapon,
+  Armor,
+  Ring,
+  Light
+}
+
+// Implement ability to turn the enum into a string
+impl fmt::Display for Slot {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Slot::Weapon => write!(fmt, "Weapon"),
+      Slot::Armor  => write!(fmt, "Armor"),
+      Slot::Ring   => write!(fmt, "Ring"),
+      Slot::Light  => write!(fmt, "Light")
+    }
+  }
+}
+
+impl Slot {
+
+  ///
+  /// Every slot, in the order they're listed
+  ///
+  pub fn all() -> [Slot; 4] {
+    [Slot::Weapon, Slot::Armor, Slot::Ring, Slot::Light]
+  }
+
+  ///
+  /// Which slot `item` goes in, if it can be equipped at all
+  ///
+  pub fn of(item: &Item) -> Option<Slot> {
+    match item.property {
+      ItemProperty::Weapon(_) => Some(Slot::Weapon),
+      ItemProperty::Armor(_) => Some(Slot::Armor),
+      ItemProperty::Ring(_) => Some(Slot::Ring),
+      ItemProperty::Light(_, _) => Some(Slot::Light),
+      _ => None
+    }
+  }
+
+  ///
+  /// What putting something in the slot is called
+  ///
+  pub fn verb(&self) -> &'static str {
+    match *self {
+      Slot::Weapon => "wield",
+      Slot::Armor => "wear",
+      Slot::Ring => "put on",
+      Slot::Light => "hold up"
+    }
+  }
+
+}
+
+///
+/// Everything a creature has on
+///
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Equipment {
+  pub weapon: Option<Item>,
+  pub armor: Option<Item>,
+  pub ring: Option<Item>,
+  pub light: Option<Item>
+}
+
+impl Equipment {
+
+  ///
+  /// Get whatever is in `slot`
+  ///
+  pub fn get(&self, slot: Slot) -> Option<&Item> {
+    match slot {
+      Slot::Weapon => self.weapon.as_ref(),
+      Slot::Armor => self.armor.as_ref(),
+      Slot::Ring => self.ring.as_ref(),
+      Slot::Light => self.light.as_ref()
+    }
+  }
+
+  ///
+  /// Get the slot itself, to put things in or take them out
+  ///
+  fn slot_mut(&mut self, slot: Slot) -> &mut Option<Item> {
+    match slot {
+      Slot::Weapon => &mut self.weapon,
+      Slot::Armor => &mut self.armor,
+      Slot::Ring => &mut self.ring,
+      Slot::Light => &mut self.light
+    }
+  }
+
+  ///
+  /// Put `item` on, returning whatever it replaced. Items that can't be equipped come straight back as an error
+  ///
+  pub fn equip(&mut self, item: Item) -> Result<Option<Item>, Item> {
+    match Slot::of(&item) {
+      Some(slot) => Ok(self.slot_mut(slot).replace(item)),
+      None => Err(item)
+    }
+  }
+
+  ///
+  /// Take off whatever is in `slot`
+  ///
+  pub fn remove(&mut self, slot: Slot) -> Option<Item> {
+    self.slot_mut(slot).take()
+  }
+
+  ///
+  /// Every slot with something in it, in order
+  ///
+  pub fn worn(&self) -> Vec<(Slot, &Item)> {
+    Slot::all().iter().filter_map(|slot| self.get(*slot).map(|item| (*slot, item))).collect()
+  }
+
+  ///
+  /// How much everything equipped weighs
+  ///
+  pub fn weight(&self) -> usize {
+    self.worn().iter().map(|&(_, item)| item.total_weight()).sum()
+  }
+
+  ///
+  /// Work out what `base` stats come to with everything equipped
+  ///
+  /// Weapons add half the most their dice can roll to attack, armor adds to defense, rings add to whatever they're for,
+  /// and everything adds its weight
+  ///
+  pub fn apply(&self, base: &Stats) -> Stats {
+
+    let mut stats = base.clone();
+
+    for (_, item) in self.worn() {
+      match item.property {
+        ItemProperty::Weapon(ref damage) => stats.attack += damage.max() / 2,
+        ItemProperty::Armor(armor) => stats.defense += armor,
+        ItemProperty::Ring(Ring::Strength(attack)) => stats.attack += attack,
+        ItemProperty::Ring(Ring::Protection(defense)) => stats.defense += defense,
+        ItemProperty::Ring(Ring::Speed(speed)) => stats.speed += speed,
+        _ => {}
+      }
+    }
+
+    stats.weight += self.weight();
+
+    return stats;
+
+  }
+
+}
@@ -14,23 +14,56 @@ pub mod actor;
 pub use self::actor::Actor;
 
 pub mod stats;
-pub use self::stats::Stats;
+pub use self::stats::{Stats, NORMAL_SPEED};
+
+pub mod registry;
+
+pub mod combat;
+
+pub mod equipment;
+pub use self::equipment::{Equipment, Slot};
 
 mod object_tests;
 
 use core::item::Item;
 use core::renderer::RGB;
-use core::world::dungeon::map::{self, Pos, tile, Tile};
+use core::world::dungeon::map::Pos;
+use core::world::occupancy::Collision;
+
+///
+/// How many different things a creature can carry, one for each letter they're picked with
+///
+pub const INVENTORY_SLOTS : usize = 26;
+
+///
+/// How much weight a creature can carry
+///
+pub const CARRY_LIMIT : usize = 50;
+
+///
+/// How many times quieter sneaking creatures are
+///
+pub const SNEAK_QUIETNESS : usize = 4;
 
 ///
 /// Creature holds a `Actor` and an `AI`, basically a package that we can create monsters from
 ///
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Creature {
   pub actor: Actor,
   pub stats: Stats,
   pub state: Actions,
 
+  // Energy builds up over time and gets spent on actions. Creatures can act whenever it isn't negative
+  pub energy: isize,
+
+  // What happens when something walks into this creature
+  pub collision: Collision,
+
+  // Sneaking creatures move slower, but make much less noise
+  #[serde(default)]
+  pub sneaking: bool,
+
   // Q: Wait, an AI trait object is clonable?
   // A: A *Boxed* AI trait object is clonable, as pointers to objects are clonable
   pub ai: Box<ai::AI>,
@@ -39,7 +72,10 @@ pub struct Creature {
   // Hold money
   pub wallet: f32,
   // Hold other items
-  pub inventory: Vec<Item>
+  pub inventory: Vec<Item>,
+  // What it has on
+  #[serde(default)]
+  pub equipment: Equipment
 }
 
 impl Creature {
@@ -66,14 +102,94 @@ impl Creature {
       stats: Stats, 
       ai: T
     ) -> Self {
+    Creature::new_boxed(name, glyph, pos, fg, bg, stats, Box::new(ai))
+  }
+
+  ///
+  /// Create a new `Creature` from an `AI` that's already boxed
+  ///
+  /// Useful when the AI isn't known until runtime, like when it comes from a monster definition
+  ///
+  pub fn new_boxed(
+      name: &'static str, 
+      glyph: char, 
+      pos: Pos, 
+      fg: RGB, 
+      bg: RGB, 
+      stats: Stats, 
+      ai: Box<ai::AI>
+    ) -> Self {
     Creature {
       actor: Actor::new(name, glyph, pos, fg, bg),
       stats: stats,
       state: Actions::Unknown,
-      ai: Box::new(ai),
+      energy: 0,
+      collision: Collision::Block,
+      sneaking: false,
+      ai: ai,
       wallet: 0.0,
-      inventory: vec![]
+      inventory: vec![],
+      equipment: Equipment::default()
+    }
+  }
+
+  ///
+  /// The creature's stats with everything it has on
+  ///
+  pub fn effective_stats(&self) -> Stats {
+    self.equipment.apply(&self.stats)
+  }
+
+  ///
+  /// How loud the creature's footsteps are. Heavier things make more noise walking around, unless they're sneaking
+  ///
+  pub fn footsteps(&self) -> usize {
+    let weight = self.effective_stats().weight;
+    if self.sneaking { weight / SNEAK_QUIETNESS } else { weight }
+  }
+
+  ///
+  /// How much everything the creature is carrying weighs, including what it has on
+  ///
+  pub fn carried_weight(&self) -> usize {
+    self.inventory.iter().map(|item| item.total_weight()).sum::<usize>() + self.equipment.weight()
+  }
+
+  ///
+  /// Whether the creature has room for `item`, and why not if it doesn't
+  ///
+  pub fn can_carry(&self, item: &Item) -> Result<(), String> {
+    if self.carried_weight() + item.total_weight() > CARRY_LIMIT {
+      return Err(format!("You can't carry {}, it's too heavy", item.describe()));
+    }
+    if self.inventory.len() >= INVENTORY_SLOTS && !self.inventory.iter().any(|held| held.stacks_with(item)) {
+      return Err(format!("You have no room for {}", item.describe()));
+    }
+    Ok(())
+  }
+
+  ///
+  /// Put `item` in the inventory, on top of a stack of the same thing if there is one
+  ///
+  /// Doesn't check whether there's room, which is what `can_carry()` is for
+  ///
+  pub fn pick_up(&mut self, item: Item) {
+    match self.inventory.iter_mut().find(|held| held.stacks_with(&item)) {
+      Some(held) => held.quantity += item.quantity,
+      None => self.inventory.push(item)
+    }
+  }
+
+  ///
+  /// Take whatever is in the `index`th inventory slot out of the inventory, leaving it at the creature's feet
+  ///
+  pub fn drop_item(&mut self, index: usize) -> Option<Item> {
+    if index >= self.inventory.len() {
+      return None;
     }
+    let mut item = self.inventory.remove(index);
+    item.pos = self.actor.pos;
+    Some(item)
   }
 
   ///
@@ -81,8 +197,8 @@ impl Creature {
   ///
   /// Essentially allows us to not need to include `AI` when we need to `take_turn()`
   ///
-  pub fn take_turn(&mut self, map: &map::Grid<Tile>, player: &Creature) {
-    self.state = self.ai.take_turn(map, player, &mut self.actor, &mut self.stats);
+  pub fn take_turn(&mut self, ctx: &mut ai::Context) {
+    self.state = self.ai.take_turn(ctx, &mut self.actor, &mut self.stats);
   }
 
 }
\ No newline at end of file
@@ -1,2 +1,411 @@
 #[cfg(test)]
-mod tests {}
+mod tests {
+
+  use core::creature::{combat, Actions, Creature, Slot, Stats, SNEAK_QUIETNESS};
+  use core::creature::ai::{AI, Context, ListenerAI, SavedAI, ScavengerAI, SimpleAI, SmellerAI};
+  use core::creature::ai::listener::Awareness;
+  use core::creature::registry::Registry;
+  use core::data_tests;
+  use core::renderer::{Renderable, RGB};
+  use core::rng::GameRng;
+  use core::world::dungeon::map::{tile, Pos};
+  use core::init;
+  use core::item::{Dice, Item, ItemProperty, Money};
+  use core::world::desire::DesireMaps;
+  use core::world::scent::ScentMap;
+  use core::world::occupancy::{Collision, Occupancy};
+
+  // The monsters that ship with the game should all load
+  #[test]
+  fn shipped_monsters_load() {
+    let registry = Registry::load("monsters").unwrap();
+    assert!(registry.monsters().iter().any(|monster| monster.id == "ant"));
+  }
+
+  // A good definition spawns a creature that looks like it
+  #[test]
+  fn spawn_from_definition() {
+    let registry = data_tests::one_file(Registry::from_files, r#"{
+      "newt": { "name": "newt", "glyph": ":", "fg": [255, 255, 0], "bg": [0, 0, 0], "scent": "Reptilian", "ai": "simple", "hp": 5, "weight": 3, "collision": "Push" }
+    }"#).unwrap();
+
+    let newt = registry.monsters()[0].spawn(Pos::new(4, 2));
+    assert_eq!(newt.actor.get_id(), "newt");
+    assert_eq!(newt.actor.get_glyph(), ':');
+    assert_eq!(newt.actor.pos, Pos::new(4, 2));
+    assert_eq!(newt.stats.weight, 3);
+    assert_eq!(newt.stats.scent_type, tile::Scent::Reptilian);
+    assert_eq!(newt.collision, Collision::Push);
+  }
+
+  // Bad definitions say what's wrong with them, including how the monster is meant to think and smell
+  #[test]
+  fn bad_definitions() {
+    data_tests::bad_definitions(Registry::from_files, "newt", r#"{ "name": "newt", "glyph": ":", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "reptilian", "ai": "simple", "hp": 1 }"#);
+
+    let bad_ai = data_tests::one_file(Registry::from_files, r#"{ "newt": { "name": "newt", "glyph": ":", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "reptilian", "ai": "napping", "hp": 1 } }"#);
+    assert!(bad_ai.err().unwrap().contains("unknown ai \"napping\""));
+
+    let bad_scent = data_tests::one_file(Registry::from_files, r#"{ "newt": { "name": "newt", "glyph": ":", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "newty", "ai": "simple", "hp": 1 } }"#);
+    assert!(bad_scent.err().unwrap().contains("unknown scent \"newty\""));
+  }
+
+  // Spawn tables only have what's allowed at that biome and depth
+  #[test]
+  fn spawn_tables() {
+    let monsters = data_tests::one_file(Registry::from_files, r#"{
+      "newt": {
+        "name": "newt", "glyph": ":", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "reptilian", "ai": "simple", "hp": 1,
+        "spawns": [{ "biome": "Sunken", "min_depth": 1, "max_depth": 3 }]
+      },
+      "rat": { "name": "rat", "glyph": "r", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "decay", "ai": "simple", "hp": 1, "spawns": [{}] },
+      "ghost": { "name": "ghost", "glyph": "G", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "decay", "ai": "simple", "hp": 1 }
+    }"#).unwrap();
+
+    // Only rats live in caves, and ghosts never spawn
+    assert_eq!(data_tests::count_picks(&monsters.spawn_table(&tile::Biome::Cave, 2), 20, |pick| pick.0.id == "rat"), 20);
+
+    // Newts need the right depth too
+    assert_eq!(data_tests::count_picks(&monsters.spawn_table(&tile::Biome::Sunken, 0), 20, |pick| pick.0.id == "rat"), 20);
+    let newts = data_tests::count_picks(&monsters.spawn_table(&tile::Biome::Sunken, 2), 100, |pick| pick.0.id == "newt");
+    assert!(newts > 0 && newts < 100);
+
+    let bad_count = data_tests::one_file(Registry::from_files, r#"{ "newt": { "name": "newt", "glyph": ":", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "reptilian", "ai": "simple", "hp": 1, "spawns": [{ "count": [3, 1] }] } }"#);
+    assert!(bad_count.err().unwrap().contains("spawn count"));
+  }
+
+  // Attacks hit and kill based on stats
+  #[test]
+  fn combat_rolls() {
+    let mut rng = GameRng::new(0);
+
+    // Nothing this strong can miss, and everything it hits dies
+    let strong = Stats::new(10, 100, 0, 0, 0, tile::Scent::Player);
+    let mut weak = Stats::new(10, 0, 0, 0, 0, tile::Scent::Insectoid);
+    match combat::roll(&strong, &mut weak, &mut rng) {
+      combat::Outcome::Kill(_) => assert_eq!(weak.hp, 0),
+      outcome => panic!("Expected a kill, got {:?}", outcome)
+    }
+
+    // And nothing this weak can ever hit something this well defended
+    let mut tough = Stats::new(10, 0, 100, 0, 0, tile::Scent::Insectoid);
+    for _ in 0..100 {
+      assert_eq!(combat::roll(&weak, &mut tough, &mut rng), combat::Outcome::Miss);
+    }
+    assert_eq!(tough.hp, 10);
+  }
+
+  // Killing a creature marks it as dead
+  #[test]
+  fn melee_kills() {
+    let mut rng = GameRng::new(0);
+    let mut attacker = Creature::new("hero", '@', Pos::new(0, 0), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(10, 100, 0, 0, 0, tile::Scent::Player), SimpleAI::new());
+    let mut defender = Creature::new("rat", 'r', Pos::new(1, 0), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(1, 0, 0, 0, 0, tile::Scent::Decay), SimpleAI::new());
+
+    combat::melee(&mut attacker, &mut defender, &mut rng);
+
+    assert_eq!(attacker.state, Actions::Attack(Pos::new(1, 0)));
+    assert_eq!(defender.state, Actions::Die);
+    assert_eq!(combat::the(&defender), "the rat");
+  }
+
+  // Wielded weapons hit for whatever their dice roll, and bare hands for whatever the attacker can manage
+  #[test]
+  fn melee_rolls_weapon_dice() {
+    let mut rng = GameRng::new(0);
+    let mut attacker = Creature::new("hero", '@', Pos::new(0, 0), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(10, 100, 0, 0, 0, tile::Scent::Player), SimpleAI::new());
+    let mut defender = Creature::new("rat", 'r', Pos::new(1, 0), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(1000, 0, 0, 0, 0, tile::Scent::Decay), SimpleAI::new());
+    assert_eq!(combat::weapon_damage(&attacker), None);
+
+    let club = Item::new("club", ')', Pos::new(0, 0), RGB(0, 0, 0), RGB(0, 0, 0), 1, ItemProperty::Weapon(Dice::new(1, 1, 49)));
+    assert!(attacker.equipment.equip(club).is_ok());
+    assert_eq!(combat::weapon_damage(&attacker), Some(Dice::new(1, 1, 49)));
+
+    for _ in 0..10 {
+      assert_eq!(combat::melee(&mut attacker, &mut defender, &mut rng), combat::Outcome::Hit(50));
+    }
+    assert_eq!(defender.stats.hp, 500);
+  }
+
+
+  fn rat(x: isize, y: isize) -> Box<Creature> {
+    Box::new(Creature::new("rat", 'r', Pos::new(x, y), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(1, 0, 0, 10, 0, tile::Scent::Decay), SimpleAI::new()))
+  }
+
+  // AIs can see the rest of the floor, but not themselves or the dead
+  #[test]
+  fn context_perception() {
+    let mut rng = GameRng::new(0);
+    let mut map = vec![vec![tile::generic_floor(); 10]; 10];
+    map[3][3].sound = 7;
+
+    let player = Creature::new("hero", '@', Pos::new(9, 9), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(10, 0, 0, 10, 0, tile::Scent::Player), SimpleAI::new());
+    let mut creatures = vec![rat(2, 2), rat(2, 3), rat(8, 8), rat(3, 2)];
+    creatures[3].state = Actions::Die;
+    let items = vec![Item::new("coin", '$', Pos::new(2, 4), RGB(0, 0, 0), RGB(0, 0, 0), 1, ItemProperty::Money(Money::Gold))];
+    let occupancy = Occupancy::build(10, 10, &player, &creatures);
+
+    let mut me = creatures[0].clone();
+    let mut ctx = Context {
+      map: &map,
+      player: &player,
+      creatures: &creatures,
+      items: &items,
+      occupancy: &occupancy,
+      desire: &DesireMaps::default(),
+      scent: &ScentMap::default(),
+      shop: None,
+      floor_num: 2,
+      me: Some(0),
+      rng: &mut rng
+    };
+
+    let others : Vec<usize> = ctx.others().iter().map(|&(idx, _)| idx).collect();
+    assert_eq!(others, vec![1, 2]);
+    let near : Vec<usize> = ctx.creatures_near(Pos::new(2, 2), 2.0).iter().map(|&(idx, _)| idx).collect();
+    assert_eq!(near, vec![1]);
+    assert_eq!(ctx.items_near(Pos::new(2, 2), 2.0).len(), 1);
+    assert_eq!(ctx.sound_at(Pos::new(3, 3)), 7);
+    assert_eq!(ctx.sound_at(Pos::new(-1, 3)), 0);
+    assert!(!ctx.is_open(Pos::new(2, 3)));
+    assert!(ctx.is_open(Pos::new(3, 2)));
+
+    // Wandering never walks into anybody
+    me.take_turn(&mut ctx);
+    assert!(me.actor.pos != Pos::new(2, 3));
+  }
+
+
+  // Smellers can be told what they like and dislike the smell of
+  #[test]
+  fn smell_definitions() {
+    let monsters = data_tests::one_file(Registry::from_files, r#"{
+      "wolf": {
+        "name": "wolf", "glyph": "w", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "canine", "ai": "smeller", "hp": 1,
+        "smell": { "attract": ["player"], "repel": ["Smoke"], "radius": 4 }
+      }
+    }"#).unwrap();
+    let wolf = monsters.monsters()[0].spawn(Pos::new(0, 0));
+    match wolf.ai.save() {
+      SavedAI::Smeller(ai) => assert_eq!(ai, SmellerAI::with_scents(vec![tile::Scent::Player], vec![tile::Scent::Smoke], 4)),
+      _ => panic!("Expected a smeller")
+    }
+
+    let bad_scent = data_tests::one_file(Registry::from_files, r#"{ "wolf": { "name": "wolf", "glyph": "w", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "canine", "ai": "smeller", "hp": 1, "smell": { "attract": ["sheep"] } } }"#);
+    assert!(bad_scent.err().unwrap().contains("unknown scent \"sheep\" in smell"));
+
+    let not_smeller = data_tests::one_file(Registry::from_files, r#"{ "wolf": { "name": "wolf", "glyph": "w", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "canine", "ai": "simple", "hp": 1, "smell": {} } }"#);
+    assert!(not_smeller.err().unwrap().contains("only the smeller ai can have a smell"));
+  }
+
+  // Let a smeller at `from` take a turn on an open 10x10 map where the player's scent gets stronger towards x = 9
+  fn sniff(mut smeller: SmellerAI, from: Pos) -> Pos {
+    let mut rng = GameRng::new(0);
+    let map = vec![vec![tile::generic_floor(); 10]; 10];
+    let mut scent = ScentMap::new(10, 10);
+    for x in 0..10 {
+      for y in 0..10 {
+        scent.leave(Pos::new(x, y), &tile::Scent::Player, (x * 10) as u8);
+      }
+    }
+
+    let player = Creature::new("hero", '@', Pos::new(9, 9), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(10, 0, 0, 10, 0, tile::Scent::Player), SimpleAI::new());
+    let occupancy = Occupancy::build(10, 10, &player, &[]);
+    let mut me = rat(from.x, from.y);
+    let mut ctx = Context {
+      map: &map,
+      player: &player,
+      creatures: &[],
+      items: &[],
+      occupancy: &occupancy,
+      desire: &DesireMaps::default(),
+      scent: &scent,
+      shop: None,
+      floor_num: 0,
+      me: None,
+      rng: &mut rng
+    };
+    smeller.take_turn(&mut ctx, &mut me.actor, &mut me.stats);
+    return me.actor.pos;
+  }
+
+  // Smellers head towards what they like and away from what they don't, right up to the edge of the map
+  #[test]
+  fn smellers_follow_gradients() {
+    let hunter = SmellerAI::with_scents(vec![tile::Scent::Player], vec![], 2);
+    let prey = SmellerAI::with_scents(vec![], vec![tile::Scent::Player], 2);
+
+    assert_eq!(sniff(hunter.clone(), Pos::new(4, 4)).x, 5);
+    assert_eq!(sniff(prey.clone(), Pos::new(4, 4)).x, 3);
+
+    // Nowhere to go but along the edge
+    assert_eq!(sniff(hunter.clone(), Pos::new(0, 0)).x, 1);
+    assert_eq!(sniff(prey.clone(), Pos::new(0, 0)).x, 0);
+    assert_eq!(sniff(hunter, Pos::new(8, 9)).x, 9);
+  }
+
+
+  // Let a listener at `from` take a turn on an open 10x10 map with a noise of `loudness` at (8, 5)
+  fn listen(listener: &mut ListenerAI, from: Pos, loudness: usize) -> Pos {
+    let mut rng = GameRng::new(0);
+    let mut map = vec![vec![tile::generic_floor(); 10]; 10];
+    for x in 0..10 {
+      for y in 0..10 {
+        map[x][y].sound = loudness.saturating_sub((Pos::new(x as isize, y as isize) ^ Pos::new(8, 5)) as usize);
+      }
+    }
+
+    let player = Creature::new("hero", '@', Pos::new(9, 9), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(10, 0, 0, 10, 0, tile::Scent::Player), SimpleAI::new());
+    let occupancy = Occupancy::build(10, 10, &player, &[]);
+    let mut me = rat(from.x, from.y);
+    let mut ctx = Context {
+      map: &map,
+      player: &player,
+      creatures: &[],
+      items: &[],
+      occupancy: &occupancy,
+      desire: &DesireMaps::default(),
+      scent: &ScentMap::default(),
+      shop: None,
+      floor_num: 0,
+      me: None,
+      rng: &mut rng
+    };
+    listener.take_turn(&mut ctx, &mut me.actor, &mut me.stats);
+    return me.actor.pos;
+  }
+
+  // Take a turn as a scavenger at `from` on an open floor with the player at `player` and something lying at `item`
+  fn scavenge(from: Pos, player: Pos, item: Pos) -> Pos {
+    let mut rng = GameRng::new(0);
+    let map = vec![vec![tile::generic_floor(); 20]; 20];
+    let player = Creature::new("hero", '@', player, RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(10, 0, 0, 10, 0, tile::Scent::Player), SimpleAI::new());
+    let items = vec![Item::new("coin", '$', item, RGB(0, 0, 0), RGB(0, 0, 0), 1, ItemProperty::Money(Money::Gold))];
+    let occupancy = Occupancy::build(20, 20, &player, &[]);
+    let desire = DesireMaps::build(&map, player.actor.pos, &[item]);
+    let mut me = rat(from.x, from.y);
+    let mut ctx = Context {
+      map: &map,
+      player: &player,
+      creatures: &[],
+      items: &items,
+      occupancy: &occupancy,
+      desire: &desire,
+      scent: &ScentMap::default(),
+      shop: None,
+      floor_num: 0,
+      me: None,
+      rng: &mut rng
+    };
+    ScavengerAI::new().take_turn(&mut ctx, &mut me.actor, &mut me.stats);
+    return me.actor.pos;
+  }
+
+  // Scavengers follow the desire maps to whatever is lying around, stay on it, and run once the player gets close
+  #[test]
+  fn scavengers_follow_desire() {
+    let item = Pos::new(10, 2);
+    let far = Pos::new(18, 18);
+
+    let step = scavenge(Pos::new(2, 2), far, item);
+    assert!((step ^ item) < (Pos::new(2, 2) ^ item));
+    assert_eq!(scavenge(item, far, item), item);
+
+    let near = Pos::new(11, 3);
+    let step = scavenge(item, near, item);
+    assert!((step ^ near) > (item ^ near));
+
+    // Nothing to go after and nothing to run from is just wandering
+    assert_ne!(scavenge(Pos::new(2, 2), far, Pos::new(-5, -5)), Pos::new(2, 2));
+  }
+
+  // Listeners sleep through quiet noises, wake to loud ones, and go looking for them until they get bored
+  #[test]
+  fn listeners_investigate() {
+    let mut listener = ListenerAI::with_hearing(2, 3, Awareness::Asleep);
+
+    // Loud enough to hear awake, but not loud enough to wake up to
+    assert_eq!(listen(&mut listener, Pos::new(2, 5), 10), Pos::new(2, 5));
+    assert_eq!(listener.awareness, Awareness::Asleep);
+
+    // Loud enough to wake up to, so off it goes
+    assert_eq!(listen(&mut listener, Pos::new(2, 5), 12).x, 3);
+    assert_eq!(listener.awareness, Awareness::Alert);
+
+    // Keeps going even once it's quiet, until it runs out of patience
+    assert_eq!(listen(&mut listener, Pos::new(3, 5), 0).x, 4);
+    assert_eq!(listen(&mut listener, Pos::new(4, 5), 0).x, 5);
+    assert_eq!(listen(&mut listener, Pos::new(5, 5), 0), Pos::new(5, 5));
+    assert_eq!(listener.awareness, Awareness::Awake);
+  }
+
+  // Footsteps are as loud as everything the creature has on, and quieter when it sneaks. The world and listeners both go
+  // by this
+  #[test]
+  fn footsteps_count_gear_and_sneaking() {
+    let mut creature = rat(0, 0);
+    creature.stats.weight = 8;
+    assert_eq!(creature.footsteps(), 8);
+
+    let mut armor = Item::new("plate", '[', Pos::new(0, 0), RGB(0, 0, 0), RGB(0, 0, 0), 1, ItemProperty::Armor(1));
+    armor.weight = 12;
+    assert!(creature.equipment.equip(armor).is_ok());
+    assert_eq!(creature.footsteps(), 20);
+
+    creature.sneaking = true;
+    assert_eq!(creature.footsteps(), 20 / SNEAK_QUIETNESS);
+  }
+
+  // Listeners can be told how well they hear
+  #[test]
+  fn hearing_definitions() {
+    let monsters = data_tests::one_file(Registry::from_files, r#"{
+      "bat": {
+        "name": "bat", "glyph": "b", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "avian", "ai": "listener", "hp": 1,
+        "hearing": { "threshold": 3, "asleep": true }
+      }
+    }"#).unwrap();
+    match monsters.monsters()[0].spawn(Pos::new(0, 0)).ai.save() {
+      SavedAI::Listener(ai) => {
+        assert_eq!(ai.hearing, 3);
+        assert_eq!(ai.awareness, Awareness::Asleep);
+      },
+      _ => panic!("Expected a listener")
+    }
+
+    let not_listener = data_tests::one_file(Registry::from_files, r#"{ "bat": { "name": "bat", "glyph": "b", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "avian", "ai": "simple", "hp": 1, "hearing": {} } }"#);
+    assert!(not_listener.err().unwrap().contains("only the listener ai can have hearing"));
+
+    let deaf = data_tests::one_file(Registry::from_files, r#"{ "bat": { "name": "bat", "glyph": "b", "fg": [0, 0, 0], "bg": [0, 0, 0], "scent": "avian", "ai": "listener", "hp": 1, "hearing": { "threshold": 0 } } }"#);
+    assert!(deaf.err().unwrap().contains("hearing threshold has to be at least 1"));
+  }
+
+  // Equipment changes what a creature fights with, but never its base stats
+  #[test]
+  fn equipment_modifies_stats() {
+    let mut creature = Creature::new("rat", 'r', Pos::new(0, 0), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(5, 1, 1, 10, 5, tile::Scent::Decay), SimpleAI::new());
+
+    assert!(creature.equipment.equip(init::items().create("short_sword", Pos::new(0, 0), 1)).unwrap().is_none());
+    assert!(creature.equipment.equip(init::items().create("leather_armor", Pos::new(0, 0), 1)).unwrap().is_none());
+    assert!(creature.equipment.equip(init::items().create("ring_of_speed", Pos::new(0, 0), 1)).unwrap().is_none());
+    assert!(creature.equipment.equip(init::items().create("ration", Pos::new(0, 0), 1)).is_err());
+
+    // 1d6+1 rolls at most 7, so the sword is worth 3 attack
+    let stats = creature.effective_stats();
+    assert_eq!(stats.attack, 4);
+    assert_eq!(stats.defense, 2);
+    assert_eq!(stats.speed, 13);
+    assert_eq!(stats.weight, 5 + 3 + 8 + 1);
+    assert_eq!(creature.stats.attack, 1);
+
+    // Putting on another sword swaps it for the first
+    let old = creature.equipment.equip(init::items().create("dagger", Pos::new(0, 0), 1)).unwrap().unwrap();
+    assert_eq!(old.get_id(), "short sword");
+    assert_eq!(creature.effective_stats().attack, 3);
+
+    assert!(creature.equipment.remove(Slot::Weapon).is_some());
+    assert!(creature.equipment.remove(Slot::Weapon).is_none());
+    assert_eq!(creature.effective_stats().attack, 1);
+  }
+
+}
@@ -0,0 +1,429 @@
+//!
+//! Monster definitions loaded from data files
+//!
+//! Every `.json` file in the `monsters` directory holds an object of monster definitions keyed by an id, like so:
+//!
+//! ```json
+//! {
+//!   "ant": {
+//!     "name": "ant",
+//!     "glyph": "a",
+//!     "fg": [255, 0, 0],
+//!     "bg": [0, 0, 0],
+//!     "scent": "insectoid",
+//!     "ai": "simple",
+//!     "hp": 3,
+//!     "attack": 1,
+//!     "weight": 1,
+//!     "spawns": [
+//!       { "biome": "Cave", "min_depth": 2, "weight": 10, "count": [2, 4] }
+//!     ]
+//!   }
+//! }
+//! ```
+//!
+//! Every monster needs some `hp`. `attack`, `defense` and `weight` can be left out, in which case they're 0. `speed` defaults
+//! to `NORMAL_SPEED`. Scents are any `tile::Scent` by
+//! name, and AIs are any of the names in `ai::from_name()`. Ids have to be unique across every file.
+//!
+//! `collision` says what happens when another creature walks into this one, and is any `Collision` by name - `"Block"`,
+//! `"Swap"` or `"Push"`. Monsters block by default.
+//!
+//! Monsters with the `"smeller"` AI can say what they hunt and what they avoid with `smell`, like so:
+//!
+//! ```json
+//! "smell": { "attract": ["player"], "repel": ["feline", "smoke"], "radius": 3 }
+//! ```
+//!
+//! Both lists are scents by name and default to nothing, and `radius` defaults to `ai::smeller::DEFAULT_SMELL_RADIUS`.
+//! Smellers without a `smell` follow insects around.
+//!
+//! Likewise, monsters with the `"listener"` AI can say how well they hear with `hearing`:
+//!
+//! ```json
+//! "hearing": { "threshold": 6, "patience": 20, "asleep": true }
+//! ```
+//!
+//! `threshold` is the quietest sound they notice, `patience` is how many turns they spend looking for a noise, and
+//! `asleep` is whether they start off asleep. They default to `ai::listener::DEFAULT_HEARING`,
+//! `ai::listener::DEFAULT_PATIENCE` and awake.
+//!
+//! # Spawning
+//!
+//! Each entry in `spawns` lets a monster appear on new floors. All of its fields are optional:
+//!
+//! * `biome` - Only spawn on tiles of this `tile::Biome`. Spawns in any biome if left out
+//! * `min_depth`, `max_depth` - Floors the monster can appear on, inclusive. Floor 0 is the first floor
+//! * `weight` - How likely the monster is to be picked compared to everything else that can spawn in the same place. Defaults to 1
+//! * `count` - The smallest and largest group the monster spawns in. Defaults to `[1, 1]`
+//!
+//! Monsters without any spawns never show up on their own. The `shopkeeper` is one of these, and only turns up minding a
+//! shop.
+//!
+
+extern crate serde_json;
+
+// Read data files
+use std::collections::BTreeMap;
+use std::usize;
+
+use core::creature::{ai, Creature, Stats, NORMAL_SPEED};
+use core::creature::ai::{ListenerAI, SmellerAI};
+use core::creature::ai::listener::Awareness;
+use core::data;
+use core::renderer::RGB;
+use core::save;
+use core::table::Table;
+use core::world::dungeon::map::{tile, Pos};
+use core::world::occupancy::Collision;
+
+///
+/// Where, when, and how many of a monster can spawn. See the module docs for what each field means
+///
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnRule {
+  #[serde(default)]
+  pub biome: Option<tile::Biome>,
+  #[serde(default)]
+  pub min_depth: usize,
+  #[serde(default = "SpawnRule::deepest")]
+  pub max_depth: usize,
+  #[serde(default = "SpawnRule::one")]
+  pub weight: usize,
+  #[serde(default = "SpawnRule::single")]
+  pub count: (usize, usize)
+}
+
+impl SpawnRule {
+
+  // Defaults for serde
+
+  fn deepest() -> usize {
+    usize::MAX
+  }
+
+  fn one() -> usize {
+    1
+  }
+
+  fn single() -> (usize, usize) {
+    (1, 1)
+  }
+
+  ///
+  /// Make sure the rule can actually be used
+  ///
+  pub fn check(&self) -> Result<(), String> {
+    if self.weight == 0 {
+      return Err("spawn weight has to be at least 1".to_string());
+    }
+    if self.min_depth > self.max_depth {
+      return Err(format!("spawn min_depth {} is deeper than max_depth {}", self.min_depth, self.max_depth));
+    }
+    if self.count.0 == 0 || self.count.0 > self.count.1 {
+      return Err(format!("spawn count [{}, {}] should be at least 1 and smallest first", self.count.0, self.count.1));
+    }
+    Ok(())
+  }
+
+  ///
+  /// Check if the rule allows spawning in `biome` at `depth`
+  ///
+  pub fn allows(&self, biome: &tile::Biome, depth: usize) -> bool {
+    self.biome.as_ref().map_or(true, |b| b == biome) && self.min_depth <= depth && depth <= self.max_depth
+  }
+
+}
+
+///
+/// What a smeller hunts and avoids, exactly as it's written in the file
+///
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSmell {
+  #[serde(default)]
+  attract: Vec<String>,
+  #[serde(default)]
+  repel: Vec<String>,
+  #[serde(default = "RawSmell::default_radius")]
+  radius: usize
+}
+
+impl RawSmell {
+
+  // Default for serde
+  fn default_radius() -> usize {
+    ai::smeller::DEFAULT_SMELL_RADIUS
+  }
+
+  ///
+  /// Check the scents, turning it into a `SmellerAI`
+  ///
+  fn to_ai(&self) -> Result<SmellerAI, String> {
+    let scents = |names: &Vec<String>| -> Result<Vec<tile::Scent>, String> {
+      names.iter().map(|name| tile::Scent::from_name(name).ok_or(format!("unknown scent \"{}\" in smell", name))).collect()
+    };
+    if self.radius == 0 {
+      return Err("smell radius has to be at least 1".to_string());
+    }
+    Ok(SmellerAI::with_scents(scents(&self.attract)?, scents(&self.repel)?, self.radius))
+  }
+
+}
+
+///
+/// How well a listener hears, exactly as it's written in the file
+///
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawHearing {
+  #[serde(default = "RawHearing::default_threshold")]
+  threshold: usize,
+  #[serde(default = "RawHearing::default_patience")]
+  patience: usize,
+  #[serde(default)]
+  asleep: bool
+}
+
+impl RawHearing {
+
+  // Defaults for serde
+
+  fn default_threshold() -> usize {
+    ai::listener::DEFAULT_HEARING
+  }
+
+  fn default_patience() -> usize {
+    ai::listener::DEFAULT_PATIENCE
+  }
+
+  ///
+  /// Check the hearing, turning it into a `ListenerAI`
+  ///
+  fn to_ai(&self) -> Result<ListenerAI, String> {
+    if self.threshold == 0 {
+      return Err("hearing threshold has to be at least 1".to_string());
+    }
+    let awareness = if self.asleep { Awareness::Asleep } else { Awareness::Awake };
+    Ok(ListenerAI::with_hearing(self.threshold, self.patience, awareness))
+  }
+
+}
+
+///
+/// A monster definition exactly as it's written in the file
+///
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawMonster {
+  name: String,
+  glyph: String,
+  fg: [u8; 3],
+  bg: [u8; 3],
+  scent: String,
+  ai: String,
+  hp: usize,
+  #[serde(default)]
+  attack: usize,
+  #[serde(default)]
+  defense: usize,
+  #[serde(default = "RawMonster::normal_speed")]
+  speed: usize,
+  #[serde(default)]
+  weight: usize,
+  #[serde(default)]
+  collision: Collision,
+  #[serde(default)]
+  smell: Option<RawSmell>,
+  #[serde(default)]
+  hearing: Option<RawHearing>,
+  #[serde(default)]
+  spawns: Vec<SpawnRule>
+}
+
+impl RawMonster {
+
+  // Default for serde
+  fn normal_speed() -> usize {
+    NORMAL_SPEED
+  }
+
+}
+
+///
+/// A checked monster definition that creatures can be spawned from
+///
+#[derive(Clone)]
+pub struct MonsterDef {
+  pub id: String,
+  pub name: &'static str,
+  pub glyph: char,
+  pub fg: RGB,
+  pub bg: RGB,
+  pub stats: Stats,
+  pub collision: Collision,
+  pub spawns: Vec<SpawnRule>,
+  // What a smeller hunts and avoids, if the definition said
+  smell: Option<SmellerAI>,
+  // How well a listener hears, if the definition said
+  hearing: Option<ListenerAI>,
+  // Name of the AI. Boxed AIs can't be shared between threads, so each creature gets a new one from the name instead
+  ai: String
+}
+
+impl MonsterDef {
+
+  ///
+  /// Check a raw definition, turning it into a `MonsterDef`
+  ///
+  fn from_raw(id: String, raw: RawMonster) -> Result<Self, String> {
+
+    if raw.name.is_empty() {
+      return Err(format!("{}: name can't be empty", id));
+    }
+
+    let mut glyph = raw.glyph.chars();
+    let glyph = match (glyph.next(), glyph.next()) {
+      (Some(ch), None) => ch,
+      _ => return Err(format!("{}: glyph should be a single character, not \"{}\"", id, raw.glyph))
+    };
+
+    let scent = tile::Scent::from_name(&raw.scent).ok_or(format!("{}: unknown scent \"{}\"", id, raw.scent))?;
+    if raw.hp == 0 {
+      return Err(format!("{}: hp has to be at least 1", id));
+    }
+
+    if raw.speed == 0 {
+      return Err(format!("{}: speed has to be at least 1", id));
+    }
+
+    if ai::from_name(&raw.ai).is_none() {
+      return Err(format!("{}: unknown ai \"{}\"", id, raw.ai));
+    }
+
+    let smell = match raw.smell {
+      Some(ref smell) if raw.ai == "smeller" => Some(smell.to_ai().map_err(|e| format!("{}: {}", id, e))?),
+      Some(_) => return Err(format!("{}: only the smeller ai can have a smell", id)),
+      None => None
+    };
+
+    let hearing = match raw.hearing {
+      Some(ref hearing) if raw.ai == "listener" => Some(hearing.to_ai().map_err(|e| format!("{}: {}", id, e))?),
+      Some(_) => return Err(format!("{}: only the listener ai can have hearing", id)),
+      None => None
+    };
+
+    for rule in &raw.spawns {
+      rule.check().map_err(|e| format!("{}: {}", id, e))?;
+    }
+
+    Ok(MonsterDef {
+      id: id,
+      name: save::intern(raw.name),
+      glyph: glyph,
+      fg: RGB(raw.fg[0], raw.fg[1], raw.fg[2]),
+      bg: RGB(raw.bg[0], raw.bg[1], raw.bg[2]),
+      stats: Stats::new(raw.hp, raw.attack, raw.defense, raw.speed, raw.weight, scent),
+      collision: raw.collision,
+      spawns: raw.spawns,
+      smell: smell,
+      hearing: hearing,
+      ai: raw.ai
+    })
+
+  }
+
+  ///
+  /// Create a creature from the definition
+  ///
+  pub fn spawn(&self, pos: Pos) -> Creature {
+    // The AI name was checked when the definition was loaded
+    let ai : Box<ai::AI> = match (&self.smell, &self.hearing) {
+      (&Some(ref smeller), _) => Box::new(smeller.clone()),
+      (_, &Some(ref listener)) => Box::new(listener.clone()),
+      _ => ai::from_name(&self.ai).unwrap()
+    };
+    let mut creature = Creature::new_boxed(self.name, self.glyph, pos, self.fg, self.bg, self.stats.clone(), ai);
+    creature.collision = self.collision;
+    return creature;
+  }
+
+}
+
+///
+/// Every monster the game knows about
+///
+pub struct Registry {
+  // Sorted by id so that spawning from a seed doesn't depend on what order the files were read in
+  monsters: Vec<MonsterDef>
+}
+
+impl Registry {
+
+  ///
+  /// Load every `.json` file in `dir` into a registry
+  ///
+  pub fn load(dir: &str) -> Result<Self, String> {
+    Registry::from_files(data::read_data_dir(dir)?)
+  }
+
+  ///
+  /// Build a registry from the names and contents of monster files
+  ///
+  pub fn from_files(files: Vec<(String, String)>) -> Result<Self, String> {
+
+    let mut monsters : Vec<MonsterDef> = vec![];
+
+    for (file_name, contents) in files {
+
+      let raw : BTreeMap<String, RawMonster> = serde_json::from_str(&contents)
+        .map_err(|e| format!("{}: {}", file_name, e))?;
+
+      for (id, raw) in raw {
+        if monsters.iter().any(|monster| monster.id == id) {
+          return Err(format!("{}: {} is already defined", file_name, id));
+        }
+        monsters.push(MonsterDef::from_raw(id, raw).map_err(|e| format!("{}: {}", file_name, e))?);
+      }
+
+    }
+
+    if monsters.is_empty() {
+      return Err("No monsters are defined".to_string());
+    }
+
+    monsters.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(Registry { monsters: monsters })
+
+  }
+
+  ///
+  /// Get all of the monsters, in order of id
+  ///
+  pub fn monsters(&self) -> &[MonsterDef] {
+    &self.monsters
+  }
+
+  ///
+  /// Get a monster by id
+  ///
+  pub fn get(&self, id: &str) -> Option<&MonsterDef> {
+    self.monsters.iter().find(|monster| monster.id == id)
+  }
+
+  ///
+  /// Build the table of everything that can spawn on a tile of `biome` on floor `depth`
+  ///
+  pub fn spawn_table(&self, biome: &tile::Biome, depth: usize) -> Table<(&MonsterDef, &SpawnRule)> {
+    let mut table = Table::new();
+    for monster in &self.monsters {
+      for rule in monster.spawns.iter().filter(|rule| rule.allows(biome, depth)) {
+        table.add(rule.weight, (monster, rule));
+      }
+    }
+    return table;
+  }
+
+}
@@ -4,13 +4,20 @@
 //!
 //! NOTE: This is terribly underimplemented on purpose, I don't want to make
 //! any serious attempts at what a `Creature` needs to have in order to create a fun
-//! combat system, and as of right now this is pretty much just a lot of fluff for a scent holder
+//! combat system. Right now it's just enough for basic melee, see `combat`
 //!
 
 use core::world::dungeon::map::tile;
 
-#[derive(Clone, Debug)]
+///
+/// How fast a normal creature is. Creatures gain their speed in energy every tick, see `World::pass_time()`
+///
+pub const NORMAL_SPEED : usize = 10;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Stats {
+  pub hp: usize,
+  pub max_hp: usize,
   pub attack: usize,
   pub defense: usize,
   pub speed: usize,
@@ -19,7 +26,11 @@ pub struct Stats {
 }
 
 impl Stats {
+  ///
+  /// Return new `Stats`. Creatures always start with full health
+  ///
   pub fn new(
+    max_hp: usize,
     attack: usize,
     defense: usize,
     speed: usize,
@@ -27,6 +38,8 @@ impl Stats {
     scent_type: tile::Scent
     ) -> Stats {
     Stats {
+      hp: max_hp,
+      max_hp: max_hp,
       attack: attack,
       defense: defense,
       speed: speed,
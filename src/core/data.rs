@@ -0,0 +1,39 @@
+//!
+//! Reading game data from disk
+//!
+//! Monsters and items are defined in directories of JSON files rather than in code. Each registry parses its own
+//! definitions, but they all find and read their files the same way, which is what lives here.
+//!
+
+use std::fs;
+use std::io::prelude::*;
+
+///
+/// Read the name and contents of every `.json` file in `dir`, sorted by name so nothing depends on the order the files
+/// happen to be listed in
+///
+/// Game data like monster and item definitions lives in directories like this, and gets interned with `save::intern()`
+/// when loaded
+///
+pub fn read_data_dir(dir: &str) -> Result<Vec<(String, String)>, String> {
+
+  let mut paths : Vec<_> = fs::read_dir(dir)
+    .map_err(|e| format!("Unable to read directory {}: {}", dir, e))?
+    .filter_map(|entry| entry.ok())
+    .map(|entry| entry.path())
+    .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+    .collect();
+  paths.sort();
+
+  let mut files = vec![];
+  for path in paths {
+    let mut contents = String::new();
+    fs::File::open(&path)
+      .and_then(|mut file| file.read_to_string(&mut contents))
+      .map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+    files.push((path.display().to_string(), contents));
+  }
+
+  Ok(files)
+
+}
@@ -0,0 +1,72 @@
+//!
+//! Helpers for testing anything loaded from a data directory (see `data::read_data_dir`)
+//!
+//! Monsters and items are both defined in data files and loaded the same way, so they go wrong the same ways too. What's
+//! checked here is whatever any registry has to get right, which leaves each registry's own tests to cover what's
+//! particular to it.
+//!
+
+extern crate serde_json;
+use self::serde_json::{Map, Value};
+
+use core::rng::GameRng;
+use core::table::Table;
+
+///
+/// Something that loads from the names and contents of data files, like `Registry::from_files`
+///
+pub type Loader<R> = fn(Vec<(String, String)>) -> Result<R, String>;
+
+///
+/// Load `contents` as if it were the only file in the data directory
+///
+pub fn one_file<R>(load: Loader<R>, contents: &str) -> Result<R, String> {
+  load(vec![("test.json".to_string(), contents.to_string())])
+}
+
+///
+/// Check that `load` turns down the mistakes any definition can make, and says what's wrong. `good` is a definition of
+/// `id` that loads, which each mistake is made in
+///
+pub fn bad_definitions<R>(load: Loader<R>, id: &str, good: &str) {
+
+  let good : Value = serde_json::from_str(good).expect("The good definition isn't JSON");
+
+  let file = |def: Value| {
+    let mut file = Map::new();
+    file.insert(id.to_string(), def);
+    Value::Object(file).to_string()
+  };
+  let with = |field: &str, value: Value| {
+    let mut def = good.clone();
+    def[field] = value;
+    file(def)
+  };
+  let error = |files: Vec<(String, String)>| match load(files) {
+    Ok(_) => panic!("A bad definition of {} loaded", id),
+    Err(e) => e
+  };
+
+  assert!(one_file(load, &file(good.clone())).is_ok());
+
+  let empty_name = error(vec![("test.json".to_string(), with("name", Value::from("")))]);
+  assert!(empty_name.contains("name can't be empty"), "{}", empty_name);
+
+  let bad_glyph = error(vec![("test.json".to_string(), with("glyph", Value::from("xx")))]);
+  assert!(bad_glyph.contains("glyph should be a single character"), "{}", bad_glyph);
+
+  let typo = error(vec![("test.json".to_string(), with("wieght", Value::from(3)))]);
+  assert!(typo.contains("wieght"), "{}", typo);
+
+  let duplicate = error(vec![("a.json".to_string(), file(good.clone())), ("b.json".to_string(), file(good.clone()))]);
+  assert!(duplicate.contains(&format!("b.json: {} is already defined", id)), "{}", duplicate);
+
+}
+
+///
+/// Pick from `table` `times` times with a fixed seed, and count how many of the picks `wanted` says are what's wanted
+///
+pub fn count_picks<T, F: Fn(&T) -> bool>(table: &Table<T>, times: usize, wanted: F) -> usize {
+  let mut rng = GameRng::new(0);
+  (0..times).filter(|_| table.choose(&mut rng).map_or(false, |pick| wanted(pick))).count()
+}
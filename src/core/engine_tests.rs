@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod tests {
+
+  extern crate serde_json;
+
+  use core::{Engine, State};
+  use std::env;
+
+  use core::keys::{self, KeySource, ScriptedKeys};
+  use core::replay::{self, Header, Recorder};
+  use core::world::dungeon::map::Pos;
+  use core::world::shop_tests::tests::{pick_up_stock, shop_world};
+  use core::tcod::input::KeyCode;
+
+  // A long walk in every direction, with some waiting and stair attempts thrown in
+  fn long_walk() -> String {
+    let moves = "hjklyubn.><";
+    let mut script = String::new();
+    for i in 0..2000 {
+      // Repeat each key a few times so the player actually gets somewhere
+      let ch = moves.chars().nth((i / 4 * 7) % moves.len()).unwrap();
+      script.push(ch);
+    }
+    return script;
+  }
+
+  // Scripts skip whitespace and comments, and understand escape
+  #[test]
+  fn script_parsing() {
+    let mut script = ScriptedKeys::from_str("# walk left\nh h\n\n  # then quit\n.^[j");
+
+    let mut pressed = vec![];
+    while let Some(key) = script.next_key() {
+      pressed.push(key);
+    }
+
+    assert_eq!(pressed.len(), 5);
+    assert_eq!(pressed[0].printable, 'h');
+    assert_eq!(pressed[1].printable, 'h');
+    assert_eq!(pressed[2].printable, '.');
+    assert_eq!(pressed[3].code, KeyCode::Escape);
+    assert_eq!(pressed[4].printable, 'j');
+  }
+
+  // Keys that can't be written as themselves read back as the keys they were written from
+  #[test]
+  fn script_carets() {
+    let written = [' ', '\r', '\t', '#', '^', 'a'].iter().map(|&ch| keys::key_from_char(ch))
+      .chain(vec![keys::escape_key(), keys::unprintable_key()])
+      .map(|key| keys::to_script(&key))
+      .collect::<String>();
+    assert_eq!(written, "^ ^M^I^#^^a^[^@");
+
+    let mut script = ScriptedKeys::from_str(&written);
+    let mut pressed = vec![];
+    while let Some(key) = script.next_key() {
+      pressed.push(key);
+    }
+
+    assert_eq!(pressed.iter().map(|key| key.printable).collect::<String>(), " \r\t#^a\0\0");
+    assert_eq!(pressed[0].code, KeyCode::Spacebar);
+    assert_eq!(pressed[1].code, KeyCode::Enter);
+    assert_eq!(pressed[6].code, KeyCode::Escape);
+  }
+
+  // Thousands of turns without a window should leave the player somewhere sensible
+  #[test]
+  fn headless_long_game() {
+    let mut engine = Engine::headless(Pos::new(89, 39), 1234, Box::new(ScriptedKeys::from_str(&long_walk())));
+    engine.play();
+
+    let world = engine.world();
+    let pos = world.player.actor.pos;
+    assert!(world.is_valid_pos(pos.x, pos.y));
+    assert!(world.floor.dun[pos].seen);
+  }
+
+  // The same seed and the same keys play out the same game
+  #[test]
+  fn headless_is_deterministic() {
+    let keys = || Box::new(ScriptedKeys::new(long_walk().chars().map(keys::key_from_char).collect()));
+
+    let mut a = Engine::headless(Pos::new(89, 39), 99, keys());
+    let mut b = Engine::headless(Pos::new(89, 39), 99, keys());
+    a.play();
+    b.play();
+
+    assert_eq!(serde_json::to_string(a.world()).unwrap(), serde_json::to_string(b.world()).unwrap());
+  }
+
+  // Escape asks before quitting, and quitting leaves play without using up the rest of the keys
+  #[test]
+  fn quit_asks_first() {
+    let mut engine = Engine::headless(Pos::new(89, 39), 1234, Box::new(ScriptedKeys::from_str("^[n^[yhhhh")));
+    let start = engine.world().player.actor.pos;
+    engine.play();
+
+    assert!(match engine.state { State::Quit => true, _ => false });
+    assert_eq!(engine.world().player.actor.pos, start);
+    assert_eq!(engine.world().time, 0);
+  }
+
+  // Playing back a recording should end up in the same place as the game that was recorded
+  #[test]
+  fn replay_round_trip() {
+    let path = env::temp_dir().join("edgequest_replay_round_trip.txt");
+    let path = path.to_str().unwrap();
+    let header = Header { seed: 4321, map_dim: Pos::new(89, 39), wizard: false };
+
+    let mut recorder = Recorder::new(path, &header).unwrap();
+    for ch in long_walk().chars() {
+      recorder.record(&keys::key_from_char(ch));
+    }
+
+    let (loaded, script) = replay::load(path).unwrap();
+    assert_eq!(loaded, header);
+
+    let mut played = Engine::headless(header.map_dim, header.seed, Box::new(ScriptedKeys::from_str(&long_walk())));
+    let mut replayed = Engine::headless(loaded.map_dim, loaded.seed, Box::new(script));
+    played.play();
+    replayed.play();
+
+    assert_eq!(serde_json::to_string(played.world()).unwrap(), serde_json::to_string(replayed.world()).unwrap());
+  }
+
+  // Play `script` while recording it to `name` in the temp directory, then play the recording back and check both games
+  // ended up in the same place
+  fn record_and_replay(name: &str, script: &str) {
+    let path = env::temp_dir().join(name);
+    let path = path.to_str().unwrap();
+    let header = Header { seed: 4321, map_dim: Pos::new(89, 39), wizard: false };
+
+    let mut played = Engine::headless(header.map_dim, header.seed, Box::new(ScriptedKeys::from_str(script)));
+    played.recorder = Some(Recorder::new(path, &header).unwrap());
+    played.play();
+
+    let (loaded, keys) = replay::load(path).unwrap();
+    let mut replayed = Engine::headless(loaded.map_dim, loaded.seed, Box::new(keys));
+    replayed.play();
+
+    assert_eq!(serde_json::to_string(played.world()).unwrap(), serde_json::to_string(replayed.world()).unwrap());
+  }
+
+  // Answers to prompts are recorded along with everything else, so backing out of quitting doesn't turn into a move
+  #[test]
+  fn replay_declined_quit() {
+    let walk = long_walk();
+    let script = format!("{}^[n{}", &walk[..1000], &walk[1000..]);
+    record_and_replay("edgequest_replay_declined_quit.txt", &script);
+
+    let (_, mut keys) = replay::load(env::temp_dir().join("edgequest_replay_declined_quit.txt").to_str().unwrap()).unwrap();
+    let mut pressed = vec![];
+    while let Some(key) = keys.next_key() {
+      pressed.push(key);
+    }
+    assert_eq!(pressed.len(), walk.len() + 2);
+    assert_eq!(pressed[1000].code, KeyCode::Escape);
+    assert_eq!(pressed[1001].printable, 'n');
+  }
+
+  // Picking items by letter and throwing them by direction are recorded too, so inventory use plays back the same way
+  #[test]
+  fn replay_inventory() {
+    let script = format!("hhdagjjaaFalhkdaWaTa{}", &long_walk()[..500]);
+    record_and_replay("edgequest_replay_inventory.txt", &script);
+  }
+
+  // Running out of keys at a prompt to buy something is a no, so nothing changes hands
+  #[test]
+  fn unanswered_trade_is_declined() {
+    let mut engine = Engine::headless(Pos::new(89, 39), 1234, Box::new(ScriptedKeys::from_str("pa")));
+    engine.world = shop_world();
+    pick_up_stock(&mut engine.world);
+    engine.world.player.wallet = 10000.0;
+    let stock = engine.world.floor.items.len();
+    engine.play();
+
+    let world = engine.world();
+    assert_eq!(world.player.wallet, 10000.0);
+    assert_eq!(world.player.inventory.len(), 1);
+    assert!(world.player.inventory[0].unpaid);
+    assert_eq!(world.floor.items.len(), stock);
+  }
+
+  // Closing a prompt or a screen with a key that can't be written as itself, like space, is recorded too, so the next key
+  // isn't taken as the answer on replay
+  #[test]
+  fn replay_unprintable_answers() {
+    let walk = long_walk();
+    let script = format!("{}^[^ {}i^ {}", &walk[..300], &walk[300..600], &walk[600..900]);
+    record_and_replay("edgequest_replay_unprintable_answers.txt", &script);
+
+    let (_, mut keys) = replay::load(env::temp_dir().join("edgequest_replay_unprintable_answers.txt").to_str().unwrap()).unwrap();
+    let mut pressed = vec![];
+    while let Some(key) = keys.next_key() {
+      pressed.push(key);
+    }
+    assert_eq!(pressed.len(), 904);
+    assert_eq!(pressed[300].code, KeyCode::Escape);
+    assert_eq!(pressed[301].printable, ' ');
+    assert_eq!(pressed[603].printable, ' ');
+  }
+
+}
@@ -0,0 +1,64 @@
+//!
+//! A module for reading command line arguments.
+//!
+
+///
+/// Options that can be given on the command line. You should not need to create your own,
+/// instead, get one from `parse()`
+///
+/// # Supported arguments
+///
+/// * `--seed <number>` - Seed the game. Overrides the seed in the configuration file.
+/// * `--script <path>` - Press the keys in a script file before handing control to the keyboard. See `keys::ScriptedKeys` for the format.
+/// * `--replay <path>` - Play back a recorded game. See the `replay` module.
+/// * `--headless` - Run without a window. The game ends once the keys run out, so this needs `--script` or `--replay` too.
+///
+#[derive(Debug, PartialEq, Default)]
+pub struct Args {
+
+  // Seed for the rng
+  pub seed: Option<usize>,
+
+  // Script of keys to press
+  pub script: Option<String>,
+
+  // Recorded game to play back
+  pub replay: Option<String>,
+
+  // Run without a window
+  pub headless: bool
+
+}
+
+///
+/// Parse command line arguments into an `Args` struct.
+///
+/// Panics on anything it doesn't understand, the same way a bad config does
+///
+pub fn parse(args: Vec<String>) -> Args {
+
+  let mut parsed = Args::default();
+  let mut args = args.into_iter();
+
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--seed" => {
+        let value = args.next().expect("--seed needs a value");
+        parsed.seed = Some(value.parse().expect("--seed needs to be a positive number"));
+      },
+      "--script" => {
+        parsed.script = Some(args.next().expect("--script needs a path"));
+      },
+      "--replay" => {
+        parsed.replay = Some(args.next().expect("--replay needs a path"));
+      },
+      "--headless" => {
+        parsed.headless = true;
+      },
+      _ => panic!("Unknown argument: {}", arg)
+    }
+  }
+
+  return parsed;
+
+}
@@ -70,7 +70,11 @@ pub struct Config {
   pub renderer: String,
 
   // Wizard mode
-  pub wizard: bool
+  pub wizard: bool,
+
+  // Seed for the rng. Optional, a random seed is used if it's missing
+  #[serde(default)]
+  pub seed: Option<usize>
 
 }
 
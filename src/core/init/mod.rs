@@ -1,16 +1,27 @@
 //! 
 //! Initialize tcod elements.
 //! 
-//! Depends on the `config` module.
+//! Depends on the `config` and `args` modules, and the monster and item registries.
 //! 
 
+extern crate rand;
+use self::rand::Rng;
+
+use std::env;
+
 // We need tcod::Console to keep our consoles in scope
 #[allow(unused_imports)]
 use core::tcod::{Console, console};
 
+pub mod args;
+use self::args::Args;
+
 pub mod config;
 use self::config::Config;
 
+use core::creature::registry::Registry;
+use core::item::registry::Registry as ItemRegistry;
+
 mod init_tests;
 
 // So for a while each function here loaded the config on it's own. And I thought to myself, "hm, is it possible for this
@@ -25,6 +36,21 @@ lazy_static! {
   static ref CFG : Config = config::load("config/cfg.yml");
 }
 
+// Command line arguments get the same treatment, skipping the first one since it's just the program name.
+//
+// The seed is also only decided once. If neither the command line nor the config specify one we have to roll one ourselves,
+// and rolling it again each time it's asked for would hand out a different seed each time
+lazy_static! {
+  static ref ARGS : Args = args::parse(env::args().skip(1).collect());
+  static ref SEED : usize = ARGS.seed.or(CFG.seed).unwrap_or_else(|| rand::thread_rng().gen());
+}
+
+// Monster and item definitions are data too, and just like the config a bad one should stop the game before it starts
+lazy_static! {
+  static ref MONSTERS : Registry = Registry::load("monsters").unwrap_or_else(|e| panic!("Bad monster definition: {}", e));
+  static ref ITEMS : ItemRegistry = ItemRegistry::load("items").unwrap_or_else(|e| panic!("Bad item definition: {}", e));
+}
+
 ///
 /// Initialize the root console.
 /// 
@@ -100,4 +126,48 @@ pub fn panel_width() -> isize {
 ///
 pub fn wizard() -> bool {
   return CFG.wizard;
+}
+
+///
+/// Get the seed for the game
+/// 
+/// The command line takes priority over the config. If neither has one, a random seed is used
+/// 
+pub fn seed() -> usize {
+  return *SEED;
+}
+
+///
+/// Get the path of the script to play, if there is one
+///
+pub fn script() -> Option<String> {
+  return ARGS.script.clone();
+}
+
+///
+/// Get the path of the replay to play back, if there is one
+///
+pub fn replay() -> Option<String> {
+  return ARGS.replay.clone();
+}
+
+///
+/// Get whether the game should run without a window
+///
+pub fn headless() -> bool {
+  return ARGS.headless;
+}
+
+///
+/// Get every monster the game knows about
+///
+pub fn monsters() -> &'static Registry {
+  return &MONSTERS;
+}
+
+///
+/// Get every item the game knows about
+///
+pub fn items() -> &'static ItemRegistry {
+  return &ITEMS;
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+//!
+//! Dice, for when an item needs to be a little unpredictable
+//!
+//! Written the way tabletop games write them, so `2d6+1` rolls two six sided dice and adds one.
+//!
+
+extern crate rand;
+use self::rand::Rng;
+
+use std::fmt;
+
+///
+/// Some number of dice with the same number of sides, plus a bonus
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Dice {
+  pub count: usize,
+  pub sides: usize,
+  pub bonus: isize
+}
+
+impl Dice {
+
+  ///
+  /// Return new `Dice`
+  ///
+  pub fn new(count: usize, sides: usize, bonus: isize) -> Self {
+    Dice {
+      count: count,
+      sides: sides,
+      bonus: bonus
+    }
+  }
+
+  ///
+  /// Read dice written like `1d4`, `2d6+1` or `1d8-2`
+  ///
+  pub fn parse(text: &str) -> Result<Dice, String> {
+
+    let text = text.trim();
+    let bad = || format!("{} isn't written like dice, which look like 2d6+1", text);
+
+    let d = text.find('d').ok_or_else(bad)?;
+    let count = text[..d].parse::<usize>().map_err(|_| bad())?;
+
+    let rest = &text[d + 1..];
+    let (sides, bonus) = match rest.find(|c| c == '+' || c == '-') {
+      Some(sign) => (&rest[..sign], rest[sign..].trim_start_matches('+').parse::<isize>().map_err(|_| bad())?),
+      None => (rest, 0)
+    };
+    let sides = sides.parse::<usize>().map_err(|_| bad())?;
+
+    if count == 0 || sides == 0 {
+      return Err(format!("{} can't be rolled, there has to be at least one die with at least one side", text));
+    }
+
+    Ok(Dice::new(count, sides, bonus))
+
+  }
+
+  ///
+  /// Roll the dice. A big enough penalty can bring the total down to 0, but never below it
+  ///
+  pub fn roll<R: Rng>(&self, rng: &mut R) -> usize {
+    let mut total = self.bonus;
+    for _ in 0..self.count {
+      total += rng.gen_range(1, self.sides as isize + 1);
+    }
+    if total < 0 { 0 } else { total as usize }
+  }
+
+  ///
+  /// The most the dice can roll
+  ///
+  pub fn max(&self) -> usize {
+    let most = (self.count * self.sides) as isize + self.bonus;
+    if most < 0 { 0 } else { most as usize }
+  }
+
+}
+
+// Write dice the same way they're read
+impl fmt::Display for Dice {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    if self.bonus > 0 {
+      write!(fmt, "{}d{}+{}", self.count, self.sides, self.bonus)
+    } else if self.bonus < 0 {
+      write!(fmt, "{}d{}{}", self.count, self.sides, self.bonus)
+    } else {
+      write!(fmt, "{}d{}", self.count, self.sides)
+    }
+  }
+}
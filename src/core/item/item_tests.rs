@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+
+  use core::data_tests;
+  use core::init;
+  use core::item::{Dice, ItemProperty, Money};
+  use core::item::registry::Registry;
+  use core::renderer::Renderable;
+  use core::rng::GameRng;
+  use core::world::dungeon::map::{tile, Pos};
+
+  // Dice read the way they're written, and write the way they're read
+  #[test]
+  fn dice_parse() {
+    assert_eq!(Dice::parse("1d4"), Ok(Dice::new(1, 4, 0)));
+    assert_eq!(Dice::parse("2d6+1"), Ok(Dice::new(2, 6, 1)));
+    assert_eq!(Dice::parse(" 1d8-2 "), Ok(Dice::new(1, 8, -2)));
+    assert!(Dice::parse("d6").is_err());
+    assert!(Dice::parse("2d").is_err());
+    assert!(Dice::parse("0d6").is_err());
+    assert!(Dice::parse("sword").is_err());
+
+    for text in &["1d4", "2d6+1", "1d8-2"] {
+      assert_eq!(Dice::parse(text).unwrap().to_string(), *text);
+    }
+  }
+
+  // Rolls stay between the least and the most the dice can roll
+  #[test]
+  fn dice_roll() {
+    let mut rng = GameRng::new(1234);
+    let dice = Dice::new(2, 6, 1);
+    for _ in 0..1000 {
+      let roll = dice.roll(&mut rng);
+      assert!(roll >= 3 && roll <= dice.max());
+    }
+    assert_eq!(Dice::new(1, 4, -10).roll(&mut rng), 0);
+  }
+
+  // Items read naturally in a sentence
+  #[test]
+  fn describe_items() {
+    let mut torch = init::items().create("torch", Pos::new(0, 0), 1);
+    assert_eq!(torch.describe(), "a torch");
+    torch.quantity = 2;
+    assert_eq!(torch.describe(), "2 torches");
+
+    let mut key = init::items().create("iron_key", Pos::new(0, 0), 1);
+    assert_eq!(key.describe(), "an iron key");
+    key.quantity = 3;
+    assert_eq!(key.describe(), "3 iron keys");
+
+    assert_eq!(init::items().create("dagger", Pos::new(0, 0), 1).details(), "1d4 damage");
+  }
+
+  // Only the thing itself gets pluralised, unless the definition says what more than one is called
+  #[test]
+  fn describe_plurals() {
+    let mut ring = init::items().create("ring_of_protection", Pos::new(0, 0), 1);
+    assert_eq!(ring.describe(), "a ring of protection");
+    ring.quantity = 2;
+    assert_eq!(ring.describe(), "2 rings of protection");
+
+    assert_eq!(init::items().create("healing_potion", Pos::new(0, 0), 2).describe(), "2 healing potions");
+    assert_eq!(init::items().create("quartz", Pos::new(0, 0), 3).describe(), "3 quartzes");
+    assert_eq!(init::items().create("leather_armor", Pos::new(0, 0), 2).describe(), "2 suits of leather armor");
+
+    let club = data_tests::one_file(Registry::from_files, r#"{
+      "clubs": { "name": "club", "plural": "cudgels", "glyph": ")", "fg": [150, 100, 50], "kind": { "Weapon": "1d6" } }
+    }"#).unwrap().create("clubs", Pos::new(0, 0), 2);
+    assert_eq!(club.describe(), "2 cudgels");
+  }
+
+  // The items that ship with the game should all load, and every kind of money should be in there somewhere
+  #[test]
+  fn shipped_items_load() {
+    let registry = Registry::load("items").unwrap();
+    assert!(registry.get("torch").is_some());
+
+    let tenders = [
+      Money::Copper, Money::Silver, Money::Electrum, Money::Gold, Money::Quartz, Money::Platinum, Money::Mithril, Money::Scale,
+      Money::Onyx, Money::Tourmaline, Money::Emerald, Money::Ruby, Money::Sapphire, Money::Topaz, Money::Diamond
+    ];
+    for tender in &tenders {
+      assert!(registry.items().iter().any(|item| item.property == ItemProperty::Money(tender.clone())), "{:?} isn't defined", tender);
+    }
+  }
+
+  // A good definition makes an item that looks like it
+  #[test]
+  fn create_from_definition() {
+    let registry = data_tests::one_file(Registry::from_files, r#"{
+      "club": { "name": "club", "glyph": ")", "fg": [150, 100, 50], "weight": 4, "kind": { "Weapon": "1d6" } }
+    }"#).unwrap();
+
+    let clubs = registry.create("club", Pos::new(4, 2), 3);
+    assert_eq!(clubs.get_id(), "club");
+    assert_eq!(clubs.pos, Pos::new(4, 2));
+    assert_eq!(clubs.quantity, 3);
+    assert_eq!(clubs.total_weight(), 12);
+    assert_eq!(clubs.property, ItemProperty::Weapon(Dice::new(1, 6, 0)));
+  }
+
+  // Bad definitions say what's wrong with them, including what kind of item they're meant to be
+  #[test]
+  fn bad_definitions() {
+    data_tests::bad_definitions(Registry::from_files, "club", r#"{ "name": "club", "glyph": ")", "fg": [0, 0, 0], "kind": "Key" }"#);
+
+    let bad_dice = data_tests::one_file(Registry::from_files, r#"{ "club": { "name": "club", "glyph": ")", "fg": [0, 0, 0], "kind": { "Weapon": "big" } } }"#);
+    assert!(bad_dice.err().unwrap().contains("isn't written like dice"));
+
+    let bad_kind = data_tests::one_file(Registry::from_files, r#"{ "club": { "name": "club", "glyph": ")", "fg": [0, 0, 0], "kind": "Hammer" } }"#);
+    assert!(bad_kind.err().unwrap().contains("Hammer"));
+
+    let dark = data_tests::one_file(Registry::from_files, r#"{ "candle": { "name": "candle", "glyph": "(", "fg": [0, 0, 0], "kind": { "Light": [0, 10] } } }"#);
+    assert!(dark.err().unwrap().contains("light radius"));
+
+    let no_plural = data_tests::one_file(Registry::from_files, r#"{ "club": { "name": "club", "plural": "", "glyph": ")", "fg": [0, 0, 0], "kind": "Key" } }"#);
+    assert!(no_plural.err().unwrap().contains("plural can't be empty"));
+  }
+
+  // Loot comes in piles as big as the rule that picked it says, and money only turns up where it's allowed to
+  #[test]
+  fn loot_tables() {
+    let items = data_tests::one_file(Registry::from_files, r#"{
+      "pearl": { "name": "pearl", "glyph": "*", "fg": [0, 0, 0], "kind": { "Money": "Scale" }, "spawns": [{ "biome": "Sunken" }] },
+      "pebble": { "name": "pebble", "glyph": "*", "fg": [0, 0, 0], "kind": { "Throwable": "1d2" }, "spawns": [{ "count": [2, 5] }] }
+    }"#).unwrap();
+
+    let table = items.loot_table(&tile::Biome::Cave, 5);
+    assert_eq!(data_tests::count_picks(&table, 20, |&(item, rule)| item.id == "pebble" && rule.count == (2, 5)), 20);
+
+    let pearls = data_tests::count_picks(&items.loot_table(&tile::Biome::Sunken, 5), 100, |&(item, _)| item.property == ItemProperty::Money(Money::Scale));
+    assert!(pearls > 0 && pearls < 100);
+  }
+
+}
@@ -1,7 +1,25 @@
 use core::renderer::{Renderable, RGB};
-use core::world::dungeon::map::Pos;
+use core::save;
+use core::world::dungeon::map::{Emitter, Pos};
 
-#[derive(Clone)]
+pub mod dice;
+pub use self::dice::Dice;
+
+pub mod registry;
+
+mod item_tests;
+
+///
+/// How much most things weigh, one at a time
+///
+pub const WEIGHT_NORMAL : usize = 1;
+
+///
+/// How much a corpse weighs, which is more than anyone can carry
+///
+pub const WEIGHT_CORPSE : usize = 100;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Money {
   Copper,
   Silver,
@@ -34,18 +52,66 @@ pub fn money_value(money: &Money) -> f32 {
   }
 }
 
-#[derive(Clone)]
+///
+/// What drinking a potion does
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Effect {
+  // Heals however much the dice roll
+  Healing(Dice),
+  // Sends the drinker somewhere else on the floor
+  Teleport,
+  // Shows the drinker the whole floor
+  Clairvoyance
+}
+
+///
+/// What wearing a ring does, and by how much
+///
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Ring {
+  // Adds to attack
+  Strength(usize),
+  // Adds to defense
+  Protection(usize),
+  // Adds to speed
+  Speed(usize)
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum ItemProperty {
   // Money is an interesting property because it should go right to a player's wallet
-  Money(Money)
+  Money(Money),
+  // What's left of something that died, along with the time it died at
+  Corpse(usize),
+  // Something to hit things with, and how hard it hits
+  Weapon(Dice),
+  // Something to wear, and how much harder it makes the wearer to hit
+  Armor(usize),
+  // Something to wear on a finger
+  Ring(Ring),
+  // Something to eat, and how much it heals
+  Food(usize),
+  // Something to drink
+  Potion(Effect),
+  // Something that lights up the dark, along with how far it reaches and how many turns it burns for
+  Light(usize, usize),
+  // Something that opens locks
+  Key,
+  // Something to throw, and how hard it hits whatever it lands on
+  Throwable(Dice)
 }
 
 ///
 /// Item struct
 ///
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Item {
-  name: &'static str,
+  #[serde(deserialize_with = "save::deserialize_static_str")]
+  name: save::StaticStr,
+  // What more than one of the item is called, if it's not just the name with an "s" on the end
+  #[serde(default, deserialize_with = "save::deserialize_optional_static_str")]
+  plural: Option<save::StaticStr>,
   glyph: char,
   // Items can potentially be in something's inventory
   pub pos: Pos,
@@ -56,7 +122,23 @@ pub struct Item {
   pub quantity: isize,
 
   // Item property
-  pub property: ItemProperty
+  pub property: ItemProperty,
+
+  // Anything the item smells of or sounds like while it's lying around
+  #[serde(default)]
+  pub emitter: Option<Emitter>,
+
+  // How heavy one of the item is
+  #[serde(default = "Item::default_weight")]
+  pub weight: usize,
+
+  // How much gold one of the item is worth to a shop. Shops have no interest in anything worth nothing
+  #[serde(default)]
+  pub value: usize,
+
+  // Whether the item still belongs to a shop, either up for sale or carried around without having been paid for
+  #[serde(default)]
+  pub unpaid: bool
 }
 
 impl Item {
@@ -67,14 +149,117 @@ impl Item {
   pub fn new(name: &'static str, glyph: char, pos: Pos, fg: RGB, bg: RGB, quantity: isize, property: ItemProperty) -> Self {
     Item {
       name: name,
+      plural: None,
       glyph: glyph, 
       pos: pos, 
       fg: fg, 
       bg: bg,
       quantity: quantity,
-      property: property
+      property: property,
+      emitter: None,
+      weight: WEIGHT_NORMAL,
+      value: 0,
+      unpaid: false
     }
   }
+
+  ///
+  /// Weight of items from saves made before items had any
+  ///
+  fn default_weight() -> usize {
+    WEIGHT_NORMAL
+  }
+
+  ///
+  /// Return the corpse of something called `name` that died at `time`, which looks like it did
+  ///
+  pub fn corpse(name: &str, glyph: char, fg: RGB, pos: Pos, time: usize) -> Self {
+    let mut corpse = Item::new(save::intern(format!("{} corpse", name)), glyph, pos, fg, RGB(0, 0, 0), 1, ItemProperty::Corpse(time));
+    corpse.weight = WEIGHT_CORPSE;
+    return corpse;
+  }
+
+  ///
+  /// How heavy the whole stack is
+  ///
+  pub fn total_weight(&self) -> usize {
+    self.weight * self.quantity.max(0) as usize
+  }
+
+  ///
+  /// Whether the item can go in the same stack as `other`, which it can if they're the same thing and they're either both
+  /// paid for or both not
+  ///
+  pub fn stacks_with(&self, other: &Item) -> bool {
+    self.name == other.name && self.property == other.property && self.weight == other.weight && self.unpaid == other.unpaid
+  }
+
+  ///
+  /// What more than one of the item is called, like "torches" or "rings of protection"
+  ///
+  pub fn plural_name(&self) -> String {
+
+    if let Some(plural) = self.plural {
+      return plural.to_string();
+    }
+
+    // Only the thing itself gets pluralised, and not whatever it's of
+    let (head, rest) = match self.name.find(" of ") {
+      Some(idx) => self.name.split_at(idx),
+      None => (self.name, "")
+    };
+
+    let ending = if ["s", "x", "z", "ch", "sh"].iter().any(|end| head.ends_with(end)) { "es" } else { "s" };
+    return format!("{}{}{}", head, ending, rest);
+
+  }
+
+  ///
+  /// Refer to the item in a sentence, like "a gold piece", "3 arrows" or "2 torches"
+  ///
+  pub fn describe(&self) -> String {
+    if self.quantity > 1 {
+      format!("{} {}", self.quantity, self.plural_name())
+    } else if self.name.starts_with(|c: char| "aeiou".contains(c)) {
+      format!("an {}", self.name)
+    } else {
+      format!("a {}", self.name)
+    }
+  }
+
+  ///
+  /// What's worth knowing about the item at a glance, like how hard a weapon hits
+  ///
+  pub fn details(&self) -> String {
+    match self.property {
+      ItemProperty::Weapon(ref damage) | ItemProperty::Throwable(ref damage) => format!("{} damage", damage),
+      ItemProperty::Armor(armor) | ItemProperty::Ring(Ring::Protection(armor)) => format!("+{} defense", armor),
+      ItemProperty::Ring(Ring::Strength(attack)) => format!("+{} attack", attack),
+      ItemProperty::Ring(Ring::Speed(speed)) => format!("+{} speed", speed),
+      ItemProperty::Food(heals) => format!("heals {}", heals),
+      ItemProperty::Potion(Effect::Healing(ref heals)) => format!("heals {}", heals),
+      ItemProperty::Light(radius, charges) => format!("lights {} tiles for {} turns", radius, charges),
+      _ => String::new()
+    }
+  }
+}
+
+///
+/// The letter an item in the `index`th inventory slot is picked with
+///
+pub fn slot_letter(index: usize) -> char {
+  (b'a' + index as u8) as char
+}
+
+///
+/// The inventory slot picked with `letter`, if it picks one at all
+///
+pub fn slot_index(letter: char) -> Option<usize> {
+  if letter >= 'a' && letter <= 'z' {
+    Some(letter as usize - 'a' as usize)
+  } else {
+    None
+  }
 }
 
 ///
@@ -0,0 +1,299 @@
+//!
+//! Item definitions and loot tables loaded from data files
+//!
+//! Every `.json` file in the `items` directory holds an object of item definitions keyed by an id, like so:
+//!
+//! ```json
+//! {
+//!   "dagger": {
+//!     "name": "dagger",
+//!     "glyph": ")",
+//!     "fg": [180, 180, 200],
+//!     "weight": 1,
+//!     "value": 4,
+//!     "kind": { "Weapon": "1d4" },
+//!     "spawns": [
+//!       { "max_depth": 5, "weight": 4 }
+//!     ]
+//!   }
+//! }
+//! ```
+//!
+//! `bg` can be left out, in which case it's black. `plural` is what more than one of the item is called, and only needs
+//! giving when it's not the name with an "s" on the end, like `"suits of leather armor"`. `weight` is how heavy one of
+//! the item is, defaulting to `WEIGHT_NORMAL`. `value` is how much gold one of the item is worth to a shop, and items
+//! left at 0 can't be bought or sold. Ids have to be unique across every file.
+//!
+//! `kind` says what the item is for, and is one of:
+//!
+//! * `{ "Money": "Gold" }` - Any `Money` by name, which goes straight to the wallet
+//! * `{ "Weapon": "1d6+1" }` - Something to hit things with, and the dice it hits for
+//! * `{ "Armor": 1 }` - Something to wear, and how much defense it adds
+//! * `{ "Ring": { "Speed": 3 } }` - Something to wear on a finger. Rings are `Strength`, `Protection` or `Speed`
+//! * `{ "Food": 5 }` - Something to eat, and how much it heals
+//! * `{ "Potion": { "Healing": "2d4+2" } }`, `{ "Potion": "Teleport" }` or `{ "Potion": "Clairvoyance" }` - Something to drink
+//! * `{ "Light": [20, 1000] }` - Something that lights up the dark, how far, and for how many turns
+//! * `"Key"` - Something that opens locks
+//! * `{ "Throwable": "1d3" }` - Something to throw, and the dice it hits for
+//!
+//! # Loot
+//!
+//! Each entry in `spawns` lets an item show up lying around on new floors, and works the same way it does for monsters
+//! (see `creature::registry`), except that `count` is how many of the item are in the pile rather than how many monsters are
+//! in a group. Each floor picks its loot from everything allowed at that depth and biome, so money is worth more and shows up
+//! less the deeper it is.
+//!
+
+extern crate serde_json;
+
+use std::collections::BTreeMap;
+
+use core::creature::registry::SpawnRule;
+use core::item::{Dice, Effect, Item, ItemProperty, Money, Ring, WEIGHT_NORMAL};
+use core::data;
+use core::renderer::RGB;
+use core::save;
+use core::table::Table;
+use core::world::dungeon::map::{tile, Pos};
+
+///
+/// What drinking a potion does, exactly as it's written in the file
+///
+#[derive(Deserialize)]
+enum RawEffect {
+  Healing(String),
+  Teleport,
+  Clairvoyance
+}
+
+///
+/// What an item is for, exactly as it's written in the file. Dice are written out as text, like `2d6+1`
+///
+#[derive(Deserialize)]
+enum RawKind {
+  Money(Money),
+  Weapon(String),
+  Armor(usize),
+  Ring(Ring),
+  Food(usize),
+  Potion(RawEffect),
+  Light(usize, usize),
+  Key,
+  Throwable(String)
+}
+
+impl RawKind {
+
+  ///
+  /// Check the kind, turning it into an `ItemProperty`
+  ///
+  fn to_property(self) -> Result<ItemProperty, String> {
+    Ok(match self {
+      RawKind::Money(money) => ItemProperty::Money(money),
+      RawKind::Weapon(damage) => ItemProperty::Weapon(Dice::parse(&damage)?),
+      RawKind::Armor(armor) => ItemProperty::Armor(armor),
+      RawKind::Ring(ring) => ItemProperty::Ring(ring),
+      RawKind::Food(heals) => ItemProperty::Food(heals),
+      RawKind::Potion(RawEffect::Healing(heals)) => ItemProperty::Potion(Effect::Healing(Dice::parse(&heals)?)),
+      RawKind::Potion(RawEffect::Teleport) => ItemProperty::Potion(Effect::Teleport),
+      RawKind::Potion(RawEffect::Clairvoyance) => ItemProperty::Potion(Effect::Clairvoyance),
+      RawKind::Light(radius, charges) => {
+        if radius == 0 {
+          return Err("light radius has to be at least 1".to_string());
+        }
+        ItemProperty::Light(radius, charges)
+      },
+      RawKind::Key => ItemProperty::Key,
+      RawKind::Throwable(damage) => ItemProperty::Throwable(Dice::parse(&damage)?)
+    })
+  }
+
+}
+
+///
+/// An item definition exactly as it's written in the file
+///
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawItem {
+  name: String,
+  #[serde(default)]
+  plural: Option<String>,
+  glyph: String,
+  fg: [u8; 3],
+  #[serde(default)]
+  bg: [u8; 3],
+  #[serde(default = "RawItem::normal_weight")]
+  weight: usize,
+  #[serde(default)]
+  value: usize,
+  kind: RawKind,
+  #[serde(default)]
+  spawns: Vec<SpawnRule>
+}
+
+impl RawItem {
+
+  // Default for serde
+  fn normal_weight() -> usize {
+    WEIGHT_NORMAL
+  }
+
+}
+
+///
+/// A checked item definition that items can be made from
+///
+#[derive(Clone)]
+pub struct ItemDef {
+  pub id: String,
+  pub name: &'static str,
+  pub plural: Option<&'static str>,
+  pub glyph: char,
+  pub fg: RGB,
+  pub bg: RGB,
+  pub weight: usize,
+  pub value: usize,
+  pub property: ItemProperty,
+  pub spawns: Vec<SpawnRule>
+}
+
+impl ItemDef {
+
+  ///
+  /// Check a raw definition, turning it into an `ItemDef`
+  ///
+  fn from_raw(id: String, raw: RawItem) -> Result<Self, String> {
+
+    if raw.name.is_empty() {
+      return Err(format!("{}: name can't be empty", id));
+    }
+
+    if raw.plural.as_ref().map_or(false, |plural| plural.is_empty()) {
+      return Err(format!("{}: plural can't be empty", id));
+    }
+
+    let mut glyph = raw.glyph.chars();
+    let glyph = match (glyph.next(), glyph.next()) {
+      (Some(ch), None) => ch,
+      _ => return Err(format!("{}: glyph should be a single character, not \"{}\"", id, raw.glyph))
+    };
+
+    let property = raw.kind.to_property().map_err(|e| format!("{}: {}", id, e))?;
+
+    for rule in &raw.spawns {
+      rule.check().map_err(|e| format!("{}: {}", id, e))?;
+    }
+
+    Ok(ItemDef {
+      id: id,
+      name: save::intern(raw.name),
+      plural: raw.plural.map(save::intern),
+      glyph: glyph,
+      fg: RGB(raw.fg[0], raw.fg[1], raw.fg[2]),
+      bg: RGB(raw.bg[0], raw.bg[1], raw.bg[2]),
+      weight: raw.weight,
+      value: raw.value,
+      property: property,
+      spawns: raw.spawns
+    })
+
+  }
+
+  ///
+  /// Make a pile of `quantity` of the item
+  ///
+  pub fn create(&self, pos: Pos, quantity: isize) -> Item {
+    let mut item = Item::new(self.name, self.glyph, pos, self.fg, self.bg, quantity, self.property.clone());
+    item.plural = self.plural;
+    item.weight = self.weight;
+    item.value = self.value;
+    return item;
+  }
+
+}
+
+///
+/// Every item the game knows about
+///
+pub struct Registry {
+  // Sorted by id so that loot from a seed doesn't depend on what order the files were read in
+  items: Vec<ItemDef>
+}
+
+impl Registry {
+
+  ///
+  /// Load every `.json` file in `dir` into a registry
+  ///
+  pub fn load(dir: &str) -> Result<Self, String> {
+    Registry::from_files(data::read_data_dir(dir)?)
+  }
+
+  ///
+  /// Build a registry from the names and contents of item files
+  ///
+  pub fn from_files(files: Vec<(String, String)>) -> Result<Self, String> {
+
+    let mut items : Vec<ItemDef> = vec![];
+
+    for (file_name, contents) in files {
+
+      let raw : BTreeMap<String, RawItem> = serde_json::from_str(&contents)
+        .map_err(|e| format!("{}: {}", file_name, e))?;
+
+      for (id, raw) in raw {
+        if items.iter().any(|item| item.id == id) {
+          return Err(format!("{}: {} is already defined", file_name, id));
+        }
+        items.push(ItemDef::from_raw(id, raw).map_err(|e| format!("{}: {}", file_name, e))?);
+      }
+
+    }
+
+    if items.is_empty() {
+      return Err("No items are defined".to_string());
+    }
+
+    items.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(Registry { items: items })
+
+  }
+
+  ///
+  /// Get all of the items, in order of id
+  ///
+  pub fn items(&self) -> &[ItemDef] {
+    &self.items
+  }
+
+  ///
+  /// Get an item by id
+  ///
+  pub fn get(&self, id: &str) -> Option<&ItemDef> {
+    self.items.iter().find(|item| item.id == id)
+  }
+
+  ///
+  /// Make a pile of `quantity` of the item called `id`. Panics if there's no such item, since the game asking for one
+  /// that isn't defined is a bug in either the game or the data
+  ///
+  pub fn create(&self, id: &str, pos: Pos, quantity: isize) -> Item {
+    self.get(id).unwrap_or_else(|| panic!("No item is defined as {}", id)).create(pos, quantity)
+  }
+
+  ///
+  /// Build the loot table for a tile of `biome` on floor `depth`
+  ///
+  pub fn loot_table(&self, biome: &tile::Biome, depth: usize) -> Table<(&ItemDef, &SpawnRule)> {
+    let mut table = Table::new();
+    for item in &self.items {
+      for rule in item.spawns.iter().filter(|rule| rule.allows(biome, depth)) {
+        table.add(rule.weight, (item, rule));
+      }
+    }
+    return table;
+  }
+
+}
@@ -0,0 +1,215 @@
+//!
+//! Sources of keypresses for the engine
+//!
+//! Normally keys come straight from the tcod root console, but the engine doesn't really care where they
+//! come from. Anything that can hand out keys one at a time can drive the game, which is how it gets played
+//! without a window.
+//!
+
+// Read script files
+use std::fs::File;
+use std::io::prelude::*;
+
+use core::tcod::input;
+use core::world::dungeon::map::Pos;
+
+///
+/// How the escape key is written in scripts, since it doesn't have a printable character
+///
+pub const ESCAPE : &str = "^[";
+
+///
+/// What keys that can't be written as themselves in a script start with. See `ScriptedKeys`
+///
+const CARET : char = '^';
+
+///
+/// Something that can feed keys to the `Engine`
+///
+pub trait KeySource {
+
+  ///
+  /// Get the next key, or `None` once there are no keys left
+  ///
+  fn next_key(&mut self) -> Option<input::Key>;
+
+}
+
+///
+/// Make a key that looks like it came from tcod from a character
+///
+pub fn key_from_char(ch: char) -> input::Key {
+  input::Key {
+    code: input::KeyCode::Char,
+    printable: ch,
+    pressed: true,
+    ..Default::default()
+  }
+}
+
+///
+/// Make an escape key
+///
+pub fn escape_key() -> input::Key {
+  input::Key {
+    code: input::KeyCode::Escape,
+    pressed: true,
+    ..Default::default()
+  }
+}
+
+///
+/// Make a key with no printable character, like an arrow key
+///
+pub fn unprintable_key() -> input::Key {
+  input::Key {
+    code: input::KeyCode::NoKey,
+    printable: '\0',
+    pressed: true,
+    ..Default::default()
+  }
+}
+
+///
+/// Write `key` the way a script would press it
+///
+pub fn to_script(key: &input::Key) -> String {
+
+  if key.code == input::KeyCode::Escape {
+    return ESCAPE.to_string();
+  }
+
+  match key.printable {
+    ' ' => "^ ".to_string(),
+    '\r' | '\n' => "^M".to_string(),
+    '\t' => "^I".to_string(),
+    '#' => "^#".to_string(),
+    '^' => "^^".to_string(),
+    // The game can't tell unprintable keys apart anyway
+    ch if ch.is_control() || ch.is_whitespace() => "^@".to_string(),
+    ch => ch.to_string()
+  }
+
+}
+
+///
+/// Get the key written as `ch` after a caret in a script, if it's one of them
+///
+fn from_caret(ch: char) -> Option<input::Key> {
+  match ch {
+    '[' => Some(escape_key()),
+    ' ' => Some(input::Key { code: input::KeyCode::Spacebar, ..key_from_char(' ') }),
+    'M' => Some(input::Key { code: input::KeyCode::Enter, ..key_from_char('\r') }),
+    'I' => Some(input::Key { code: input::KeyCode::Tab, ..key_from_char('\t') }),
+    '#' | '^' => Some(key_from_char(ch)),
+    '@' => Some(unprintable_key()),
+    _ => None
+  }
+}
+
+///
+/// Get the step a movement key points in, for anything that asks the player which way
+///
+pub fn direction(ch: char) -> Option<Pos> {
+  match ch {
+    'h' => Some(Pos::new(-1, 0)),
+    'j' => Some(Pos::new(0, 1)),
+    'k' => Some(Pos::new(0, -1)),
+    'l' => Some(Pos::new(1, 0)),
+    'y' => Some(Pos::new(-1, -1)),
+    'u' => Some(Pos::new(1, -1)),
+    'b' => Some(Pos::new(-1, 1)),
+    'n' => Some(Pos::new(1, 1)),
+    _ => None
+  }
+}
+
+///
+/// Keys that were decided ahead of time
+///
+/// # Script format
+///
+/// Each character in a script is pressed in order, so `hhjj>` walks left twice, down twice, then takes the stairs.
+/// Whitespace is ignored so scripts can be split up however you like, and lines starting with `#` are comments. Keys
+/// that can't be written as themselves start with a caret:
+///
+/// * `^[` - Escape
+/// * `^ ` - Space
+/// * `^M` - Enter
+/// * `^I` - Tab
+/// * `^#` and `^^` - `#` and `^`
+/// * `^@` - Any key without a printable character, like the arrow keys
+///
+/// Anything else after a caret is ignored.
+///
+pub struct ScriptedKeys {
+  keys: Vec<input::Key>,
+  // Index of the next key
+  next: usize
+}
+
+impl ScriptedKeys {
+
+  ///
+  /// Return a new `ScriptedKeys` that presses each of `keys` in order
+  ///
+  pub fn new(keys: Vec<input::Key>) -> Self {
+    ScriptedKeys { keys: keys, next: 0 }
+  }
+
+  ///
+  /// Return a new `ScriptedKeys` from a script
+  ///
+  pub fn from_str(script: &str) -> Self {
+
+    let mut keys = vec![];
+
+    for line in script.lines() {
+
+      // Skip comments
+      if line.trim_start().starts_with('#') {
+        continue;
+      }
+
+      let mut chars = line.chars();
+      while let Some(ch) = chars.next() {
+        if ch == CARET {
+          // A caret at the end of a line has nothing to escape
+          if let Some(key) = chars.next().and_then(from_caret) {
+            keys.push(key);
+          }
+        } else if !ch.is_whitespace() {
+          keys.push(key_from_char(ch));
+        }
+      }
+
+    }
+
+    return ScriptedKeys::new(keys);
+
+  }
+
+  ///
+  /// Return a new `ScriptedKeys` from a script file
+  ///
+  pub fn from_file(path: &str) -> Self {
+    let mut file = File::open(path).expect("Unable to open script");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).expect("Problem reading script");
+    return ScriptedKeys::from_str(&contents);
+  }
+
+}
+
+impl KeySource for ScriptedKeys {
+
+  fn next_key(&mut self) -> Option<input::Key> {
+    if self.next < self.keys.len() {
+      self.next += 1;
+      return Some(self.keys[self.next - 1]);
+    } else {
+      return None;
+    }
+  }
+
+}
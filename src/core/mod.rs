@@ -12,6 +12,9 @@ extern crate tcod;
 use self::tcod::{console, Console};
 use self::tcod::input;
 
+extern crate rand;
+use self::rand::Rng;
+
 // The game log
 //
 // Every roguelike needs a way of delivering messages to the player because - spoiler - ASCII isn't exactly expressive enough
@@ -33,6 +36,7 @@ use self::log::GlobalLog;
 pub mod world;
 // Import world directly so we can make instances of it
 use self::world::World;
+use self::world::desire::Desire;
 use self::world::dungeon::map::Pos;
 
 // Creatures
@@ -42,11 +46,11 @@ use self::world::dungeon::map::Pos;
 pub mod creature;
 // While normally this module most likely should not have access to objects, we need to see `Action`s as the player's
 // choices changes the state of the game
+use self::creature::Slot;
 use self::creature::actions::Actions;
 
 // Items
 pub mod item;
-use self::item::{ItemProperty, money_value};
 
 // Renderer
 //
@@ -58,7 +62,48 @@ use self::item::{ItemProperty, money_value};
 // Renderer is public so that docs are generated for it
 pub mod renderer;
 // We import the renderer to create instances of it and RGB so we can color some log outputs
-use self::renderer::{Renderer, Renderable, RGB};
+use self::renderer::{Renderer, RGB};
+
+// Randomness
+//
+// Anything random in the game comes from one seeded generator, so that a game can be replayed exactly
+pub mod rng;
+
+// Weighted tables
+//
+// Built on top of the rng, for when some random choices should come up more often than others
+pub mod table;
+
+// Game data
+//
+// Monster and item definitions are read from data directories, which both registries share
+pub mod data;
+
+// Keys
+//
+// The engine reads keys from whatever it's given, not just the keyboard. This is what lets the game run from a script
+// with no window at all
+pub mod keys;
+use self::keys::{direction, KeySource, ScriptedKeys};
+
+// Replays
+//
+// Every game is recorded as it's played so that it can be played back later, which is mostly useful for chasing down crashes
+pub mod replay;
+use self::replay::{Header, Recorder, REPLAY_PATH};
+
+// Saving and loading
+//
+// The whole world can be written to disk and read back. This is mostly a matter of serde derives, but
+// the module deals with the parts of the game that serde can't handle by itself
+pub mod save;
+
+// Morgue files
+//
+// When the player dies, what happened gets written down so they can look back on it
+pub mod morgue;
+
+mod morgue_tests;
 
 // Initializer
 // 
@@ -68,6 +113,12 @@ use self::renderer::{Renderer, Renderable, RGB};
 // Pretty sure you understand why this is public by now
 pub mod init;
 
+mod engine_tests;
+
+// Checks shared by the tests of everything loaded from data files
+#[cfg(test)]
+mod data_tests;
+
 ///
 /// Enum representing the state of the game
 /// 
@@ -85,7 +136,11 @@ pub enum State {
   // A key was pressed
   Keypress,
   // Debug command was triggered
-  Debug
+  Debug,
+  // The player died
+  GameOver,
+  // The player asked to leave
+  Quit
 }
 
 ///
@@ -100,7 +155,16 @@ pub struct Engine {
   world: World,
   state: State,
   ren: Renderer,
-  root: console::Root,
+  // The window. Headless engines don't have one
+  root: Option<console::Root>,
+  // Keys to press before listening to the keyboard
+  script: Option<Box<KeySource>>,
+  // Records keys for a replay
+  recorder: Option<Recorder>,
+  // Whether the game is a replay, which always starts from scratch and is never recorded
+  replaying: bool,
+  // Whether to list the keys when play starts. Set from the options screen
+  help: bool,
   
   // Debug options the engine tracks
   wizard: bool,
@@ -117,8 +181,28 @@ impl Engine {
 
     match keypress.code {
       
-      // If the keycode isn't escape we continue checking for important keys
-      input::KeyCode::Escape => panic!("Bye"),
+      // Escape saves the game and quits, once the player says they really mean it. If the save fails we'd rather tell
+      // the player and keep playing than quietly lose their game. Headless games are throwaway, so they don't get to
+      // overwrite the player's save
+      input::KeyCode::Escape => {
+        // Asking doesn't take any time, whatever the answer. Nobody being left to answer is as good as a yes, since the
+        // game is over either way and it should be saved
+        self.state = State::Keypress;
+        if self.confirm("Save and quit? (y/n)", true) {
+          // The recording carries on from wherever the save was loaded, so a replay does too
+          if self.replaying {
+            log!(("The game was saved here.", RGB(150, 150, 150)));
+            return;
+          }
+          if self.root.is_some() {
+            if let Err(e) = save::save(&self.world) {
+              log!((Box::leak(format!("Could not save: {}", e).into_boxed_str()), RGB(255, 0, 0)));
+              return;
+            }
+          }
+          self.state = State::Quit;
+        }
+      },
 
       // This part of the code is for capturing the keypress not as an object, but as a character for easier parsing
       _ => { 
@@ -172,33 +256,91 @@ impl Engine {
               self.world.player.state = Actions::Move;
             },
 
-            // Pick up item
+            // Pick up items, which only takes time if there was something to pick up
             'g' => {
-              // Player pos
-              let player_pos = self.world.player.actor.pos.clone();
-              // Get items at players feet
-              // let items_at_feet = self.world.floor.items.iter().filter(|item| item.pos == self.world.player.actor.pos.clone());
-              for item in &self.world.floor.items {
-                if item.pos == player_pos {
-                  match item.property {
-                    ItemProperty::Money(ref tender) => self.world.player.wallet += money_value(&tender) * item.quantity as f32
-                  }
-                  if item.quantity > 1 {
-                    log!( (Box::leak(format!("You pick up {} {}s", item.quantity, item.get_id()).into_boxed_str()), item.get_fg()) );
-                  } else {
-                    log!( (Box::leak(format!("You pick up a {}", item.get_id()).into_boxed_str()), item.get_fg()) );
+              self.world.player.state = if self.world.player_pick_up() { Actions::PickUp } else { Actions::Unknown };
+            },
+
+            // Look through the inventory, which doesn't take any time
+            'i' => {
+              self.show_inventory("You are carrying:");
+              self.world.player.state = Actions::Unknown;
+            },
+
+            // Drop an item, picked by its letter
+            'd' => {
+              let dropped = match self.show_inventory("Drop what?").and_then(|key| item::slot_index(key.printable)) {
+                Some(idx) => self.world.player_drop(idx),
+                None => false
+              };
+              self.world.player.state = if dropped { Actions::Drop } else { Actions::Unknown };
+            },
+
+            // Use an item, picked by its letter
+            'a' => {
+              let applied = match self.show_inventory("Apply what?").and_then(|key| item::slot_index(key.printable)) {
+                Some(idx) => self.world.player_apply(idx),
+                None => false
+              };
+              self.world.player.state = if applied { Actions::Apply } else { Actions::Unknown };
+            },
+
+            // Throw an item, picked by its letter, in a direction picked with the movement keys
+            'F' => {
+              let thrown = match self.show_inventory("Throw what?").and_then(|key| item::slot_index(key.printable)) {
+                Some(idx) if idx < self.world.player.inventory.len() => {
+                  log!(("Which way? (hjklyubn)", RGB(255, 255, 0)));
+                  self.draw();
+                  match self.next_key().and_then(|key| direction(key.printable)) {
+                    Some(dir) => self.world.player_throw(idx, dir),
+                    None => false
                   }
-                }
-              }
-              // Prune picked up items
-              self.world.floor.items.retain( |item| item.pos != player_pos );
-            }
+                },
+                _ => false
+              };
+              self.world.player.state = if thrown { Actions::Throw } else { Actions::Unknown };
+            },
 
-            // Force reload word
+            // Wield a weapon, picked by its letter
             'w' => {
+              let wielded = match self.show_inventory("Wield what?").and_then(|key| item::slot_index(key.printable)) {
+                Some(idx) => self.world.player_equip(idx, true),
+                None => false
+              };
+              self.world.player.state = if wielded { Actions::Equip } else { Actions::Unknown };
+            },
+
+            // Wear armor, a ring or a light, picked by its letter
+            'W' => {
+              let worn = match self.show_inventory("Wear what?").and_then(|key| item::slot_index(key.printable)) {
+                Some(idx) => self.world.player_equip(idx, false),
+                None => false
+              };
+              self.world.player.state = if worn { Actions::Equip } else { Actions::Unknown };
+            },
+
+            // Take something off, picked by the letter of its slot
+            'T' => {
+              let removed = match self.show_equipment("Take off what?").and_then(|key| item::slot_index(key.printable)) {
+                Some(idx) if idx < Slot::all().len() => self.world.player_remove(Slot::all()[idx]),
+                _ => false
+              };
+              self.world.player.state = if removed { Actions::Unequip } else { Actions::Unknown };
+            },
+
+            // Buy, sell and haggle in a shop, which doesn't take any time however long it goes on
+            'p' => {
+              self.trade();
+              self.world.player.state = Actions::Unknown;
+            },
+
+            // Force reload word
+            'G' => {
               if self.wizard {
                 log!(("You remold the earth like clay." , RGB(255, 0, 0)));
-                self.world = World::new(Pos::from_tup(init::map_dimensions()));
+                // Seed the new world from the old one so the remolding can be reproduced too
+                let seed = self.world.rng.gen();
+                self.world = World::new(Pos::from_tup(init::map_dimensions()), seed);
               }
               self.state = State::Act(Actions::Unknown);
             },
@@ -215,6 +357,17 @@ impl Engine {
               self.world.player.state = Actions::Wait;
             },
 
+            // Toggle sneaking
+            's' => {
+              self.world.player.sneaking = !self.world.player.sneaking;
+              match self.world.player.sneaking {
+                true => log!(("You start sneaking.", RGB(150, 150, 150))),
+                false => log!(("You stop sneaking.", RGB(150, 150, 150)))
+              }
+              // Doesn't take any time
+              self.world.player.state = Actions::Unknown;
+            },
+
             // Go downstars (if possible)
             '>' => { self.world.player.state = Actions::DownStair },
             // Go upstairs (if possible)
@@ -230,7 +383,7 @@ impl Engine {
                   false => log!(("Your eyes percieve scent like light.", RGB(255, 0, 0)))
                 }
                 self.ren.show_scent = !self.ren.show_scent;
-                self.ren.draw_all(&mut self.root, &mut self.world);
+                self.draw();
               }
               self.state = State::Debug;
             },
@@ -243,7 +396,27 @@ impl Engine {
                   false => log!(("Your eyes percieve sound like light.", RGB(255, 0, 0)))
                 }
                 self.ren.show_sound = !self.ren.show_sound;
-                self.ren.draw_all(&mut self.root, &mut self.world);
+                self.draw();
+              }
+              self.state = State::Debug;
+            },
+
+            // Cycle through desire maps
+            'x' => {
+              if self.wizard {
+                let next = match self.ren.show_desire {
+                  None => Some(Desire::Player),
+                  Some(Desire::Player) => Some(Desire::Items),
+                  Some(Desire::Items) => Some(Desire::Stairs),
+                  Some(Desire::Stairs) => Some(Desire::Flee),
+                  Some(Desire::Flee) => None
+                };
+                match next {
+                  Some(desire) => log!((Box::leak(format!("You sense the pull of {:?}.", desire).into_boxed_str()), RGB(255, 0, 0))),
+                  None => log!(("Your vision returns to normal.", RGB(255, 0, 0)))
+                }
+                self.ren.show_desire = next;
+                self.draw();
               }
               self.state = State::Debug;
             },
@@ -256,7 +429,7 @@ impl Engine {
                   false => log!(("Your third eye opens, revealing the universe.", RGB(255, 0, 0)))
                 }
                 self.ren.fov = !self.ren.fov;
-                self.ren.draw_all(&mut self.root, &mut self.world);
+                self.draw();
               }
               self.state = State::Debug;
             },
@@ -275,7 +448,9 @@ impl Engine {
 
             // Tcod test
             'c' => {
-              self.ren.tcod_test(&mut self.root);
+              if let Some(ref mut root) = self.root {
+                self.ren.tcod_test(root);
+              }
               self.state = State::Debug;
             },
 
@@ -288,8 +463,8 @@ impl Engine {
           // In addition, we should also process the action of the player while we're here
           match self.state {
 
-            // If state is Debug, don't override
-            State::Debug => (),
+            // If state is Debug, don't override, and once the player has quit there's nothing left to act on
+            State::Debug | State::Quit => (),
 
             _ => {
 
@@ -301,10 +476,18 @@ impl Engine {
 
                 Actions::Move => {
 
-                  // Make sure player doesn't do anything dumb
-                  if !self.world.is_valid_pos(self.world.player.actor.pos.x, self.world.player.actor.pos.y) && !self.noclip {
+                  let target = self.world.player.actor.pos;
+
+                  // Moving into a creature attacks it instead
+                  if self.world.creature_at(target).is_some() {
+                    self.world.player.actor.pos = oldpos;
+                    self.world.player.state = Actions::Attack(target);
+                    self.state = State::Act(Actions::Attack(target));
+                  // Make sure player doesn't do anything dumb. Walking into a wall doesn't take any time
+                  } else if !self.world.is_valid_pos(self.world.player.actor.pos.x, self.world.player.actor.pos.y) && !self.noclip {
                     self.world.player.actor.pos = oldpos;
                     self.world.player.state = Actions::Unknown;
+                    self.state = State::Act(Actions::Unknown);
                   // Otherwise if the position is valid...
                   } else {
                     // Empty for right now
@@ -335,17 +518,44 @@ impl Engine {
   ///
   /// Return a new `Engine`
   /// 
+  /// What kind of engine depends on the command line. See `init::args` for the options
+  /// 
   pub fn new() -> Self {
 
     // Get map height
-    let map_dim = Pos::from_tup(init::map_dimensions());
+    let mut map_dim = Pos::from_tup(init::map_dimensions());
+    let mut seed = init::seed();
+    let mut wizard = init::wizard();
+
+    // Load the script if there is one
+    let mut script = init::script().map(|path| Box::new(ScriptedKeys::from_file(&path)) as Box<KeySource>);
+
+    // A replay decides what world gets built, and its keys take the place of a script
+    let replaying = match init::replay() {
+      Some(path) => {
+        let (header, keys) = replay::load(&path).unwrap_or_else(|e| panic!("{}", e));
+        map_dim = header.map_dim;
+        seed = header.seed;
+        wizard = header.wizard;
+        script = Some(Box::new(keys));
+        true
+      },
+      None => false
+    };
+
+    if init::headless() {
+      let mut engine = Engine::headless(map_dim, seed, script.expect("Headless mode needs a --script or --replay to play"));
+      engine.wizard = wizard;
+      return engine;
+    }
 
     // Get root console
     let root = init::root();
 
+    // Whether there's a saved game to carry on with gets decided on the title screen
     Engine {
 
-      world: World::new(map_dim),
+      world: World::new(map_dim, seed),
 
       state: State::New,
 
@@ -353,19 +563,196 @@ impl Engine {
         map_dim, 
         Pos::new(root.width() as isize, root.height() as isize), 
         init::console_height(),
-        init::panel_width()
+        init::panel_width(),
+        seed
       ),
       
-      root: root,
+      root: Some(root),
+
+      script: script,
+
+      recorder: None,
+
+      replaying: replaying,
+
+      help: true,
 
       // Debug 
       noclip: false,
-      wizard: init::wizard()
+      wizard: wizard
 
     }
     
   }
 
+  ///
+  /// Return a new `Engine` that has no window and plays `keys` until they run out
+  /// 
+  /// Nothing is read from the config or the save, so headless games only depend on their arguments
+  /// 
+  pub fn headless(map_dim: Pos, seed: usize, keys: Box<KeySource>) -> Self {
+
+    Engine {
+
+      world: World::new(map_dim, seed),
+
+      state: State::New,
+
+      // Nothing is ever drawn, but the renderer still holds the debug toggles
+      ren: Renderer::new(map_dim, map_dim, 0, 0, seed),
+
+      root: None,
+
+      script: Some(keys),
+
+      recorder: None,
+
+      replaying: false,
+
+      help: true,
+
+      noclip: false,
+      wizard: false
+
+    }
+
+  }
+
+  ///
+  /// Get the world the engine is playing
+  ///
+  pub fn world(&self) -> &World {
+    &self.world
+  }
+
+  ///
+  /// Draw the world, if there's anywhere to draw it
+  ///
+  fn draw(&mut self) {
+    if let Some(ref mut root) = self.root {
+      self.ren.draw_all(root, &self.world);
+    }
+  }
+
+  ///
+  /// Show the player's inventory over the map, and return the key they pressed to close it
+  ///
+  fn show_inventory(&mut self, title: &str) -> Option<input::Key> {
+    self.draw();
+    if let Some(ref mut root) = self.root {
+      self.ren.draw_inventory(root, &self.world.player, title);
+    }
+    self.next_key()
+  }
+
+  ///
+  /// Show what the player has on over the map, and return the key they pressed to close it
+  ///
+  fn show_equipment(&mut self, title: &str) -> Option<input::Key> {
+    self.draw();
+    if let Some(ref mut root) = self.root {
+      self.ren.draw_equipment(root, &self.world.player, title);
+    }
+    self.next_key()
+  }
+
+  ///
+  /// Trade with the shopkeeper until the player picks something that isn't in their inventory
+  ///
+  /// Picking something unpaid for buys it, and picking anything else sells it, once the player agrees to the price
+  ///
+  fn trade(&mut self) {
+
+    if let Err(reason) = self.world.open_for_business() {
+      log!((reason, RGB(150, 150, 150)));
+      return;
+    }
+
+    loop {
+
+      self.draw();
+      if let Some(ref mut root) = self.root {
+        self.ren.draw_shop(root, &self.world);
+      }
+
+      let key = match self.next_key() {
+        Some(key) => key,
+        None => return
+      };
+
+      if key.printable == 'H' {
+        self.world.player_haggle();
+        continue;
+      }
+
+      let idx = match item::slot_index(key.printable) {
+        Some(idx) if idx < self.world.player.inventory.len() => idx,
+        _ => return
+      };
+
+      let (unpaid, described) = {
+        let item = &self.world.player.inventory[idx];
+        (item.unpaid, item.describe())
+      };
+
+      // Anything the shop doesn't want gets turned down before the player is asked about it, and nobody left to answer
+      // means no deal
+      match self.world.quote(&self.world.player.inventory[idx]) {
+        Some(price) if unpaid => {
+          if self.confirm(Box::leak(format!("Buy {} for {} gold? (y/n)", described, price).into_boxed_str()), false) {
+            self.world.player_buy(idx);
+          }
+        },
+        Some(price) if price > 0 => {
+          if self.confirm(Box::leak(format!("Sell {} for {} gold? (y/n)", described, price).into_boxed_str()), false) {
+            self.world.player_sell(idx);
+          }
+        },
+        Some(_) => log!(("The shopkeeper isn't interested in that.", RGB(150, 150, 150))),
+        None => return
+      }
+
+    }
+
+  }
+
+  ///
+  /// Get the next key to process
+  ///
+  /// Scripted keys come first, then the keyboard. Returns `None` when the game should stop, either because the window
+  /// was closed or because a headless game ran out of keys
+  ///
+  /// Every key read is recorded, including answers to prompts and menus, since a replay has to answer them the same way.
+  /// Keys are recorded before they're acted on, so a key that crashes the game still makes it into the replay
+  ///
+  fn next_key(&mut self) -> Option<input::Key> {
+
+    let scripted = match self.script {
+      Some(ref mut script) => script.next_key(),
+      None => None
+    };
+
+    let key = match scripted {
+      Some(key) => key,
+      None => match self.root {
+        Some(ref mut root) => {
+          if root.window_closed() {
+            return None;
+          }
+          root.wait_for_keypress(true)
+        },
+        None => return None
+      }
+    };
+
+    if let Some(ref mut recorder) = self.recorder {
+      recorder.record(&key);
+    }
+
+    return Some(key);
+
+  }
+
   ///
   /// Update the game state, then update the world depending on the new state
   ///
@@ -374,7 +761,12 @@ impl Engine {
     match self.state {
 
       // Player moving or waiting prompts a world update
-      State::Act(Actions::Move) | State::Act(Actions::Wait) => self.world.update(),
+      // Attacking takes a turn too
+      State::Act(Actions::Move) | State::Act(Actions::Wait) | State::Act(Actions::Attack(_)) => self.world.update(),
+
+      // So does handling items
+      State::Act(Actions::PickUp) | State::Act(Actions::Drop) | State::Act(Actions::Apply) | State::Act(Actions::Throw) |
+      State::Act(Actions::Equip) | State::Act(Actions::Unequip) => self.world.update(),
 
       // Trying to go up and downstairs prompts the respective response from world
       State::Act(Actions::DownStair) => {
@@ -382,8 +774,9 @@ impl Engine {
         if self.noclip {
           log!(("You lose your physicality, and sink into the floor.", RGB(255, 150, 150)));
           self.world.go_down();
-        } else {
-          self.world.player_go_down();
+        } else if self.world.player_go_down() {
+          // The stairs take time, and whatever lives on the new floor gets to use it
+          self.world.update();
         }
       },
 
@@ -392,8 +785,8 @@ impl Engine {
         if self.noclip {
           log!(("You lose your physicality, and ascend through the cieling.", RGB(255, 150, 150)));
           self.world.go_up();
-        } else {
-          self.world.player_go_up();
+        } else if self.world.player_go_up() {
+          self.world.update();
         }
       }
       
@@ -405,72 +798,236 @@ impl Engine {
   }
 
   ///
-  /// Title screen test
+  /// Clear the screen and show `lines` in the middle of it
   ///
-  fn title_screen(&mut self) {
+  fn draw_screen(root: &mut console::Root, lines: &[(String, RGB)]) {
 
-    // First part of this pretty much just fills the screen with black
-
-    let w = self.root.width().clone();
-    let h = self.root.height().clone();
+    let w = root.width();
+    let h = root.height();
 
     for x in 0..w {
       for y in 0..h {
-        self.root.put_char_ex(
-          x as i32,
-          y as i32,
-          ' ',
-          RGB(0, 0, 0).to_tcod(),
-          RGB(0, 0, 0).to_tcod()
-        );
+        root.put_char_ex(x, y, ' ', RGB(0, 0, 0).to_tcod(), RGB(0, 0, 0).to_tcod());
       }
     }
 
-    // Second part displays stuff
-
-    let title = "Edgequest";
-    let subtitle = "Press any key to start.";
-
-    self.root.set_default_foreground(RGB(255, 255, 255).to_tcod());
     // i32 conversion is a pain since I'd rather store stuff as isize and the tcod lib wants i32 since it's
     // pretty much just a C++ interface which is annoying
-    self.root.print((w / 2 - (title.len() / 2) as i32) as i32, (h / 2 - 1) as i32, title);
-    self.root.print((w / 2 - (subtitle.len() / 2) as i32) as i32, (h / 2 + 1) as i32, subtitle);
+    let top = h / 2 - (lines.len() / 2) as i32;
+    for (i, &(ref line, color)) in lines.iter().enumerate() {
+      root.set_default_foreground(color.to_tcod());
+      root.print(w / 2 - (line.len() / 2) as i32, top + i as i32, line);
+    }
 
-    self.root.flush();
+    root.flush();
 
-    // Wait for keypress
-    let keypress = self.root.wait_for_keypress(true);
+  }
 
-    // Escape on title should quit the game
-    match keypress.code {
-      
-      // If the keycode isn't escape we continue checking for important keys
-      input::KeyCode::Escape => panic!("Bye"),
-      _ => {}
+  ///
+  /// Ask the player a yes or no question in the log. Anything but yes is a no. Running out of keys means the window was
+  /// closed or the script is over and there's nobody left to ask, so the answer is `default`
+  ///
+  fn confirm(&mut self, question: &'static str, default: bool) -> bool {
+    log!((question, RGB(255, 255, 0)));
+    self.draw();
+    match self.next_key() {
+      Some(key) => key.printable == 'y' || key.printable == 'Y',
+      None => default
+    }
+  }
 
+  ///
+  /// Start recording the game, carrying on with the last recording if the game was loaded from a save
+  ///
+  fn start_recording(&mut self, loaded: bool) {
+
+    if self.replaying {
+      return;
+    }
+
+    let recorder = if loaded {
+      Recorder::append(REPLAY_PATH)
+    } else {
+      Recorder::new(REPLAY_PATH, &Header { seed: self.world.rng.seed(), map_dim: Pos::new(self.world.floor.dun.width as isize, self.world.floor.dun.height as isize), wizard: self.wizard })
+    };
+
+    match recorder {
+      Ok(recorder) => self.recorder = Some(recorder),
+      Err(e) => log!((Box::leak(format!("Could not record replay: {}", e).into_boxed_str()), RGB(255, 0, 0)))
     }
 
   }
 
+  ///
+  /// Let the player change how the game is set up before it starts
+  ///
+  fn options_screen(&mut self) {
+
+    loop {
+
+      let root = match self.root {
+        Some(ref mut root) => root,
+        None => return
+      };
+
+      Engine::draw_screen(root, &[
+        ("Options".to_string(), RGB(255, 255, 255)),
+        (String::new(), RGB(255, 255, 255)),
+        (format!("h) List the keys when play starts: {}", if self.help { "on" } else { "off" }), RGB(255, 255, 255)),
+        (String::new(), RGB(255, 255, 255)),
+        ("Esc) Back".to_string(), RGB(150, 150, 150))
+      ]);
+
+      let keypress = root.wait_for_keypress(true);
+
+      if keypress.code == input::KeyCode::Escape || root.window_closed() {
+        return;
+      }
+
+      if keypress.printable == 'h' {
+        self.help = !self.help;
+      }
+
+    }
+
+  }
+
+  ///
+  /// Show the title screen and its menu. Returns whether there's a game to play, which there isn't if the player quit
+  ///
+  /// Headless games and replays skip straight to playing
+  ///
+  fn main_menu(&mut self) -> bool {
+
+    if self.root.is_none() || self.replaying {
+      return true;
+    }
+
+    // Why the last choice didn't work out, if it didn't
+    let mut problem = String::new();
+
+    loop {
+
+      let can_continue = save::exists();
+
+      let keypress = {
+        let root = self.root.as_mut().unwrap();
+
+        Engine::draw_screen(root, &[
+          ("Edgequest".to_string(), RGB(255, 0, 255)),
+          (String::new(), RGB(255, 255, 255)),
+          ("n) New Game".to_string(), RGB(255, 255, 255)),
+          ("c) Continue".to_string(), if can_continue { RGB(255, 255, 255) } else { RGB(80, 80, 80) }),
+          ("o) Options".to_string(), RGB(255, 255, 255)),
+          ("q) Quit".to_string(), RGB(255, 255, 255)),
+          (String::new(), RGB(255, 255, 255)),
+          (problem.clone(), RGB(255, 0, 0))
+        ]);
+
+        if root.window_closed() {
+          return false;
+        }
+
+        root.wait_for_keypress(true)
+      };
+
+      if keypress.code == input::KeyCode::Escape {
+        return false;
+      }
+
+      match keypress.printable {
+
+        'n' => {
+          self.start_recording(false);
+          return true;
+        },
+
+        'c' if can_continue => {
+          match save::load() {
+            Ok(world) => {
+              // Saves only last until they're loaded
+              save::delete();
+              self.world = world;
+              self.start_recording(true);
+              log!(("Welcome back.", RGB(255, 255, 255)));
+              return true;
+            },
+            Err(e) => problem = format!("Could not load save: {}", e)
+          }
+        },
+
+        'o' => self.options_screen(),
+
+        'q' => return false,
+
+        _ => {}
+
+      }
+
+    }
+
+  }
+
+  ///
+  /// Write the morgue file and show how the game went
+  ///
+  fn death_screen(&mut self) {
+
+    // Headless games are throwaway, so they don't get a morgue file either
+    let root = match self.root {
+      Some(ref mut root) => root,
+      None => return
+    };
+
+    let messages = GlobalLog.lock().unwrap().get_last_n_messages(morgue::MORGUE_MESSAGES).to_vec();
+    let written = match morgue::write(morgue::MORGUE_PATH, &self.world, &messages) {
+      Ok(()) => format!("Morgue file written to {}", morgue::MORGUE_PATH),
+      Err(e) => e
+    };
+
+    let mut lines = vec![("You died.".to_string(), RGB(255, 0, 0)), (String::new(), RGB(255, 255, 255))];
+    lines.extend(morgue::summary(&self.world).into_iter().map(|line| (line, RGB(255, 255, 255))));
+    lines.push((String::new(), RGB(255, 255, 255)));
+    lines.push((written, RGB(150, 150, 150)));
+    lines.push(("Press any key to exit.".to_string(), RGB(150, 150, 150)));
+
+    Engine::draw_screen(root, &lines);
+    root.wait_for_keypress(true);
+
+  }
+
   ///
   /// Play the game.
   /// 
+  /// Returns once the game is over, whether the player died, quit, or the keys ran out
+  /// 
   pub fn play(&mut self) {
     
-    // Create the title screen
-    self.title_screen();
+    // Create the title screen, and leave if the player would rather not play after all
+    if !self.main_menu() {
+      return;
+    }
 
     // Some starting messages, will be removed in later versions (hopefully)
     log!(("Welcome to Edgequest",                 RGB(255,   0, 255)));
-    log!(("Move with vim keys",                   RGB(255, 255, 255)));
-    log!(("esc to quit",                          RGB(255, 255, 255)));
+    if self.help {
+      log!(("Move with vim keys, s to sneak",       RGB(255, 255, 255)));
+      log!(("g to pick up, d to drop, i for items", RGB(255, 255, 255)));
+      log!(("a to apply an item, F to throw one",   RGB(255, 255, 255)));
+      log!(("w to wield, W to wear, T to take off", RGB(255, 255, 255)));
+      log!(("p to shop, once you find one",         RGB(255, 255, 255)));
+      log!(("esc to save and quit",                 RGB(255, 255, 255)));
+    }
+
+    // The seed is all it takes to reproduce a game, so make sure the player can find it
+    log!((Box::leak(format!("Seed: {}", self.world.rng.seed()).into_boxed_str()), RGB(150, 150, 150)));
 
     if self.wizard {
       log!(("You are in wizard mode",                   RGB(255,   0,   0)));
-      log!(("w to regenerate the map, q to destroy it", RGB(255, 150, 150)));
+      log!(("G to regenerate the map, q to destroy it", RGB(255, 150, 150)));
       log!(("r to toggle scent, t to toggle sound",     RGB(255, 150, 150)));
       log!(("f to toggle FoV, z to toggle noclip",      RGB(255, 150, 150)));
+      log!(("x to cycle through the desire maps",       RGB(255, 150, 150)));
       log!(("Ascend and descend anywhere with < and > while noclip is activated", RGB(255, 150, 150)));
     }
 
@@ -478,23 +1035,41 @@ impl Engine {
     self.update();
 
     // Draw all and capture keypresses
-    while !self.root.window_closed() {
+    loop {
 
       // Draw what the camera sees
-      self.ren.draw_all(&mut self.root, &mut self.world);
+      self.draw();
       
       // Capture game keys (Keys that change the state of the player)
       // This is what gives it the turn based nature, i.e. waits for player input before
       // doing anything. Process keypress also updates the engine state.
       //
-      // We save input to keypress because directly sending the root back into the engine 
-      // causes it to be mutably borrowed more than once
-      let keypress = self.root.wait_for_keypress(true);
+      // No key means there's nothing left to play
+      let keypress = match self.next_key() {
+        Some(keypress) => keypress,
+        None => break
+      };
+
       self.process_keypress(keypress);
 
+      // Saved and done
+      if let State::Quit = self.state {
+        break;
+      }
+
       // Update engine based on state
       self.update();
 
+      // The game is over once the player dies. Give them a chance to see what happened before leaving
+      if self.world.player.state == Actions::Die {
+        self.state = State::GameOver;
+        log!(("You die...", RGB(255, 0, 0)));
+        self.draw();
+        self.next_key();
+        self.death_screen();
+        break;
+      }
+
     } 
 
   }
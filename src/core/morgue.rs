@@ -0,0 +1,129 @@
+//!
+//! Write down how the player died
+//!
+//! Once the player dies, a morgue file is written out with a summary of the game, a plain text drawing of the map around
+//! where they died and the last messages in the log. It's overwritten every time someone dies, so copy it somewhere if
+//! it's worth keeping.
+//!
+
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::Path;
+
+use core::renderer::{Renderable, RGB};
+use core::world::World;
+use core::world::dungeon::map::{path, tile, Pos, Tile};
+
+///
+/// Where the morgue file goes
+///
+pub const MORGUE_PATH : &str = "save/morgue.txt";
+
+///
+/// How many of the last log messages make it into the morgue file
+///
+pub const MORGUE_MESSAGES : usize = 20;
+
+// How far around the player the map in the morgue file reaches, across and down
+const MAP_RADIUS_X : isize = 20;
+const MAP_RADIUS_Y : isize = 8;
+
+///
+/// Sum up how the game went, a line at a time. This is what goes on the death screen too
+///
+pub fn summary(world: &World) -> Vec<String> {
+  vec![
+    world.cause_of_death.clone().unwrap_or("Died of unknown causes".to_string()),
+    format!("Reached floor {}", world.floor_num),
+    format!("Collected {} gold", world.player.wallet),
+    format!("Survived for {} ticks", world.time)
+  ]
+}
+
+///
+/// Pick a character to stand in for a tile in plain text, since most tiles are drawn with nothing but a background colour
+///
+fn tile_char(t: &Tile) -> char {
+  match t.tiletype {
+    tile::Type::Wall(_) => '#',
+    tile::Type::Water => '~',
+    _ if t.glyph != ' ' => t.glyph,
+    _ => '.'
+  }
+}
+
+///
+/// Draw the part of the map around the player that they'd seen, with everything standing or lying on it
+///
+pub fn map(world: &World) -> Vec<String> {
+
+  let center = world.player.actor.pos;
+  let mut lines = vec![];
+
+  for y in (center.y - MAP_RADIUS_Y)..(center.y + MAP_RADIUS_Y + 1) {
+    let mut line = String::new();
+    for x in (center.x - MAP_RADIUS_X)..(center.x + MAP_RADIUS_X + 1) {
+      let pos = Pos::new(x, y);
+      if !path::in_bounds(&world.floor.dun.grid, pos) || !world.floor.dun[pos].seen {
+        line.push(' ');
+      } else if pos == center {
+        line.push(world.player.actor.get_glyph());
+      } else if let Some(creature) = world.floor.creatures.iter().find(|creature| creature.actor.pos == pos) {
+        line.push(creature.actor.get_glyph());
+      } else if let Some(item) = world.floor.items.iter().find(|item| item.pos == pos) {
+        line.push(item.get_glyph());
+      } else {
+        line.push(tile_char(&world.floor.dun[pos]));
+      }
+    }
+    lines.push(line.trim_end().to_string());
+  }
+
+  return lines;
+
+}
+
+///
+/// Put the whole morgue file together
+///
+pub fn report(world: &World, messages: &[(&'static str, RGB)]) -> String {
+
+  let mut report = String::from("Edgequest morgue file\n\n");
+
+  for line in summary(world) {
+    report.push_str(&line);
+    report.push('\n');
+  }
+  report.push_str(&format!("Seed: {}\n", world.rng.seed()));
+
+  report.push_str("\nWhere it happened:\n\n");
+  for line in map(world) {
+    report.push_str(&line);
+    report.push('\n');
+  }
+
+  report.push_str("\nLast messages:\n\n");
+  for &(message, _) in messages {
+    report.push_str(message);
+    report.push('\n');
+  }
+
+  return report;
+
+}
+
+///
+/// Write the morgue file to `path`, creating its directory if needed
+///
+pub fn write(path: &str, world: &World, messages: &[(&'static str, RGB)]) -> Result<(), String> {
+
+  if let Some(dir) = Path::new(path).parent() {
+    fs::create_dir_all(dir).map_err(|e| format!("Unable to create morgue directory: {}", e))?;
+  }
+
+  let mut file = File::create(path).map_err(|e| format!("Unable to create morgue file: {}", e))?;
+  file.write_all(report(world, messages).as_bytes()).map_err(|e| format!("Unable to write morgue file: {}", e))?;
+
+  Ok(())
+
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+
+  use std::env;
+  use std::fs;
+
+  use core::morgue;
+  use core::renderer::RGB;
+  use core::world::World;
+  use core::world::dungeon::map::Pos;
+
+  fn dead_world() -> World {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.cause_of_death = Some("Killed by a rat".to_string());
+    world.player.wallet = 12.0;
+    world.floor_num = 2;
+    return world;
+  }
+
+  // The summary says what happened, and the map is centred on the player
+  #[test]
+  fn morgue_report() {
+    let world = dead_world();
+    let summary = morgue::summary(&world);
+    assert_eq!(summary[0], "Killed by a rat");
+    assert_eq!(summary[1], "Reached floor 2");
+    assert_eq!(summary[2], "Collected 12 gold");
+
+    let map = morgue::map(&world);
+    assert_eq!(map.len(), 17);
+    assert_eq!(map[8].chars().nth(20), Some('@'));
+
+    let report = morgue::report(&world, &[("The rat bites you.", RGB(0, 0, 0)), ("You die...", RGB(0, 0, 0))]);
+    assert!(report.contains("Killed by a rat"));
+    assert!(report.contains(&format!("Seed: {}", world.rng.seed())));
+    assert!(report.ends_with("The rat bites you.\nYou die...\n"));
+  }
+
+  // Morgue files end up on disk
+  #[test]
+  fn morgue_written() {
+    let dir = env::temp_dir().join("edgequest_morgue");
+    let path = dir.join("morgue.txt");
+    let _ = fs::remove_dir_all(&dir);
+
+    let world = dead_world();
+    morgue::write(path.to_str().unwrap(), &world, &[]).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), morgue::report(&world, &[]));
+  }
+
+}
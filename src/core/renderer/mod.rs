@@ -12,8 +12,14 @@ use std::char;
 use core::tcod::{Console, console};
 
 use core::GlobalLog;
+use core::creature::{Creature, Slot, CARRY_LIMIT, INVENTORY_SLOTS};
+use core::item;
+use core::rng::GameRng;
 use core::world::World;
+use core::world::desire::Desire;
 use core::world::dungeon::Dungeon;
+use core::world::dungeon::map::Grid;
+use core::world::scent::ScentMap;
 // Used to expliclty reference constants
 use core::world::dungeon::map::{tile, Pos, Tile};
 
@@ -96,7 +102,11 @@ pub struct Renderer {
   panel_width: isize,
   pub show_scent: bool,
   pub fov: bool,
-  pub show_sound: bool
+  pub show_sound: bool,
+  // Which desire map to draw, if any
+  pub show_desire: Option<Desire>,
+  // Cosmetic effects get their own rng so that drawing never changes what happens in the world
+  rng: GameRng
 }
 
 impl Renderer {
@@ -104,28 +114,27 @@ impl Renderer {
   ///
   /// Render for each monster as a visible colored entity
   ///
-  fn debug_render_scent_map(&mut self, con: &mut console::Root, dungeon: &Dungeon) {
+  fn debug_render_scent_map(&mut self, con: &mut console::Root, dungeon: &Dungeon, scent: &ScentMap) {
 
     for x in 0..dungeon.width {
       for y in 0..dungeon.height {
+        let pos = Pos::from_usize(x, y);
+        let smell = |scent_type: tile::Scent| scent.get(pos, &scent_type);
         // Pretty much just random, Player is red, bugs are green, cats are yellow and dogs are blue
-        let mut color = RGB(
-          dungeon[x][y].scents[0].val + 50 + dungeon[x][y].scents[3].val, 
-          dungeon[x][y].scents[1].val + 25 + dungeon[x][y].scents[3].val, 
-          dungeon[x][y].scents[2].val + 50 
+        let color = RGB(
+          smell(tile::Scent::Player).saturating_add(50).saturating_add(smell(tile::Scent::Feline)),
+          smell(tile::Scent::Insectoid).saturating_add(25).saturating_add(smell(tile::Scent::Feline)),
+          smell(tile::Scent::Canine).saturating_add(50)
         );
         // Iterate over scents, context of what scent it is isn't necessary
-        for scent_type in 0..tile::Scent::Num as usize {
-          if dungeon[x][y].scents[scent_type].val > 0 {
-            self.draw_renderable(con, Pos::new(x as isize, y as isize), &Tile::new(
-              "Debug Scent",
-              ' ',
-              RGB(255, 255, 255),
-              color,
-              tile::Type::Debug
-            ));
-            break;
-          }
+        if tile::Scent::iterator().any(|scent_type| scent.get(pos, scent_type) > 0) {
+          self.draw_renderable(con, pos, &Tile::new(
+            "Debug Scent",
+            ' ',
+            RGB(255, 255, 255),
+            color,
+            tile::Type::Debug
+          ));
         }
       }
     }
@@ -159,10 +168,40 @@ impl Renderer {
 
   }
 
+  ///
+  /// Render a desire map as a green entity, brightest where creatures most want to be
+  ///
+  fn debug_render_desire_map(&mut self, con: &mut console::Root, costs: &Grid<isize>) {
+
+    // Scale everything between the lowest and highest reachable costs, since flee maps go negative
+    let reachable = costs.iter().flat_map(|column| column.iter()).filter(|cost| **cost != isize::MAX);
+    let (low, high) = reachable.fold((isize::MAX, isize::MIN), |(low, high), cost| (low.min(*cost), high.max(*cost)));
+    if low > high {
+      return;
+    }
+    let range = (high - low).max(1);
+
+    for x in 0..costs.len() {
+      for y in 0..costs[x].len() {
+        if costs[x][y] != isize::MAX {
+          let brightness = (255 - (costs[x][y] - low) * 255 / range) as u8;
+          self.draw_renderable(con, Pos::new(x as isize, y as isize), &Tile::new(
+            "Debug Desire",
+            ' ',
+            RGB(255, 255, 255),
+            RGB(0, brightness, brightness / 3),
+            tile::Type::Debug
+          ));
+        }
+      }
+    }
+
+  }
+
   ///
   /// Draw all.
   ///
-  pub fn draw_all(&mut self, con: &mut console::Root, world: &mut World) {
+  pub fn draw_all(&mut self, con: &mut console::Root, world: &World) {
     
     //
     // Console prep
@@ -198,6 +237,110 @@ impl Renderer {
 
   }
 
+  ///
+  /// Draw a list of what `creature` is carrying over the map, each thing next to the letter that picks it
+  ///
+  /// Meant to be drawn after `draw_all()`, so the game still shows around it
+  ///
+  pub fn draw_inventory(&self, con: &mut console::Root, creature: &Creature, title: &str) {
+
+    let mut lines = vec![title.to_string(), String::new()];
+
+    if creature.inventory.is_empty() {
+      lines.push("You aren't carrying anything".to_string());
+    }
+
+    for (idx, item) in creature.inventory.iter().enumerate() {
+      let details = item.details();
+      let unpaid = if item.unpaid { ", unpaid" } else { "" };
+      if details.is_empty() {
+        lines.push(format!("{}) {}{} ({} wt)", item::slot_letter(idx), item.describe(), unpaid, item.total_weight()));
+      } else {
+        lines.push(format!("{}) {}, {}{} ({} wt)", item::slot_letter(idx), item.describe(), details, unpaid, item.total_weight()));
+      }
+    }
+
+    lines.push(String::new());
+    lines.push(format!("Carrying {}/{} wt in {}/{} slots", creature.carried_weight(), CARRY_LIMIT, creature.inventory.len(), INVENTORY_SLOTS));
+
+    self.draw_overlay(con, &lines);
+
+  }
+
+  ///
+  /// Draw what `creature` has on over the map, each slot next to the letter that picks it
+  ///
+  pub fn draw_equipment(&self, con: &mut console::Root, creature: &Creature, title: &str) {
+
+    let mut lines = vec![title.to_string(), String::new()];
+
+    for (idx, slot) in Slot::all().iter().enumerate() {
+      match creature.equipment.get(*slot) {
+        Some(item) => lines.push(format!("{}) {}: {}", item::slot_letter(idx), slot, item.get_id())),
+        None => lines.push(format!("{}) {}: nothing", item::slot_letter(idx), slot))
+      }
+    }
+
+    self.draw_overlay(con, &lines);
+
+  }
+
+  ///
+  /// Draw what the shop in `world` would charge for everything the player hasn't paid for, and pay for everything else,
+  /// each thing next to the letter that picks it
+  ///
+  pub fn draw_shop(&self, con: &mut console::Root, world: &World) {
+
+    let markup = world.shopkeeper().map_or(0, |(_, shop)| shop.markup);
+    let mut lines = vec![
+      format!("The shopkeeper charges {}% of what things are worth.", markup),
+      format!("You have {} gold.", world.player.wallet as usize),
+      String::new()
+    ];
+
+    if world.player.inventory.is_empty() {
+      lines.push("You don't have anything to sell".to_string());
+    }
+
+    for (idx, item) in world.player.inventory.iter().enumerate() {
+      match world.quote(item) {
+        Some(price) if item.unpaid => lines.push(format!("{}) {} - buy for {} gold", item::slot_letter(idx), item.describe(), price)),
+        Some(price) if price > 0 => lines.push(format!("{}) {} - sell for {} gold", item::slot_letter(idx), item.describe(), price)),
+        _ => lines.push(format!("{}) {} - not wanted", item::slot_letter(idx), item.describe()))
+      }
+    }
+
+    lines.push(String::new());
+    lines.push("Pick something to buy or sell it, H to haggle, anything else to leave".to_string());
+
+    self.draw_overlay(con, &lines);
+
+  }
+
+  ///
+  /// Draw `lines` in a box in the top left of the map
+  ///
+  fn draw_overlay(&self, con: &mut console::Root, lines: &[String]) {
+
+    // Just wide enough for the longest line, with a space on either side
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0) as i32 + 2;
+    let height = lines.len() as i32 + 2;
+
+    for x in 0..width {
+      for y in 0..height {
+        con.put_char_ex(x + 1, y + 1, ' ', RGB(0, 0, 0).to_tcod(), RGB(0, 0, 0).to_tcod());
+      }
+    }
+
+    con.set_default_foreground(RGB(255, 255, 255).to_tcod());
+    for (idx, line) in lines.iter().enumerate() {
+      con.print(2, idx as i32 + 2, line);
+    }
+
+    con.flush();
+
+  }
+
   ///
   /// Draw the log
   ///
@@ -277,7 +420,7 @@ impl Renderer {
   /// 
   /// NOTE: This function is super basic and is intended to be revised/removed
   /// 
-  fn draw_ui(&self, con: &mut console::Root, world: &mut World) {
+  fn draw_ui(&self, con: &mut console::Root, world: &World) {
     
     // Draw horizontal line to split game from the log console
     for x in 0..self.screen.x {
@@ -344,12 +487,7 @@ impl Renderer {
     );
 
     // Scent of non-players
-    let mut non_player_scent = 0;
-    for scent in &tile.scents {
-      if &scent.scent_type != &tile::Scent::Player { 
-        non_player_scent += scent.val; // BUG: Panics here with overflow
-      }
-    }
+    let non_player_scent = world.floor.scent.others(world.player.actor.pos, &tile::Scent::Player);
 
     con.print(
       (self.screen.x - self.panel_width + 1) as i32,
@@ -385,12 +523,53 @@ impl Renderer {
       format!("{}: {}", "AU", world.player.wallet)
     );
 
+    // Health
+    con.print(
+      (self.screen.x - self.panel_width + 1) as i32,
+      10,
+      format!("{}: {}/{}", "HP", world.player.stats.hp, world.player.stats.max_hp)
+    );
+
+    // Time
+    con.print(
+      (self.screen.x - self.panel_width + 1) as i32,
+      11,
+      format!("{}: {}", "Time", world.time)
+    );
+
+    // Sneaking
+    if world.player.sneaking {
+      con.print(
+        (self.screen.x - self.panel_width + 1) as i32,
+        12,
+        "Sneaking"
+      );
+    }
+
+    // Stats, with everything the player has on
+    let stats = world.player.effective_stats();
+    con.print(
+      (self.screen.x - self.panel_width + 1) as i32,
+      14,
+      format!("Atk: {} Def: {} Spd: {}", stats.attack, stats.defense, stats.speed)
+    );
+
+    // Equipment
+    for (idx, slot) in Slot::all().iter().enumerate() {
+      let name = world.player.equipment.get(*slot).map_or("-", |item| item.get_id());
+      con.print(
+        (self.screen.x - self.panel_width + 1) as i32,
+        15 + idx as i32,
+        format!("{}: {}", slot, name)
+      );
+    }
+
   }
 
   ///
   /// Draw the contents of the world from the player's point of view
   /// 
-  fn draw_world(&mut self, con: &mut console::Root, world: &mut World) {
+  fn draw_world(&mut self, con: &mut console::Root, world: &World) {
 
     // Draw the world in three steps:
     //
@@ -415,19 +594,17 @@ impl Renderer {
           // And it's in the FoV
           if world.tcod_map.is_in_fov(x as i32, y as i32) {
 
-            // Update tile if possible
-            match &world.floor.dun[x][y].tiletype {
+            // Water shimmers. Only the copy being drawn changes so that drawing doesn't touch the world
+            let mut tile = world.floor.dun[x][y].clone();
+            match &tile.tiletype {
               tile::Type::Water => {
-                &world.floor.dun[x][y].set_bg(*rand::thread_rng().choose(&WATER_COLORS).unwrap());
+                tile.set_bg(*self.rng.choose(&WATER_COLORS).unwrap());
               },
               _ => {}
             }
 
             // Draw a tile slightly more vibrant than it actually is to emulate torchlight
-            self.draw_renderable(con, Pos::from_usize(x, y), &yellowish(&world.floor.dun[x][y]));
-
-            // Mark tile as seen if it's in the FoV
-            world.floor.dun[x][y].seen = true;
+            self.draw_renderable(con, Pos::from_usize(x, y), &yellowish(&tile));
 
           }
 
@@ -470,7 +647,7 @@ impl Renderer {
 
     // Debug scent
     if self.show_scent {
-      self.debug_render_scent_map(con, &world.floor.dun);
+      self.debug_render_scent_map(con, &world.floor.dun, &world.floor.scent);
     }
 
     // Debug sound
@@ -478,6 +655,11 @@ impl Renderer {
       self.debug_render_sound_map(con, &world.floor.dun);
     }
 
+    // Debug desire
+    if let Some(desire) = self.show_desire {
+      self.debug_render_desire_map(con, world.floor.desire.get(desire));
+    }
+
     //
     // Draw creatures
     //
@@ -546,9 +728,10 @@ impl Renderer {
   ///
   /// * `map` - `Pos` that holds the map dimensions
   /// * `screen` - `Pos` that holds the screen dimensions
+  /// * `seed` - Seed for cosmetic randomness
   ///
   #[inline]
-  pub fn new(map: Pos, screen: Pos, console_height: isize, panel_width: isize) -> Self {
+  pub fn new(map: Pos, screen: Pos, console_height: isize, panel_width: isize, seed: usize) -> Self {
     Renderer { 
       // Camera takes a modified screen value that compensates for the console_height
       // This way the render still knows that that area is "reserved" for the console
@@ -558,7 +741,8 @@ impl Renderer {
       ), 
       console_height: console_height, panel_width: panel_width,
       screen: screen,
-      show_scent: false, fov: true, show_sound: false 
+      show_scent: false, fov: true, show_sound: false, show_desire: None,
+      rng: GameRng::new(seed)
     }
   }
 
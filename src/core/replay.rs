@@ -0,0 +1,183 @@
+//!
+//! Record games as they're played, and play them back
+//!
+//! A replay is just a key script (see `keys::ScriptedKeys`) with a header of comments describing the game it came
+//! from. Since everything random comes from the seed, pressing the same keys in a world built from the same header
+//! plays out the exact same game, crashes included.
+//!
+//! Replays follow the save around. Loading a save keeps appending to the replay that was being recorded when it was
+//! written, so a replay always covers the whole game from the first turn. Because of that, a replay doesn't stop where the
+//! game was saved and quit, and just carries on to wherever it was resumed.
+//!
+
+// Read and write replay files
+use std::fs::{self, File, OpenOptions};
+use std::io::prelude::*;
+use std::path::Path;
+
+use core::tcod::input;
+
+use core::log;
+use core::keys::{self, ScriptedKeys};
+use core::renderer::RGB;
+use core::world::dungeon::map::Pos;
+
+///
+/// Where the current game is recorded to
+///
+pub const REPLAY_PATH : &str = "save/replay.txt";
+
+// How many keys go on each line of a replay. Purely to keep them readable
+const KEYS_PER_LINE : usize = 80;
+
+///
+/// Everything besides the keys that's needed to play a game back
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Header {
+  pub seed: usize,
+  pub map_dim: Pos,
+  pub wizard: bool
+}
+
+impl Header {
+
+  ///
+  /// Write the header as script comments
+  ///
+  fn to_script(&self) -> String {
+    format!(
+      "# Edgequest replay\n# seed: {}\n# map: {} {}\n# wizard: {}\n",
+      self.seed, self.map_dim.x, self.map_dim.y, self.wizard
+    )
+  }
+
+  ///
+  /// Read a header back out of a replay
+  ///
+  fn from_script(script: &str) -> Result<Header, String> {
+
+    let mut seed = None;
+    let mut map_dim = None;
+    let mut wizard = false;
+
+    for line in script.lines().filter(|line| line.starts_with("# ")) {
+
+      let mut split = line[2..].splitn(2, ": ");
+
+      match (split.next(), split.next()) {
+        (Some("seed"), Some(value)) => {
+          seed = Some(value.parse().map_err(|_| format!("Bad seed: {}", value))?);
+        },
+        (Some("map"), Some(value)) => {
+          let dims : Vec<isize> = value.split_whitespace().filter_map(|dim| dim.parse().ok()).collect();
+          if dims.len() != 2 {
+            return Err(format!("Bad map dimensions: {}", value));
+          }
+          map_dim = Some(Pos::new(dims[0], dims[1]));
+        },
+        (Some("wizard"), Some(value)) => {
+          wizard = value == "true";
+        },
+        // Any other comment is just a comment
+        _ => {}
+      }
+
+    }
+
+    Ok(Header {
+      seed: seed.ok_or("Replay has no seed")?,
+      map_dim: map_dim.ok_or("Replay has no map dimensions")?,
+      wizard: wizard
+    })
+
+  }
+
+}
+
+///
+/// Writes keys to a replay file as they're pressed
+///
+/// Every key is written straight to disk so that nothing is lost if the game crashes, which is exactly when the
+/// replay is wanted most
+///
+pub struct Recorder {
+  file: File,
+  // Keys written on the current line
+  line_len: usize
+}
+
+impl Recorder {
+
+  ///
+  /// Start a new replay at `path`, replacing whatever was there
+  ///
+  pub fn new(path: &str, header: &Header) -> Result<Self, String> {
+
+    // Make sure the directory is there first
+    if let Some(dir) = Path::new(path).parent() {
+      fs::create_dir_all(dir).map_err(|e| format!("Unable to create replay directory: {}", e))?;
+    }
+
+    let mut file = File::create(path).map_err(|e| format!("Unable to create replay: {}", e))?;
+    file.write_all(header.to_script().as_bytes()).map_err(|e| format!("Unable to write replay: {}", e))?;
+
+    Ok(Recorder { file: file, line_len: 0 })
+
+  }
+
+  ///
+  /// Keep recording an existing replay at `path`
+  ///
+  pub fn append(path: &str) -> Result<Self, String> {
+
+    let mut file = OpenOptions::new().append(true).open(path).map_err(|e| format!("Unable to open replay: {}", e))?;
+
+    // Start on a fresh line so it's clear where the game was resumed
+    file.write_all(b"\n# resumed\n").map_err(|e| format!("Unable to write replay: {}", e))?;
+
+    Ok(Recorder { file: file, line_len: 0 })
+
+  }
+
+  ///
+  /// Record a key
+  ///
+  /// Every key is written down, even the ones that can't be written as themselves (see `keys::to_script`), since the game
+  /// reads them all and a replay has to as well
+  ///
+  pub fn record(&mut self, key: &input::Key) {
+
+    let mut written = keys::to_script(key);
+
+    self.line_len += 1;
+    if self.line_len == KEYS_PER_LINE {
+      written.push('\n');
+      self.line_len = 0;
+    }
+
+    // A broken recording shouldn't take the game down with it
+    if let Err(e) = self.file.write_all(written.as_bytes()) {
+      log!((Box::leak(format!("Unable to record replay: {}", e).into_boxed_str()), RGB(255, 0, 0)));
+    }
+
+  }
+
+}
+
+///
+/// Load a replay, returning the header of the game and the keys to press
+///
+/// Keys written with a caret are read back the same way scripts read them, so they come back as the keys they were
+///
+pub fn load(path: &str) -> Result<(Header, ScriptedKeys), String> {
+
+  let mut file = File::open(path).map_err(|e| format!("Unable to open replay: {}", e))?;
+  let mut contents = String::new();
+  file.read_to_string(&mut contents).map_err(|e| format!("Unable to read replay: {}", e))?;
+
+  let header = Header::from_script(&contents)?;
+
+  Ok((header, ScriptedKeys::from_str(&contents)))
+
+}
@@ -0,0 +1,110 @@
+//!
+//! The one random number generator the game is allowed to use
+//!
+//! Everything that needs randomness - dungeon generation, AIs, traps - draws from a `GameRng` that is owned by the `World`.
+//! Since it starts from a known seed, two games with the same seed and the same inputs play out exactly the same, which is
+//! what lets us reproduce bug reports. Calling `rand::thread_rng()` anywhere in the game code breaks that guarantee, so don't.
+//!
+
+extern crate rand;
+use self::rand::{Rng, SeedableRng, StdRng};
+
+extern crate serde;
+use self::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+///
+/// Seedable RNG that remembers how far along its sequence it is
+///
+/// `StdRng` can't be serialized, so instead of saving its internal state we save the seed and how many numbers were drawn.
+/// Loading then reseeds and throws away that many numbers, which puts the generator right back where it was.
+///
+/// The draws are counted by width because a 64 bit draw may consume more of the underlying generator than a 32 bit one
+/// (depending on the platform), but since the total consumed only depends on how many of each were taken, the order
+/// doesn't matter when replaying them.
+///
+#[derive(Clone)]
+pub struct GameRng {
+  seed: usize,
+  draws32: u64,
+  draws64: u64,
+  rng: StdRng
+}
+
+impl GameRng {
+
+  ///
+  /// Create a new `GameRng` from a seed
+  ///
+  pub fn new(seed: usize) -> Self {
+    GameRng {
+      seed: seed,
+      draws32: 0,
+      draws64: 0,
+      rng: SeedableRng::from_seed(&[seed][..])
+    }
+  }
+
+  ///
+  /// Get the seed this generator started from
+  ///
+  #[inline]
+  pub fn seed(&self) -> usize {
+    self.seed
+  }
+
+  ///
+  /// Rebuild a `GameRng` by replaying all of its previous draws
+  ///
+  fn replay(seed: usize, draws32: u64, draws64: u64) -> Self {
+    let mut rng = GameRng::new(seed);
+    for _ in 0..draws32 {
+      rng.next_u32();
+    }
+    for _ in 0..draws64 {
+      rng.next_u64();
+    }
+    return rng;
+  }
+
+}
+
+///
+/// All of `Rng` is built on these two, so every `gen_range()`, `choose()`, etc. is counted
+///
+impl Rng for GameRng {
+
+  #[inline]
+  fn next_u32(&mut self) -> u32 {
+    self.draws32 += 1;
+    self.rng.next_u32()
+  }
+
+  #[inline]
+  fn next_u64(&mut self) -> u64 {
+    self.draws64 += 1;
+    self.rng.next_u64()
+  }
+
+}
+
+///
+/// What actually gets written to a save
+///
+#[derive(Serialize, Deserialize)]
+struct SavedRng {
+  seed: usize,
+  draws32: u64,
+  draws64: u64
+}
+
+impl Serialize for GameRng {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+    SavedRng { seed: self.seed, draws32: self.draws32, draws64: self.draws64 }.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for GameRng {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+    SavedRng::deserialize(deserializer).map(|saved| GameRng::replay(saved.seed, saved.draws32, saved.draws64))
+  }
+}
@@ -0,0 +1,138 @@
+//!
+//! Save and load the game to and from disk
+//!
+//! The entire `World` is serialized with serde into a single JSON file. Most game objects just derive
+//! `Serialize` and `Deserialize`, but there are two things serde can't do on its own:
+//!
+//! * `&'static str` names - serde can only borrow strings from the data it is reading, and that data goes away
+//! once loading is done. We deserialize them as `String`s and leak them instead, which is the same trick the log
+//! uses to hold formatted messages. Names are interned so loading a save twice doesn't leak the same name twice.
+//! * `Box<AI>` trait objects - see `ai::SavedAI` for how those get persisted.
+//!
+//! The tcod map is also skipped, since it's entirely derived from the dungeon and can just be rebuilt.
+//!
+
+extern crate serde;
+extern crate serde_json;
+use self::serde::{Deserialize, Deserializer};
+
+// Read and write files
+use std::collections::HashSet;
+use std::fs;
+use std::io::prelude::*;
+use std::path::Path;
+use std::sync::Mutex;
+
+use core::world::World;
+
+///
+/// A `&'static str` that can be loaded from a save
+///
+/// serde assumes that any field written as `&'static str` borrows from the data being read, which would mean a save could
+/// only ever be loaded from a `&'static` buffer. Spelling the type through an alias hides it from that check, and pairing the
+/// field with `deserialize_static_str` gives it a real `'static` string to hold.
+///
+pub type StaticStr = &'static str;
+
+///
+/// Where the game is saved to
+///
+pub const SAVE_PATH : &str = "save/game.json";
+
+// Strings that have already been leaked by the loader
+lazy_static! {
+  static ref INTERNED : Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
+}
+
+///
+/// Turn a `String` into a `&'static str`, reusing a previous allocation if the same string was interned before
+///
+pub fn intern(string: String) -> &'static str {
+
+  let mut interned = INTERNED.lock().unwrap();
+
+  // Hand back the string we already leaked
+  if let Some(existing) = interned.get(string.as_str()) {
+    return *existing;
+  }
+
+  let leaked : &'static str = Box::leak(string.into_boxed_str());
+  interned.insert(leaked);
+
+  return leaked;
+
+}
+
+///
+/// Deserialize a `&'static str` by interning it
+///
+/// Use with `#[serde(deserialize_with = "save::deserialize_static_str")]`
+///
+pub fn deserialize_static_str<'de, D>(deserializer: D) -> Result<&'static str, D::Error> where D: Deserializer<'de> {
+  let string = String::deserialize(deserializer)?;
+  Ok(intern(string))
+}
+
+///
+/// Deserialize an `Option<&'static str>` by interning it, if it's there
+///
+/// Use with `#[serde(deserialize_with = "save::deserialize_optional_static_str")]`
+///
+pub fn deserialize_optional_static_str<'de, D>(deserializer: D) -> Result<Option<&'static str>, D::Error> where D: Deserializer<'de> {
+  let string = Option::<String>::deserialize(deserializer)?;
+  Ok(string.map(intern))
+}
+
+///
+/// Check if there is a saved game
+///
+pub fn exists() -> bool {
+  Path::new(SAVE_PATH).exists()
+}
+
+///
+/// Write the world to the save file, creating the save directory if needed
+///
+pub fn save(world: &World) -> Result<(), String> {
+
+  // Make sure the directory is there first
+  if let Some(dir) = Path::new(SAVE_PATH).parent() {
+    fs::create_dir_all(dir).map_err(|e| format!("Unable to create save directory: {}", e))?;
+  }
+
+  let contents = serde_json::to_string(world).map_err(|e| format!("Unable to serialize world: {}", e))?;
+
+  let mut file = fs::File::create(SAVE_PATH).map_err(|e| format!("Unable to create save file: {}", e))?;
+  file.write_all(contents.as_bytes()).map_err(|e| format!("Unable to write save file: {}", e))?;
+
+  Ok(())
+
+}
+
+///
+/// Read the world back from the save file
+///
+/// The returned world has its tcod map and FoV rebuilt, so it can be played immediately
+///
+pub fn load() -> Result<World, String> {
+
+  let mut file = fs::File::open(SAVE_PATH).map_err(|e| format!("Unable to open save file: {}", e))?;
+  let mut contents = String::new();
+  file.read_to_string(&mut contents).map_err(|e| format!("Unable to read save file: {}", e))?;
+
+  let mut world : World = serde_json::from_str(&contents).map_err(|e| format!("Corrupt save file: {}", e))?;
+  world.restore();
+
+  Ok(world)
+
+}
+
+///
+/// Remove the save file
+///
+/// Saves are deleted once they are loaded, so dying means dying
+///
+pub fn delete() {
+  // If there's nothing to delete we don't particularly care
+  let _ = fs::remove_file(SAVE_PATH);
+}
@@ -0,0 +1,68 @@
+//!
+//! Weighted tables for picking things at random
+//!
+//! Anything that gets picked from a list where some entries should come up more than others - which monster spawns,
+//! what loot drops - goes through a `Table`.
+//!
+
+extern crate rand;
+use self::rand::Rng;
+
+///
+/// A list of entries, each with a weight. An entry with twice the weight of another is picked twice as often
+///
+#[derive(Clone, Debug)]
+pub struct Table<T> {
+  entries: Vec<(usize, T)>,
+  total: usize
+}
+
+impl<T> Table<T> {
+
+  ///
+  /// Return a new, empty `Table`
+  ///
+  pub fn new() -> Self {
+    Table { entries: vec![], total: 0 }
+  }
+
+  ///
+  /// Add an entry. Entries with no weight are never picked, so they aren't added at all
+  ///
+  pub fn add(&mut self, weight: usize, entry: T) {
+    if weight > 0 {
+      self.total += weight;
+      self.entries.push((weight, entry));
+    }
+  }
+
+  ///
+  /// Check if there's nothing to pick
+  ///
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  ///
+  /// Pick an entry, or `None` if the table is empty
+  ///
+  pub fn choose<R: Rng>(&self, rng: &mut R) -> Option<&T> {
+
+    if self.is_empty() {
+      return None;
+    }
+
+    // Walk the entries until the roll falls inside one of them
+    let mut roll = rng.gen_range(0, self.total);
+    for &(weight, ref entry) in &self.entries {
+      if roll < weight {
+        return Some(entry);
+      }
+      roll -= weight;
+    }
+
+    unreachable!("Roll was larger than the total weight");
+
+  }
+
+}
@@ -0,0 +1,142 @@
+//!
+//! Desire maps, for AIs that want to go somewhere
+//!
+//! A desire map holds how far every tile on the floor is from something worth walking to (or away from). They get built
+//! once a turn from the terrain alone, so they're the same for every creature, and an AI decides where to go by mixing
+//! them together with weights of its own and stepping downhill. A creature that wants the player but is scared of them
+//! could weigh `Desire::Player` at 1.0 and `Desire::Flee` at 0.5, a scavenger could weigh `Desire::Items` the most, and so on.
+//!
+//! See `path::dijkstra()` and `path::flee()` for how the maps are made.
+//!
+
+use std::{isize, usize};
+
+use core::world::dungeon::map::{path, tile, Grid, Pos, Tile};
+use core::world::occupancy::{Occupancy, Occupant};
+
+///
+/// Things a creature can want to walk towards or away from
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Desire {
+  // The player
+  Player,
+  // Items lying on the floor
+  Items,
+  // Stairs, up or down
+  Stairs,
+  // Anywhere away from the player
+  Flee
+}
+
+///
+/// Every desire map for a floor
+///
+/// Unreachable tiles are `isize::MAX`
+///
+#[derive(Clone, Debug, Default)]
+pub struct DesireMaps {
+  player: Grid<isize>,
+  items: Grid<isize>,
+  stairs: Grid<isize>,
+  flee: Grid<isize>
+}
+
+impl DesireMaps {
+
+  ///
+  /// Build every map for `map`
+  ///
+  pub fn build(map: &Grid<Tile>, player: Pos, items: &[Pos]) -> Self {
+
+    let mut stairs = vec![];
+    for (x, column) in map.iter().enumerate() {
+      for (y, t) in column.iter().enumerate() {
+        if let tile::Type::Stair(_) = t.tiletype {
+          stairs.push(Pos::from_usize(x, y));
+        }
+      }
+    }
+
+    let to_player = path::dijkstra(map, None, &[player]);
+    let flee = path::flee(map, None, &to_player);
+
+    DesireMaps {
+      player: DesireMaps::signed(to_player),
+      items: DesireMaps::signed(path::dijkstra(map, None, items)),
+      stairs: DesireMaps::signed(path::dijkstra(map, None, &stairs)),
+      flee: flee
+    }
+
+  }
+
+  ///
+  /// Turn a map from `path::dijkstra()` into one that can sit alongside flee maps
+  ///
+  fn signed(costs: Grid<usize>) -> Grid<isize> {
+    costs.into_iter()
+      .map(|column| column.into_iter().map(|cost| if cost == usize::MAX { isize::MAX } else { cost as isize }).collect())
+      .collect()
+  }
+
+  ///
+  /// Get the map for a desire. Empty if the maps haven't been built yet
+  ///
+  pub fn get(&self, desire: Desire) -> &Grid<isize> {
+    match desire {
+      Desire::Player => &self.player,
+      Desire::Items => &self.items,
+      Desire::Stairs => &self.stairs,
+      Desire::Flee => &self.flee
+    }
+  }
+
+  ///
+  /// Weigh up how much a creature wants to be at `pos`. Lower is better
+  ///
+  /// Maps that can't reach `pos`, or haven't been built, don't add anything
+  ///
+  pub fn value(&self, weights: &[(Desire, f32)], pos: Pos) -> f32 {
+    let mut total = 0.0;
+    for &(desire, weight) in weights {
+      let map = self.get(desire);
+      if pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < map.len() && (pos.y as usize) < map[0].len() {
+        let cost = map[pos.x as usize][pos.y as usize];
+        if cost != isize::MAX {
+          total += cost as f32 * weight;
+        }
+      }
+    }
+    return total;
+  }
+
+  ///
+  /// Find the step from `from` that goes furthest downhill on the weighted sum of maps, or `None` if staying put is best
+  ///
+  /// Tiles other creatures are standing on are skipped, but the player's isn't, since walking into them is an attack
+  ///
+  pub fn downhill(&self, map: &Grid<Tile>, occupancy: &Occupancy, from: Pos, weights: &[(Desire, f32)]) -> Option<Pos> {
+
+    let mut best = None;
+    let mut best_value = self.value(weights, from);
+
+    for dir in path::DIRECTIONS.iter() {
+      let next = from + *dir;
+      if path::step_cost(map, None, next, None).is_none() {
+        continue;
+      }
+      if let Some(Occupant::Creature(_)) = occupancy.get(next) {
+        continue;
+      }
+      let value = self.value(weights, next);
+      if value < best_value {
+        best = Some(next);
+        best_value = value;
+      }
+    }
+
+    return best;
+
+  }
+
+}
@@ -5,6 +5,7 @@
 //! 
 
 use super::Automaton;
+use core::rng::GameRng;
 use core::world::dungeon::map::{self, Pos, Tile};
 
 ///
@@ -36,21 +37,21 @@ impl Automaton for DrunkardsWalk {
 
   type Output = Tile;
 
-  fn apply(&self, grid: &mut map::Grid<Tile>, pos: Option<Pos>, find: Option<Tile>, replace: Tile, iterations: u32) {
+  fn apply(&self, grid: &mut map::Grid<Tile>, pos: Option<Pos>, find: Option<Tile>, replace: Tile, iterations: u32, rng: &mut GameRng) {
 
     // Get our starting x and y
 
-    let mut starting_pos = self.unwrap_pos(grid, pos);
+    let mut starting_pos = self.unwrap_pos(grid, pos, rng);
     
     // Store old dice positions. Initialize with whatever
-    let mut old_dice = self.get_d4();
+    let mut old_dice = self.get_d4(rng);
 
     for _ in 0..iterations {
 
       let dice : usize;
 
       // Generate chaos
-      let chaos = self.get_chaos();
+      let chaos = self.get_chaos(rng);
 
       // Determine order/chaos
       if chaos > self.chaos {
@@ -58,7 +59,7 @@ impl Automaton for DrunkardsWalk {
         dice = old_dice;
       } else {
         // Chaos; randomize
-        dice = self.get_d4();
+        dice = self.get_d4(rng);
         old_dice = dice;
       }
 
@@ -5,6 +5,7 @@
 extern crate rand;
 use self::rand::Rng;
 
+use core::rng::GameRng;
 use core::world::dungeon::map;
 
 // Import automatons here
@@ -36,15 +37,19 @@ pub trait Automaton {
   /// It is implied that you should initialize each builder with it's `new()` method
   /// then call this function
   /// 
-  fn apply(&self, grid: &mut map::Grid<Self::Output>, starting_pos: Option<map::Pos>, find: Option<Self::Output>, replace: Self::Output, iterations: u32);
+  fn apply(&self, grid: &mut map::Grid<Self::Output>, starting_pos: Option<map::Pos>, find: Option<Self::Output>, replace: Self::Output, iterations: u32, rng: &mut GameRng);
 
   /// 
   /// Automatically unwrap a `Pos` in a way thats suitable for most automata
   /// 
-  fn unwrap_pos(&self, grid: &map::Grid<Self::Output>, pos: Option<map::Pos>) -> map::Pos {
+  fn unwrap_pos(&self, grid: &map::Grid<Self::Output>, pos: Option<map::Pos>, rng: &mut GameRng) -> map::Pos {
     match pos {
       Some(pos) => pos,
-      None => map::Pos::from_usize(rand::thread_rng().gen_range(1, grid.len() - 2), rand::thread_rng().gen_range(1, grid[0].len() - 2))
+      None => {
+        let x = rng.gen_range(1, grid.len() - 2);
+        let y = rng.gen_range(1, grid[0].len() - 2);
+        map::Pos::from_usize(x, y)
+      }
     }
   } 
 
@@ -63,29 +68,29 @@ pub trait Automaton {
   ///
   /// Get chaos. Basically just a random number between 0 and 1
   /// 
-  fn get_chaos(&self) -> f32 {
-    rand::thread_rng().gen::<f32>()
+  fn get_chaos(&self, rng: &mut GameRng) -> f32 {
+    rng.gen::<f32>()
   }
 
   ///
   /// Get a d4 for cartesian movement
   /// 
-  fn get_d4(&self) -> usize {
-    rand::thread_rng().gen_range(1, 5)
+  fn get_d4(&self, rng: &mut GameRng) -> usize {
+    rng.gen_range(1, 5)
   }
 
   ///
   /// Get a d8 for all direction cartesian movement
   /// 
-  fn get_d8(&self) -> usize {
-    rand::thread_rng().gen_range(1, 9)
+  fn get_d8(&self, rng: &mut GameRng) -> usize {
+    rng.gen_range(1, 9)
   }
 
   ///
   /// Get a d9 for all direction cartesian movement, plus a 9th spot to represent the currently 'stood on' tile
   /// 
-  fn get_d9(&self) -> usize {
-    rand::thread_rng().gen_range(1, 10)
+  fn get_d9(&self, rng: &mut GameRng) -> usize {
+    rng.gen_range(1, 10)
   }
 
 }
\ No newline at end of file
@@ -1,6 +1,11 @@
 extern crate fuss;
 use self::fuss::Simplex;
 
+extern crate rand;
+use self::rand::Rng;
+
+use core::rng::GameRng;
+
 use core::world::dungeon::builder::Buildable;
 use core::world::dungeon::map;
 
@@ -35,14 +40,16 @@ impl Fussy {
   ///
   /// Return a new `Fussy`
   /// 
-  pub fn new(grid: map::Grid<u8>, threshold: f32) -> Self {
+  /// The noise is seeded from `rng`, the same way `Simplex::new()` would seed itself from the thread rng
+  /// 
+  pub fn new(grid: map::Grid<u8>, threshold: f32, rng: &mut GameRng) -> Self {
 
     // Make a new dungeon with our fresh grid of size `w` by `h`
     let fussy = Fussy { 
       grid: grid.clone(), 
       w: grid.len(), 
       h: grid[0].len(),
-      noise: Simplex::new(),
+      noise: Simplex::from_seed(rng.gen_iter::<usize>().take(256).collect()),
       threshold: threshold
     };
 
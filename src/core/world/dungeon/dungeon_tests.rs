@@ -1,3 +1,93 @@
-
 #[cfg(test)]
-mod tests {}
\ No newline at end of file
+mod tests {
+
+  use core::rng::GameRng;
+  use core::world::dungeon::Dungeon;
+  use core::world::dungeon::filter::{Filter, Storefront};
+  use core::world::dungeon::map::{path, tile, Pos};
+
+  // The same seed has to build the same dungeon, tile for tile
+  #[test]
+  fn same_seed_same_dungeon() {
+    let a = Dungeon::new(Pos::new(89, 39)).build(&mut GameRng::new(1234));
+    let b = Dungeon::new(Pos::new(89, 39)).build(&mut GameRng::new(1234));
+    assert!(a.grid == b.grid);
+  }
+
+  // Shops are walled in, and the only way in or out is through the door
+  #[test]
+  fn shops_have_one_way_in() {
+    let mut shops = 0;
+
+    for seed in 0..10 {
+      let dun = Dungeon::new(Pos::new(89, 39)).build(&mut GameRng::new(seed));
+      let shop = match dun.shop {
+        Some(ref shop) => shop.clone(),
+        None => continue
+      };
+      shops += 1;
+
+      assert!(tile::in_shop(&dun[shop.door]) && tile::in_shop(&dun[shop.post]));
+
+      for x in 1..dun.width - 1 {
+        for y in 1..dun.height - 1 {
+          let pos = Pos::from_usize(x, y);
+          if !tile::in_shop(&dun[pos]) {
+            continue;
+          }
+          let ways_out = (-1..2).flat_map(|dx| (-1..2).map(move |dy| Pos::new(dx, dy)))
+            .filter(|&step| !tile::in_shop(&dun[pos + step]) && tile::walkable(&dun[pos + step]))
+            .count();
+          if pos == shop.door {
+            assert!(ways_out > 0, "Nothing outside the door with seed {}", seed);
+          } else {
+            assert!(ways_out == 0, "A way out of the shop at {:?} with seed {}", pos, seed);
+            assert!(shop.stock.contains(&pos) || pos == shop.post || (pos ^ shop.door) <= 1.0, "{:?} is in the shop but not for sale", pos);
+          }
+        }
+      }
+    }
+
+    assert!(shops > 0);
+  }
+
+  // Shops never wall off part of the floor, so stairs that could reach each other before a shop went in still can
+  #[test]
+  fn shops_keep_stairs_connected() {
+    let mut shops = 0;
+
+    for seed in 0..200 {
+      let dun = Dungeon::new(Pos::new(89, 39)).build(&mut GameRng::new(seed));
+
+      let mut down = None;
+      let mut up = None;
+      for x in 0..dun.width {
+        for y in 0..dun.height {
+          match dun.grid[x][y].tiletype {
+            tile::Type::Stair(tile::Stair::DownStair(_)) => down = Some(Pos::from_usize(x, y)),
+            tile::Type::Stair(tile::Stair::UpStair(_)) => up = Some(Pos::from_usize(x, y)),
+            _ => {}
+          }
+        }
+      }
+      let (down, up) = (down.unwrap(), up.unwrap());
+
+      if path::astar(&dun.grid, None, down, up).is_none() {
+        continue;
+      }
+
+      // Try to squeeze another shop in, right on top of everything
+      let mut grid = dun.grid.clone();
+      let mut storefront = Storefront::new();
+      storefront.apply(&mut grid, &mut GameRng::new(seed));
+      if storefront.shop.is_some() {
+        shops += 1;
+      }
+
+      assert!(path::astar(&grid, None, down, up).is_some(), "A shop cut the stairs off from each other with seed {}", seed);
+    }
+
+    assert!(shops > 0);
+  }
+
+}
\ No newline at end of file
@@ -1,3 +1,4 @@
+use core::rng::GameRng;
 use core::world::dungeon::map;
 
 pub mod structure;
@@ -6,6 +7,9 @@ pub use self::structure::Structure;
 pub mod simple;
 pub use self::simple::Simple;
 
+pub mod storefront;
+pub use self::storefront::Storefront;
+
 ///
 /// `Filter` trait to define a uniform set of behavior for dungeon generation
 /// 
@@ -16,6 +20,6 @@ pub trait Filter {
   /// 
   type Output : Clone;
 
-  fn apply(&mut self, grid: &mut map::Grid<Self::Output>);
+  fn apply(&mut self, grid: &mut map::Grid<Self::Output>, rng: &mut GameRng);
 
 }
\ No newline at end of file
@@ -2,6 +2,7 @@ extern crate rand;
 use self::rand::Rng;
 
 use core::renderer::RGB;
+use core::rng::GameRng;
 
 use super::Filter;
 
@@ -33,13 +34,11 @@ impl Simple {
   ///
   /// Add rooms to the `rooms` vec and build them on the `grid`
   /// 
-  fn add_rooms(&mut self, grid: &mut Grid<Tile>) {
+  fn add_rooms(&mut self, grid: &mut Grid<Tile>, rng: &mut GameRng) {
 
     // Clear rooms
     self.rooms = Vec::<Rect>::new();
 
-    let mut rng = rand::thread_rng();
-
     // Number of rooms correspond to map size
     let n = (self.w + self.h) / 10;
 
@@ -168,10 +167,10 @@ impl Filter for Simple {
 
   type Output = Tile;
 
-  fn apply(&mut self, grid: &mut Grid<Self::Output>) {
+  fn apply(&mut self, grid: &mut Grid<Self::Output>, rng: &mut GameRng) {
 
     // Generate the dungeon
-    self.add_rooms(grid);
+    self.add_rooms(grid, rng);
     self.connect_rooms(grid);
 
   }
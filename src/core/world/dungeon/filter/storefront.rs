@@ -0,0 +1,162 @@
+extern crate rand;
+use self::rand::Rng;
+
+use core::renderer::RGB;
+use core::rng::GameRng;
+
+use super::Filter;
+
+use super::map::construct::Shop;
+use super::map::{path, Grid, Pos, tile, Tile};
+
+///
+/// How many spots to try before giving up on fitting a shop in
+///
+const SHOP_TRIES : usize = 30;
+
+///
+/// Shop placer
+///
+/// Builds a small walled room with a single door somewhere it can be walked into, and remembers it as a `Shop` so the
+/// world can stock it. The shop is never built anywhere it would cut part of the floor off from the rest, or on top of
+/// anything but walls and plain ground. If there's nowhere for the shop to go, the floor just doesn't get one.
+///
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Storefront {
+  // The shop that got built, if one did
+  pub shop: Option<Shop>
+}
+
+impl Storefront {
+
+  ///
+  /// Try to fit a shop onto the grid
+  ///
+  fn add_shop(&mut self, grid: &mut Grid<Tile>, rng: &mut GameRng) {
+
+    let total_w = grid.len() as isize;
+    let total_h = grid[0].len() as isize;
+
+    // What could be walked between before the shop went in
+    let before = path::regions(grid);
+
+    for _ in 0..SHOP_TRIES {
+
+      // Size of the inside of the shop
+      let w : isize = rng.gen_range(4, 8);
+      let h : isize = rng.gen_range(3, 6);
+
+      // Leave room for the walls, and the map edge past them
+      if total_w < w + 4 || total_h < h + 4 {
+        return;
+      }
+      let x : isize = rng.gen_range(2, total_w - w - 1);
+      let y : isize = rng.gen_range(2, total_h - h - 1);
+
+      // Pick a wall to put the door in, never in a corner. `out` points out of the shop, and `along` runs down the wall
+      let (door, out, along) = match rng.gen_range(0, 4) {
+        0 => (Pos::new(x + rng.gen_range(1, w - 1), y - 1), Pos::new(0, -1), Pos::new(1, 0)),
+        1 => (Pos::new(x + rng.gen_range(1, w - 1), y + h), Pos::new(0, 1), Pos::new(1, 0)),
+        2 => (Pos::new(x - 1, y + rng.gen_range(1, h - 1)), Pos::new(-1, 0), Pos::new(0, 1)),
+        _ => (Pos::new(x + w, y + rng.gen_range(1, h - 1)), Pos::new(1, 0), Pos::new(0, 1))
+      };
+
+      // A shop nobody can walk into isn't much of a shop
+      if !tile::spawnable(&grid[door + out]) {
+        continue;
+      }
+
+      // Anything special is left where it is
+      let footprint = (x - 1..x + w + 1).flat_map(|tx| (y - 1..y + h + 1).map(move |ty| Pos::new(tx, ty)));
+      if footprint.clone().any(|pos| !tile::spawnable(&grid[pos]) && !tile::opaque(&grid[pos])) {
+        continue;
+      }
+
+      // Wall it in, and floor it
+      let mut built = grid.clone();
+      for pos in footprint {
+        let inside = pos.x >= x && pos.x < x + w && pos.y >= y && pos.y < y + h;
+        built[pos] = if inside || pos == door {
+          tile::shop_floor()
+        } else {
+          Tile::new("Wall", ' ', RGB(40, 40, 40), RGB(33, 33, 33), tile::Type::Wall(tile::Wall::Normal))
+        };
+      }
+
+      if Storefront::splits(&before, &built) {
+        continue;
+      }
+      *grid = built;
+
+      // The shopkeeper stands beside the way in, so they don't block it
+      let entrance = door - out;
+      let post = entrance + along;
+
+      let mut stock = vec![];
+      for tx in x..x + w {
+        for ty in y..y + h {
+          let pos = Pos::new(tx, ty);
+          if pos != entrance && pos != post {
+            stock.push(pos);
+          }
+        }
+      }
+
+      self.shop = Some(Shop::new(door, post, stock));
+      return;
+
+    }
+
+  }
+
+  ///
+  /// Check if anything outside the shop that could be walked between `before` it went in can't be walked between once
+  /// it's `built`
+  ///
+  fn splits(before: &Grid<Option<usize>>, built: &Grid<Tile>) -> bool {
+
+    let after = path::regions(built);
+
+    // Where each old region ended up, as far as anything still in it goes
+    let mut moved : Vec<Option<usize>> = vec![];
+
+    for x in 0..built.len() {
+      for y in 0..built[x].len() {
+        if tile::in_shop(&built[x][y]) {
+          continue;
+        }
+        if let (Some(old), Some(new)) = (before[x][y], after[x][y]) {
+          if moved.len() <= old {
+            moved.resize(old + 1, None);
+          }
+          match moved[old] {
+            Some(region) if region != new => return true,
+            _ => moved[old] = Some(new)
+          }
+        }
+      }
+    }
+
+    return false;
+
+  }
+
+  ///
+  /// Return a new `Storefront`
+  ///
+  pub fn new() -> Self {
+    Storefront { shop: None }
+  }
+
+}
+
+impl Filter for Storefront {
+
+  type Output = Tile;
+
+  fn apply(&mut self, grid: &mut Grid<Self::Output>, rng: &mut GameRng) {
+    self.shop = None;
+    self.add_shop(grid, rng);
+  }
+
+}
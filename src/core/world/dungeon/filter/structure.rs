@@ -7,6 +7,7 @@ use std::fs;
 
 
 use core::renderer::RGB;
+use core::rng::GameRng;
 
 use super::Filter;
 use core::world::dungeon::map::{self, tile, Tile};
@@ -24,13 +25,13 @@ impl Structure {
   ///
   /// Add a random structure
   ///
-  fn add_rand_struct(&mut self, grid: &mut map::Grid<Tile>) {
-
-    // RNG
-    let mut rng = rand::thread_rng();
+  fn add_rand_struct(&mut self, grid: &mut map::Grid<Tile>, rng: &mut GameRng) {
 
     // Create a vector out of collecting the read_dir by mapping the unwrapped paths
-    let paths : Vec<_> = fs::read_dir("./strct").unwrap().map(|res| res.unwrap().path()).collect();
+    let mut paths : Vec<_> = fs::read_dir("./strct").unwrap().map(|res| res.unwrap().path()).collect();
+
+    // read_dir makes no promises about order, so sort the paths to make sure the same seed picks the same structure
+    paths.sort();
 
     // Choose a random element (aka file from paths)
     let mut file = fs::File::open(rng.choose(&paths).unwrap()).unwrap();
@@ -144,8 +145,8 @@ impl Filter for Structure {
 
   type Output = Tile;
 
-  fn apply(&mut self, grid: &mut map::Grid<Self::Output>) {
-    self.add_rand_struct(grid);
+  fn apply(&mut self, grid: &mut map::Grid<Self::Output>, rng: &mut GameRng) {
+    self.add_rand_struct(grid, rng);
   }
 
 }
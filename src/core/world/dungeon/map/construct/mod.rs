@@ -6,4 +6,7 @@ pub mod corr;
 pub use self::corr::Corr;
 
 pub mod rect;
-pub use self::rect::Rect;
\ No newline at end of file
+pub use self::rect::Rect;
+
+pub mod shop;
+pub use self::shop::Shop;
\ No newline at end of file
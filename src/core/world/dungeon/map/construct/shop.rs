@@ -0,0 +1,56 @@
+use core::world::dungeon::map::Pos;
+
+///
+/// How much shopkeepers charge over what things are worth, in percent, until the world says otherwise
+///
+pub const MARKUP_NORMAL : usize = 125;
+
+///
+/// How many times a shopkeeper will put up with haggling
+///
+pub const PATIENCE_NORMAL : usize = 3;
+
+///
+/// Struct to represent a shop that's been built into a `Dungeon`
+/// 
+/// The dungeon only builds the room. What's for sale and who's selling it is up to the world, so the shop just
+/// remembers where everything goes, and once the world opens it, who's minding it and how they feel about the player.
+/// 
+/// * `door` - The gap in the wall the shop is entered through
+/// * `post` - Where the shopkeeper stands, just inside the door and out of the way
+/// * `stock` - Every spot inside the shop that something can be put up for sale on
+/// * `keeper` - Index of the shopkeeper in the floor's creatures, if the shop is open and they're still alive
+/// * `markup` - What the shopkeeper charges over what things are worth, in percent
+/// * `patience` - How many more times the shopkeeper will haggle
+/// * `angry` - Whether the player has wronged the shopkeeper
+/// 
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct Shop {
+  pub door: Pos,
+  pub post: Pos,
+  pub stock: Vec<Pos>,
+  pub keeper: Option<usize>,
+  pub markup: usize,
+  pub patience: usize,
+  pub angry: bool
+}
+
+impl Shop {
+
+  /// 
+  /// Return a new `Shop`
+  /// 
+  #[inline]
+  pub fn new(door: Pos, post: Pos, stock: Vec<Pos>) -> Self {
+    return Shop {
+      door: door,
+      post: post,
+      stock: stock,
+      keeper: None,
+      markup: MARKUP_NORMAL,
+      patience: PATIENCE_NORMAL,
+      angry: false
+    };
+  }
+
+}
@@ -0,0 +1,53 @@
+//!
+//! Things that give off a smell or a sound all on their own
+//!
+//! Creatures leave scent wherever they go and make noise when they move, but plenty of other things should too. An
+//! `Emitter` can be put on a `Tile` or an `Item`, and every turn the world picks up every emitter on the floor along with
+//! the creatures, so that a brazier fills a room with smoke and a rotting corpse draws in whatever likes the smell of decay.
+//!
+
+use super::tile;
+
+///
+/// How loud the water that makes noise is
+///
+pub const BABBLING_LOUDNESS : usize = 3;
+
+///
+/// Something that smells, makes noise, or both, every turn
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Emitter {
+  // What it smells of, if anything
+  pub scent: Option<tile::Scent>,
+  // How loud it is, or 0 if it's silent
+  pub loudness: usize
+}
+
+impl Emitter {
+
+  ///
+  /// Return a new `Emitter`
+  ///
+  pub fn new(scent: Option<tile::Scent>, loudness: usize) -> Self {
+    Emitter {
+      scent: scent,
+      loudness: loudness
+    }
+  }
+
+  ///
+  /// Return a new `Emitter` that only smells
+  ///
+  pub fn smell(scent: tile::Scent) -> Self {
+    Emitter::new(Some(scent), 0)
+  }
+
+  ///
+  /// Return a new `Emitter` that only makes noise
+  ///
+  pub fn sound(loudness: usize) -> Self {
+    Emitter::new(None, loudness)
+  }
+
+}
@@ -0,0 +1,32 @@
+//!
+//! Maps shared by the tests of everything that works on a `Grid`
+//!
+//! Pathing, sound and scent are all tested on small open rooms with a wall or two put up in them, so the rooms are built
+//! here instead of in each of their tests.
+//!
+
+use core::renderer::RGB;
+use core::world::dungeon::map::{tile, Grid, Tile};
+
+///
+/// An open `width` by `height` room
+///
+pub fn room(width: usize, height: usize) -> Grid<Tile> {
+  vec![vec![tile::generic_floor(); height]; width]
+}
+
+///
+/// A plain tile of `tiletype`, for when only what kind of tile it is matters
+///
+pub fn plain(tiletype: tile::Type) -> Tile {
+  Tile::new("Test", ' ', RGB(0, 0, 0), RGB(0, 0, 0), tiletype)
+}
+
+///
+/// Put `wall` all the way down column `x` of `map`
+///
+pub fn wall_down(map: &mut Grid<Tile>, x: usize, wall: &Tile) {
+  for t in map[x].iter_mut() {
+    *t = wall.clone();
+  }
+}
@@ -4,11 +4,26 @@
 
 pub mod construct;
 
+pub mod emitter;
+pub use self::emitter::Emitter;
+
 pub mod grid;
 pub use self::grid::Grid;
 
+// Maps shared by the tests of everything that works on a grid
+#[cfg(test)]
+pub mod grid_tests;
+
+pub mod path;
+
+mod path_tests;
+
 pub mod pos;
 pub use self::pos::Pos;
 
+pub mod sound;
+
+mod sound_tests;
+
 pub mod tile;
 pub use self::tile::Tile;
\ No newline at end of file
@@ -0,0 +1,271 @@
+//!
+//! Find ways across a map
+//!
+//! Everything here works on a `Grid<Tile>`, where each step goes to one of the 8 tiles around the current one and costs
+//! whatever `tile::move_cost()` says it costs to walk onto. Tiles without a cost can't be crossed.
+//!
+//! When given an `Occupancy`, tiles with someone standing on them cost extra to walk onto rather than being off limits. That
+//! way creatures walk around each other when there's room to, but still queue up behind each other in corridors instead of
+//! giving up on the path altogether.
+//!
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::{isize, usize};
+
+use core::world::occupancy::Occupancy;
+
+use super::{tile, Grid, Pos, Tile};
+
+///
+/// Extra cost of walking onto a tile someone is standing on
+///
+pub const OCCUPIED_COST : usize = tile::MOVE_COST_NORMAL * 4;
+
+///
+/// How much further than the threat is close fleeing creatures would like to be, as a fraction. Anything above 1 lets them
+/// double back past the threat when there's more room behind it
+///
+const FLEE_NUMERATOR : isize = 6;
+const FLEE_DENOMINATOR : isize = 5;
+
+///
+/// Every direction a step can be taken in
+///
+pub const DIRECTIONS : [Pos; 8] = [
+  Pos { x: -1, y: -1 }, Pos { x: 0, y: -1 }, Pos { x: 1, y: -1 },
+  Pos { x: -1, y: 0 },                       Pos { x: 1, y: 0 },
+  Pos { x: -1, y: 1 },  Pos { x: 0, y: 1 },  Pos { x: 1, y: 1 }
+];
+
+///
+/// Check if `pos` is on the map
+///
+#[inline]
+pub fn in_bounds(map: &Grid<Tile>, pos: Pos) -> bool {
+  pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < map.len() && (pos.y as usize) < map[0].len()
+}
+
+///
+/// Get the cost of stepping onto `pos`, or `None` if it can't be stepped on
+///
+/// Whoever is standing on `goal` is what the path is trying to reach, so they don't make it cost any more
+///
+pub fn step_cost(map: &Grid<Tile>, occupancy: Option<&Occupancy>, pos: Pos, goal: Option<Pos>) -> Option<usize> {
+
+  if !in_bounds(map, pos) {
+    return None;
+  }
+
+  let cost = tile::move_cost(&map[pos])?;
+
+  match occupancy {
+    Some(occupancy) if Some(pos) != goal && !occupancy.is_free(pos) => Some(cost + OCCUPIED_COST),
+    _ => Some(cost)
+  }
+
+}
+
+///
+/// Guess how much it costs to get from `a` to `b`. Never guesses high, which is what keeps A* paths as cheap as possible
+///
+#[inline]
+fn heuristic(a: Pos, b: Pos) -> usize {
+  let dx = (a.x - b.x).abs() as usize;
+  let dy = (a.y - b.y).abs() as usize;
+  (if dx > dy { dx } else { dy }) * tile::MOVE_COST_NORMAL
+}
+
+///
+/// Find the cheapest path from `from` to `to` with A*
+///
+/// The path starts with the first step away from `from` and ends on `to`, so it's empty if they're the same tile. Returns
+/// `None` if there's no way to get there
+///
+pub fn astar(map: &Grid<Tile>, occupancy: Option<&Occupancy>, from: Pos, to: Pos) -> Option<Vec<Pos>> {
+
+  if !in_bounds(map, from) || step_cost(map, None, to, None).is_none() {
+    return None;
+  }
+
+  let width = map.len();
+  let height = map[0].len();
+
+  let mut cost = vec![vec![usize::MAX; height]; width];
+  let mut came_from : Grid<Option<Pos>> = vec![vec![None; height]; width];
+
+  // Ordered by estimated total cost, then position so that ties always break the same way
+  let mut open = BinaryHeap::new();
+
+  cost[from.x as usize][from.y as usize] = 0;
+  open.push(Reverse((heuristic(from, to), from.x, from.y)));
+
+  while let Some(Reverse((_, x, y))) = open.pop() {
+
+    let current = Pos::new(x, y);
+    if current == to {
+      break;
+    }
+
+    let current_cost = cost[x as usize][y as usize];
+
+    for dir in DIRECTIONS.iter() {
+      let next = current + *dir;
+      if let Some(step) = step_cost(map, occupancy, next, Some(to)) {
+        let next_cost = current_cost + step;
+        if next_cost < cost[next.x as usize][next.y as usize] {
+          cost[next.x as usize][next.y as usize] = next_cost;
+          came_from[next.x as usize][next.y as usize] = Some(current);
+          open.push(Reverse((next_cost + heuristic(next, to), next.x, next.y)));
+        }
+      }
+    }
+
+  }
+
+  if cost[to.x as usize][to.y as usize] == usize::MAX {
+    return None;
+  }
+
+  // Walk back from the end to build the path
+  let mut path = vec![];
+  let mut current = to;
+  while current != from {
+    path.push(current);
+    current = came_from[current.x as usize][current.y as usize].unwrap();
+  }
+  path.reverse();
+
+  return Some(path);
+
+}
+
+///
+/// Split the map up into regions that can be walked between, numbering each tile by the region it's in
+///
+/// Tiles that can't be crossed aren't in any region
+///
+pub fn regions(map: &Grid<Tile>) -> Grid<Option<usize>> {
+
+  let width = map.len();
+  let height = map[0].len();
+
+  let mut region = vec![vec![None; height]; width];
+  let mut count = 0;
+
+  for x in 0..width {
+    for y in 0..height {
+
+      if region[x][y].is_some() || tile::move_cost(&map[x][y]).is_none() {
+        continue;
+      }
+
+      // Fill out everything that can be reached from here
+      region[x][y] = Some(count);
+      let mut open = vec![Pos::from_usize(x, y)];
+      while let Some(current) = open.pop() {
+        for dir in DIRECTIONS.iter() {
+          let next = current + *dir;
+          if step_cost(map, None, next, None).is_some() && region[next.x as usize][next.y as usize].is_none() {
+            region[next.x as usize][next.y as usize] = Some(count);
+            open.push(next);
+          }
+        }
+      }
+
+      count += 1;
+
+    }
+  }
+
+  return region;
+
+}
+
+///
+/// Build a map of how much it costs to get from each tile to the closest of `goals` with Dijkstra's algorithm
+///
+/// Tiles that can't reach any goal are `usize::MAX`
+///
+pub fn dijkstra(map: &Grid<Tile>, occupancy: Option<&Occupancy>, goals: &[Pos]) -> Grid<usize> {
+  let seeds = goals.iter().map(|goal| (*goal, 0)).collect();
+  flood(map, occupancy, seeds).into_iter()
+    .map(|column| column.into_iter().map(|cost| if cost == isize::MAX { usize::MAX } else { cost as usize }).collect())
+    .collect()
+}
+
+///
+/// Build a map for running away from whatever `toward` leads to
+///
+/// Simply flipping the sign of `toward` would send anything fleeing into the nearest dead end. Instead the flipped costs
+/// are scaled up a little and flooded again, so that the way downhill sometimes leads past the threat towards somewhere
+/// much further away from it. Tiles that can't be reached are `isize::MAX`
+///
+pub fn flee(map: &Grid<Tile>, occupancy: Option<&Occupancy>, toward: &Grid<usize>) -> Grid<isize> {
+  let mut seeds = vec![];
+  for (x, column) in toward.iter().enumerate() {
+    for (y, cost) in column.iter().enumerate() {
+      if *cost != usize::MAX {
+        seeds.push((Pos::from_usize(x, y), -(*cost as isize) * FLEE_NUMERATOR / FLEE_DENOMINATOR));
+      }
+    }
+  }
+  flood(map, occupancy, seeds)
+}
+
+///
+/// Spread costs out from `seeds`, each of which starts at its own cost, with Dijkstra's algorithm
+///
+/// Tiles that can't be reached from any seed are `isize::MAX`
+///
+fn flood(map: &Grid<Tile>, occupancy: Option<&Occupancy>, seeds: Vec<(Pos, isize)>) -> Grid<isize> {
+
+  let width = map.len();
+  let height = map[0].len();
+
+  let mut cost = vec![vec![isize::MAX; height]; width];
+  let mut seeded = vec![vec![false; height]; width];
+  let mut open = BinaryHeap::new();
+
+  for (seed, seed_cost) in seeds.into_iter().filter(|&(seed, _)| in_bounds(map, seed)) {
+    if seed_cost < cost[seed.x as usize][seed.y as usize] {
+      cost[seed.x as usize][seed.y as usize] = seed_cost;
+      seeded[seed.x as usize][seed.y as usize] = true;
+      open.push(Reverse((seed_cost, seed.x, seed.y)));
+    }
+  }
+
+  while let Some(Reverse((current_cost, x, y))) = open.pop() {
+
+    // Already found a cheaper way here
+    if current_cost > cost[x as usize][y as usize] {
+      continue;
+    }
+
+    let current = Pos::new(x, y);
+
+    // Costs flow outwards from the seeds, but walking goes the other way, so what counts is the cost of stepping onto
+    // the tile closer to the seed. Whoever is standing on a seed is what's being walked to, so they don't count
+    let goal = if seeded[x as usize][y as usize] { Some(current) } else { None };
+    let step = match step_cost(map, occupancy, current, goal) {
+      Some(step) => step as isize,
+      None => continue
+    };
+
+    for dir in DIRECTIONS.iter() {
+      let next = current + *dir;
+      if step_cost(map, None, next, None).is_some() {
+        let next_cost = current_cost + step;
+        if next_cost < cost[next.x as usize][next.y as usize] {
+          cost[next.x as usize][next.y as usize] = next_cost;
+          seeded[next.x as usize][next.y as usize] = false;
+          open.push(Reverse((next_cost, next.x, next.y)));
+        }
+      }
+    }
+
+  }
+
+  return cost;
+
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+
+  use std::usize;
+
+  use core::creature::{Creature, Stats};
+  use core::creature::ai::{AI, Context, PlayerAI, TrackerAI};
+  use core::renderer::RGB;
+  use core::rng::GameRng;
+  use core::world::dungeon::map::{grid_tests, path, tile, Grid, Pos, Tile};
+  use core::world::desire::DesireMaps;
+  use core::world::scent::ScentMap;
+  use core::world::occupancy::Occupancy;
+
+  // An open 10x10 room
+  fn room() -> Grid<Tile> {
+    grid_tests::room(10, 10)
+  }
+
+  // A wall along x = 5 with a single gap at the bottom
+  fn walled_room() -> Grid<Tile> {
+    let mut map = room();
+    grid_tests::wall_down(&mut map, 5, &tile::generic_wall());
+    map[5][9] = tile::generic_floor();
+    map
+  }
+
+  fn creature(pos: Pos) -> Creature {
+    Creature::new("rat", 'r', pos, RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(1, 0, 0, 10, 0, tile::Scent::Decay), PlayerAI::new())
+  }
+
+  // Nothing in the way means a straight line
+  #[test]
+  fn astar_straight() {
+    // Diagonal steps cost the same as straight ones, so any three steps that get there will do
+    let path = path::astar(&room(), None, Pos::new(1, 1), Pos::new(4, 1)).unwrap();
+    assert_eq!(path.len(), 3);
+    assert_eq!(path[2], Pos::new(4, 1));
+    assert_eq!(path::astar(&room(), None, Pos::new(1, 1), Pos::new(1, 1)).unwrap(), vec![]);
+  }
+
+  // Walls get walked around, and walled off goals can't be reached
+  #[test]
+  fn astar_around_walls() {
+    let map = walled_room();
+    let path = path::astar(&map, None, Pos::new(2, 2), Pos::new(8, 2)).unwrap();
+    assert!(path.contains(&Pos::new(5, 9)));
+    for pos in &path {
+      assert!(tile::walkable(&map[*pos]));
+    }
+
+    let mut map = walled_room();
+    map[5][9] = tile::generic_wall();
+    assert_eq!(path::astar(&map, None, Pos::new(2, 2), Pos::new(8, 2)), None);
+  }
+
+  // Expensive tiles are worth a short detour
+  #[test]
+  fn astar_avoids_costly_tiles() {
+    let mut map = room();
+    for y in 0..8 {
+      map[5][y] = Tile::new("Water", '~', RGB(0, 0, 0), RGB(0, 0, 0), tile::Type::Water);
+    }
+
+    // Going around the water costs less than wading across it
+    let path = path::astar(&map, None, Pos::new(4, 7), Pos::new(6, 7)).unwrap();
+    assert_eq!(path, vec![Pos::new(5, 8), Pos::new(6, 7)]);
+
+    // But not a long one
+    let path = path::astar(&map, None, Pos::new(4, 1), Pos::new(6, 1)).unwrap();
+    assert_eq!(path.len(), 2);
+    assert!(path[0].y < 8);
+  }
+
+  // Crowded tiles are walked around when there's room
+  #[test]
+  fn astar_avoids_creatures() {
+    let map = room();
+    let player = creature(Pos::new(9, 9));
+    let creatures = vec![Box::new(creature(Pos::new(2, 1)))];
+    let occupancy = Occupancy::build(10, 10, &player, &creatures);
+
+    let path = path::astar(&map, Some(&occupancy), Pos::new(1, 1), Pos::new(3, 1)).unwrap();
+    assert!(!path.contains(&Pos::new(2, 1)));
+
+    // Whoever is standing on the goal doesn't count
+    let path = path::astar(&map, Some(&occupancy), Pos::new(8, 8), Pos::new(9, 9)).unwrap();
+    assert_eq!(path, vec![Pos::new(9, 9)]);
+  }
+
+  // Dijkstra maps count the cost to the nearest goal
+  #[test]
+  fn dijkstra_distances() {
+    let map = walled_room();
+    let costs = path::dijkstra(&map, None, &[Pos::new(0, 0), Pos::new(9, 0)]);
+
+    assert_eq!(costs[0][0], 0);
+    assert_eq!(costs[3][3], 3 * tile::MOVE_COST_NORMAL);
+    assert_eq!(costs[9][4], 4 * tile::MOVE_COST_NORMAL);
+    assert_eq!(costs[5][0], usize::MAX);
+  }
+
+  // Trackers find their way around walls to the player
+  #[test]
+  fn tracker_follows_paths() {
+    let map = walled_room();
+    let mut rng = GameRng::new(0);
+    let player = creature(Pos::new(8, 2));
+    let creatures = vec![];
+    let mut me = creature(Pos::new(2, 2));
+    let mut ai = TrackerAI::new();
+
+    for _ in 0..20 {
+      let occupancy = Occupancy::build(10, 10, &player, &creatures);
+      let mut ctx = Context {
+        map: &map,
+        player: &player,
+        creatures: &creatures,
+        items: &[],
+        occupancy: &occupancy,
+        desire: &DesireMaps::default(),
+        scent: &ScentMap::default(),
+        shop: None,
+        floor_num: 0,
+        me: None,
+        rng: &mut rng
+      };
+      let last = me.actor.pos;
+      ai.take_turn(&mut ctx, &mut me.actor, &mut me.stats);
+      assert!(tile::walkable(&map[me.actor.pos]));
+      assert!((me.actor.pos.x - last.x).abs() <= 1 && (me.actor.pos.y - last.y).abs() <= 1);
+      if me.actor.pos == player.actor.pos {
+        break;
+      }
+    }
+
+    assert_eq!(me.actor.pos, player.actor.pos);
+  }
+
+}
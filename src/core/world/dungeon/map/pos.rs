@@ -13,7 +13,7 @@ use std::ops::{Add, AddAssign, BitXor, Neg, Sub, SubAssign};
 /// `x` - x axis location
 /// `y` - y axis location
 /// 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
 pub struct Pos {
   pub x: isize,
   pub y: isize,
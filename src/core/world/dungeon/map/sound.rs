@@ -0,0 +1,101 @@
+//!
+//! Carry sound across a map
+//!
+//! Each noise floods outwards from where it was made, getting quieter with every tile it passes through by however much
+//! `tile::sound_damping()` says that tile muffles it. Tiles that don't let sound through stop it outright, so noises go
+//! around corners and down corridors instead of straight through rock. A noise stops spreading once it's too quiet to hear,
+//! so the work done only depends on how far the noise carries and not on the size of the map.
+//!
+//! When more than one noise reaches a tile they add up, the same as they would before.
+//!
+
+use std::collections::BinaryHeap;
+
+use super::path::{in_bounds, DIRECTIONS};
+use super::{tile, Grid, Pos, Tile};
+
+///
+/// Fill in `sound` on every tile of `map` from the given `(position, loudness)` sources
+///
+/// Returns how many tiles the noises reached between them, counting a tile once for every noise that reached it. Each of
+/// those is spread from once, which is all the work done apart from clearing last turn's sound
+///
+pub fn propagate(map: &mut Grid<Tile>, sources: &[(Pos, usize)]) -> usize {
+
+  if map.is_empty() {
+    return 0;
+  }
+
+  let width = map.len();
+  let height = map[0].len();
+
+  for column in map.iter_mut() {
+    for t in column.iter_mut() {
+      t.sound = 0;
+    }
+  }
+
+  // Scratch space shared by every source. Only the tiles a noise reached get cleared afterwards, which is what keeps this
+  // from costing a whole pass over the map per source
+  let mut heard = vec![vec![0; height]; width];
+  let mut reached = vec![];
+  let mut work = 0;
+
+  for &(source, loudness) in sources {
+    if loudness == 0 || !in_bounds(map, source) {
+      continue;
+    }
+    flood(map, &mut heard, &mut reached, source, loudness);
+    work += reached.len();
+    for pos in reached.drain(..) {
+      map[pos].sound += heard[pos.x as usize][pos.y as usize];
+      heard[pos.x as usize][pos.y as usize] = 0;
+    }
+  }
+
+  return work;
+
+}
+
+///
+/// Spread a single noise out from `source`, loudest first, recording how loud it is on every tile it reaches
+///
+fn flood(map: &Grid<Tile>, heard: &mut Grid<usize>, reached: &mut Vec<Pos>, source: Pos, loudness: usize) {
+
+  let mut open = BinaryHeap::new();
+
+  heard[source.x as usize][source.y as usize] = loudness;
+  reached.push(source);
+  open.push((loudness, source.x, source.y));
+
+  while let Some((current, x, y)) = open.pop() {
+
+    // Already heard louder here
+    if current < heard[x as usize][y as usize] {
+      continue;
+    }
+
+    for dir in DIRECTIONS.iter() {
+      let next = Pos::new(x, y) + *dir;
+      if !in_bounds(map, next) {
+        continue;
+      }
+      let damping = match tile::sound_damping(&map[next]) {
+        Some(damping) => damping,
+        None => continue
+      };
+      // Diagonals are further away, which keeps noises from spreading out in squares
+      let damping = if dir.x != 0 && dir.y != 0 { damping + damping / 2 } else { damping };
+      let next_loudness = current.saturating_sub(damping);
+      if next_loudness > heard[next.x as usize][next.y as usize] {
+        if heard[next.x as usize][next.y as usize] == 0 {
+          reached.push(next);
+        }
+        heard[next.x as usize][next.y as usize] = next_loudness;
+        open.push((next_loudness, next.x, next.y));
+      }
+    }
+
+  }
+
+}
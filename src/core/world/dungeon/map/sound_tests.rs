@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+
+  use core::world::dungeon::map::{sound, tile, Grid, Pos, Tile};
+  use core::world::dungeon::map::grid_tests::{self, room};
+
+  // A 20x20 room split down x = 10 by a wall of `tiletype`
+  fn divided(tiletype: tile::Type) -> Grid<Tile> {
+    let mut map = room(20, 20);
+    grid_tests::wall_down(&mut map, 10, &grid_tests::plain(tiletype));
+    map
+  }
+
+  // Open ground carries sound, getting quieter the further it goes
+  #[test]
+  fn sound_spreads() {
+    let mut map = room(20, 20);
+    map[0][0].sound = 50;
+    sound::propagate(&mut map, &[(Pos::new(10, 10), 20)]);
+
+    let damping = tile::SOUND_DAMPING_OPEN;
+    assert_eq!(map[10][10].sound, 20);
+    assert_eq!(map[12][10].sound, 20 - 2 * damping);
+    assert_eq!(map[10][7].sound, 20 - 3 * damping);
+    assert_eq!(map[11][11].sound, 20 - damping - damping / 2);
+    assert!(map[11][11].sound < map[11][10].sound);
+
+    // Last turn's sound is gone, and so is anything too far away to hear
+    assert_eq!(map[0][0].sound, 0);
+    assert_eq!(map[19][19].sound, 0);
+  }
+
+  // Solid rock stops sound, ordinary walls muffle it, and it finds its way around through gaps
+  #[test]
+  fn walls_block_sound() {
+    let source = [(Pos::new(8, 10), 20)];
+
+    let mut open = room(20, 20);
+    sound::propagate(&mut open, &source);
+
+    let mut hard = divided(tile::Type::Wall(tile::Wall::Hard));
+    sound::propagate(&mut hard, &source);
+    for x in 11..20 {
+      for y in 0..20 {
+        assert_eq!(hard[x][y].sound, 0);
+      }
+    }
+
+    let mut normal = divided(tile::Type::Wall(tile::Wall::Normal));
+    sound::propagate(&mut normal, &source);
+    assert!(normal[11][10].sound > 0);
+    assert!(normal[11][10].sound < open[11][10].sound);
+
+    let mut gap = divided(tile::Type::Wall(tile::Wall::Hard));
+    gap[10][12] = tile::generic_floor();
+    sound::propagate(&mut gap, &source);
+    assert!(gap[11][12].sound > 0);
+    assert!(gap[11][12].sound > gap[11][2].sound);
+  }
+
+  // Each material muffles sound by a different amount
+  #[test]
+  fn materials_dampen_sound() {
+    let source = [(Pos::new(8, 10), 20)];
+    let heard = |tiletype: tile::Type| -> usize {
+      let mut map = divided(tiletype);
+      sound::propagate(&mut map, &source);
+      map[12][10].sound
+    };
+
+    let crystal = heard(tile::Type::Wall(tile::Wall::Crystal));
+    let normal = heard(tile::Type::Wall(tile::Wall::Normal));
+    let water = heard(tile::Type::Water);
+    let floor = heard(tile::Type::Floor(tile::Floor::Normal));
+    let grass = heard(tile::Type::TallGrass);
+
+    assert!(water > floor);
+    assert!(floor > grass);
+    assert!(crystal > normal);
+    assert!(normal > 0);
+  }
+
+  // Noises that reach the same tile add up
+  #[test]
+  fn sounds_add_up() {
+    let mut map = room(20, 20);
+    sound::propagate(&mut map, &[(Pos::new(5, 5), 10), (Pos::new(7, 5), 10)]);
+    assert_eq!(map[6][5].sound, 2 * (10 - tile::SOUND_DAMPING_OPEN));
+
+    // Noises off the map, or that make no sound, don't do anything
+    assert_eq!(sound::propagate(&mut map, &[(Pos::new(-1, 5), 10), (Pos::new(5, 5), 0)]), 0);
+    assert!(map.iter().all(|column| column.iter().all(|t| t.sound == 0)));
+  }
+
+  // A noise only costs the tiles it reaches, however big the map is. Spreading every noise over the whole map, as it used
+  // to, would cost sources x width x height
+  #[test]
+  fn work_stays_near_the_noise() {
+    // Loud enough to carry 12 tiles across open ground, and far enough from the edges of the map to get that far
+    let sources : Vec<(Pos, usize)> = (0..200).map(|i| (Pos::new(20 + (i * 37) % 160, 20 + (i * 91) % 160), 25)).collect();
+    let reach = (25 - 1) / tile::SOUND_DAMPING_OPEN;
+
+    let mut big = room(200, 200);
+    let work = sound::propagate(&mut big, &sources);
+    assert!(work > 0);
+    assert!(work <= sources.len() * (2 * reach + 1) * (2 * reach + 1));
+    assert!(work * 50 < sources.len() * 200 * 200);
+
+    let mut bigger = room(400, 400);
+    assert_eq!(sound::propagate(&mut bigger, &sources), work);
+
+    // Shut in 4x4 cells of solid rock, every noise only fills its own cell
+    let rock = grid_tests::plain(tile::Type::Wall(tile::Wall::Hard));
+    let mut cells = room(200, 200);
+    for x in 0..200 {
+      for y in 0..200 {
+        if x % 5 == 0 || y % 5 == 0 {
+          cells[x][y] = rock.clone();
+        }
+      }
+    }
+    let inside : Vec<(Pos, usize)> = sources.iter()
+      .filter(|&&(pos, _)| pos.x % 5 != 0 && pos.y % 5 != 0)
+      .cloned()
+      .collect();
+    assert!(!inside.is_empty());
+    assert_eq!(sound::propagate(&mut cells, &inside), inside.len() * 4 * 4);
+  }
+
+}
@@ -2,11 +2,14 @@ use std::fmt;
 use std::slice::Iter;
 
 use core::renderer::{Renderable, RGB};
+use core::save;
+
+use super::emitter::Emitter;
 
 ///
 /// Tiles have types
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Type {
   Wall(Wall),
   Floor(Floor),
@@ -16,22 +19,25 @@ pub enum Type {
   Water,
   Unseen,
   Trap(Trap),
+  Fixture(Fixture),
   Debug
 }
 
 ///
 /// Floors have types
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Floor {
   Normal,
-  Crystal
+  Crystal,
+  // Everything lying on a shop floor is for sale
+  Shop
 }
 
 ///
 /// Walls have types
 /// 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Wall {
   Normal,
   Crystal,
@@ -41,7 +47,7 @@ pub enum Wall {
 ///
 /// Traps have types
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Trap {
   MemoryLoss,
   Shaft,
@@ -49,10 +55,19 @@ pub enum Trap {
   Teleport
 }
 
+///
+/// Fixtures have types
+///
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Fixture {
+  Brazier,
+  Incense
+}
+
 ///
 /// Stairs have types
 /// 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Stair {
   DownStair(DownStair),
   UpStair(UpStair)
@@ -61,12 +76,12 @@ pub enum Stair {
 ///
 /// Up/Down stairs have types
 /// 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum DownStair {
   Normal
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum UpStair {
   Normal
 }
@@ -75,24 +90,54 @@ pub enum UpStair {
 /// Properties
 /// 
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Props {
   Visibility(Visibility),
   Traversability(Traversability)
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Visibility {
   Opaque,
   Transparent
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Traversability {
   Walkable,
   Blocking
 }
 
+///
+/// Cost of crossing a plain floor tile. Everything else that can be crossed costs some multiple of it
+///
+pub const MOVE_COST_NORMAL : usize = 1;
+
+///
+/// How much quieter sound gets crossing a plain floor tile. Water carries it further, and everything else muffles it more
+///
+pub const SOUND_DAMPING_OPEN : usize = 2;
+
+///
+/// How much of its scent a tile keeps each turn. Currently 255/256
+///
+pub const SCENT_DECAY_NORMAL : f32 = 0.99609375;
+
+///
+/// How much of its scent water keeps each turn, which isn't much
+///
+pub const SCENT_DECAY_WATER : f32 = 0.5;
+
+///
+/// How much of its scent cave ground keeps each turn, a little less than dry stone
+///
+pub const SCENT_DECAY_CAVE : f32 = 0.99;
+
+///
+/// How much of its scent sunken ground keeps each turn, since it is too damp to hold onto it for long
+///
+pub const SCENT_DECAY_SUNKEN : f32 = 0.97;
+
 ///
 /// Tile type helper functions
 /// 
@@ -111,9 +156,45 @@ pub fn opaque(t: &Tile) -> bool {
   }
 }
 
-// Is it okay to spawn stuff on this tile / replace it?
+// How much effort does it take to cross the tile, if it can be crossed at all? Open ground costs `MOVE_COST_NORMAL`
+pub fn move_cost(t: &Tile) -> Option<usize> {
+  match t.tiletype {
+    Type::Floor(_) | Type::Stair(_) | Type::Trap(_) | Type::Fixture(_) => Some(MOVE_COST_NORMAL),
+    Type::TallGrass => Some(MOVE_COST_NORMAL * 2),
+    Type::Vine | Type::Water => Some(MOVE_COST_NORMAL * 3),
+    _ => None
+  }
+}
+
+// How much quieter does sound get passing through the tile, if it gets through at all? Open ground takes `SOUND_DAMPING_OPEN`
+pub fn sound_damping(t: &Tile) -> Option<usize> {
+  match t.tiletype {
+    Type::Water => Some(SOUND_DAMPING_OPEN / 2),
+    Type::Floor(_) | Type::Stair(_) | Type::Trap(_) | Type::Fixture(_) | Type::Unseen | Type::Debug => Some(SOUND_DAMPING_OPEN),
+    Type::TallGrass | Type::Vine => Some(SOUND_DAMPING_OPEN * 2),
+    Type::Wall(Wall::Crystal) => Some(SOUND_DAMPING_OPEN * 2),
+    Type::Wall(Wall::Normal) => Some(SOUND_DAMPING_OPEN * 5),
+    Type::Wall(Wall::Hard) => None
+  }
+}
+
+// How much of its scent does the tile keep each turn, if it can hold scent at all? Damp biomes don't hold onto it as well
+pub fn scent_decay(t: &Tile) -> Option<f32> {
+  match t.tiletype {
+    Type::Wall(_) => None,
+    Type::Water => Some(SCENT_DECAY_WATER),
+    _ => match t.biome {
+      Biome::Dungeon | Biome::Crypt | Biome::Crystal => Some(SCENT_DECAY_NORMAL),
+      Biome::Cave => Some(SCENT_DECAY_CAVE),
+      Biome::Sunken => Some(SCENT_DECAY_SUNKEN)
+    }
+  }
+}
+
+// Is it okay to spawn stuff on this tile / replace it? Shops are stocked by the world, so nothing turns up in them by chance
 pub fn spawnable(t: &Tile) -> bool {
   match t.tiletype {
+    Type::Floor(Floor::Shop) => false,
     Type::Floor(_) | Type::Water | Type::TallGrass | Type::Vine => true,
     _ => false
   }
@@ -122,11 +203,16 @@ pub fn spawnable(t: &Tile) -> bool {
 // Is the tile able to be walked on?
 pub fn walkable(t: &Tile) -> bool {
   match t.tiletype {
-    Type::Floor(_) | Type::Water | Type::Stair(_) | Type::Trap(_) | Type::TallGrass | Type::Vine => true,
+    Type::Floor(_) | Type::Water | Type::Stair(_) | Type::Trap(_) | Type::Fixture(_) | Type::TallGrass | Type::Vine => true,
     _ => false
   }
 }
 
+// Is the tile part of a shop?
+pub fn in_shop(t: &Tile) -> bool {
+  t.tiletype == Type::Floor(Floor::Shop)
+}
+
 ///
 /// Archetypal floor patterns
 /// 
@@ -141,6 +227,16 @@ pub fn generic_floor() -> Tile {
   )
 }
 
+pub fn shop_floor() -> Tile {
+  Tile::new(
+    "Shop Floor",
+    '.',
+    RGB(90, 70, 40),
+    RGB(30, 24, 16),
+    Type::Floor(Floor::Shop)
+  )
+}
+
 pub fn generic_wall() -> Tile {
   Tile::new(
     "Generic Wall",
@@ -151,10 +247,38 @@ pub fn generic_wall() -> Tile {
   )
 }
 
+///
+/// Fixtures, which give off smells and sounds of their own
+///
+
+pub fn brazier() -> Tile {
+  let mut t = Tile::new(
+    "Brazier",
+    '&',
+    RGB(255, 120, 0),
+    RGB(0, 0, 0),
+    Type::Fixture(Fixture::Brazier)
+  );
+  t.emitter = Some(Emitter::new(Some(Scent::Smoke), 2));
+  return t;
+}
+
+pub fn incense_burner() -> Tile {
+  let mut t = Tile::new(
+    "Incense Burner",
+    '&',
+    RGB(180, 120, 220),
+    RGB(0, 0, 0),
+    Type::Fixture(Fixture::Incense)
+  );
+  t.emitter = Some(Emitter::smell(Scent::Incense));
+  return t;
+}
+
 ///
 /// Tiles have biomes
 ///
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Biome {
   Dungeon,
   Crypt,
@@ -179,7 +303,7 @@ impl fmt::Display for Biome {
 ///
 /// Scents
 /// 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Scent {
   Player = 0,
   Insectoid,
@@ -229,40 +353,32 @@ impl Scent {
     ];
     SCENT_TYPES.into_iter()
   }
-}
 
-#[derive(Clone, PartialEq, Eq, Debug)]
-pub struct _Scent {
-  pub val: u8,
-  pub scent_type: Scent
-}
-
-impl _Scent {
-
-  #[inline]
-  pub fn new(value: u8, scent_type: Scent) -> Self {
-    _Scent {
-      val: value,
-      scent_type: scent_type
-    }
+  ///
+  /// Get a scent from its name, ignoring case
+  ///
+  pub fn from_name(name: &str) -> Option<Scent> {
+    Scent::iterator().find(|scent| scent.to_string().to_lowercase() == name.to_lowercase()).cloned()
   }
-
 }
 
 ///
 /// Tile represents an environmental entity
 /// 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Tile {
-  name: &'static str,
+  #[serde(deserialize_with = "save::deserialize_static_str")]
+  name: save::StaticStr,
   pub glyph: char,
   pub fg: RGB,
   pub bg: RGB,
   pub biome: Biome,
-  pub scents: Vec<_Scent>,
   pub sound: usize,
   pub tiletype: Type,
-  pub seen: bool
+  pub seen: bool,
+  // Anything the tile smells of or sounds like all by itself
+  #[serde(default)]
+  pub emitter: Option<Emitter>
 }
 
 impl Tile {
@@ -278,17 +394,10 @@ impl Tile {
       fg: fg,
       bg: bg,
       biome: Biome::Dungeon,
-      // Create scents by iterating over ScentTypes
-      scents: {
-        let mut scent_vec = vec![];
-        for scent in Scent::iterator() {
-          scent_vec.push(_Scent::new(0, scent.clone()));
-        }
-        scent_vec
-      },
       sound: 0,
       tiletype: tiletype,
-      seen: false
+      seen: false,
+      emitter: None
     }
   }
 
@@ -6,13 +6,15 @@ extern crate rand;
 use self::rand::Rng;
 
 use core::renderer::{Renderable, RGB};
+use core::rng::GameRng;
 
 pub mod map;
-use self::map::{tile, Pos, Tile};
+use self::map::{emitter, tile, Emitter, Pos, Tile};
+use self::map::construct::Shop;
 
 // Privately use filter
 mod filter;
-use self::filter::{Filter, Structure, Simple};
+use self::filter::{Filter, Structure, Simple, Storefront};
 
 // Privately use automata
 mod automata;
@@ -34,15 +36,33 @@ const VINE_GLYPHS : [char; 5] = [
   '/', '|', '\\', '-', '~'
 ];
 
+///
+/// Percent chance for each water tile to make noise
+///
+const BABBLING_CHANCE : usize = 5;
+
+///
+/// How many braziers are put on each floor
+///
+const BRAZIERS : usize = 2;
+
+///
+/// Percent chance for each floor to have a shop
+///
+const SHOP_CHANCE : usize = 50;
+
 
 ///
 /// `Dungeon` struct to stitch together all builders and cellular automatons
 ///
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Dungeon {
   pub width: usize,
   pub height: usize,
   pub grid: map::Grid<Tile>,
+  // The floor's shop, if it has one
+  #[serde(default)]
+  pub shop: Option<Shop>,
 }
 
 // Make Dungeon Indexable
@@ -101,7 +121,9 @@ impl Dungeon {
   ///
   /// Make the dungeon
   ///
-  pub fn build(mut self) -> Self {
+  /// Every random choice made while building comes from `rng`, so the same seed always builds the same dungeon
+  ///
+  pub fn build(mut self, rng: &mut GameRng) -> Self {
 
     // The purpose of this function is to create some basic grid object, 
     // and completely fill it out into a dungeon.
@@ -128,7 +150,7 @@ impl Dungeon {
 
     // Apply simple builder. This creates a simple corridor/room dungeon based off the simple builder
     // Note how the grid is being consumed to replace itself here, but we don't want this later on.
-    Simple::new(&grid).apply(&mut grid);
+    Simple::new(&grid).apply(&mut grid, rng);
 
     // Create several reusable constructs for walls and floors. Since passing these directly into functions
     // will cause the references to be lost, clone them first, since Tile derives Clone.
@@ -153,37 +175,38 @@ impl Dungeon {
     // though it may be in the future.
     
     // This is geared towards eating walls and replacing them with floors, so mainly just to flesh out the dungeon.
-    let drunk = |chaos: f32, iter: u32, grid: &mut map::Grid<Tile> | {
+    let drunk = |chaos: f32, iter: u32, grid: &mut map::Grid<Tile>, rng: &mut GameRng| {
       let d = DrunkardsWalk::new(chaos);
       d.apply(
         grid,
         None,
         Some(wall.clone()),
         floor.clone(),
-        iter
+        iter,
+        rng
       )
     };
 
     // Make three passes of this basic walk to carve caves.
 
     // Total randomness - Really centralized areas that are mostly opened since it walks over itself a lot
-    drunk(1.0, 800, &mut grid);
+    drunk(1.0, 800, &mut grid, rng);
 
     // Semi random - A mixture of the previous and next option
-    drunk(0.5, 1000, &mut grid);
+    drunk(0.5, 1000, &mut grid, rng);
 
     // Mostly orderly - Long corridors that occassionally deviate
-    drunk(0.25, 1000, &mut grid);
+    drunk(0.25, 1000, &mut grid, rng);
 
     // Add structures
-    Structure::new().apply(&mut grid);
+    Structure::new().apply(&mut grid, rng);
 
     // Biome generation
 
     // Mostly just a proof of concept. Biomes are generated by comparing noise maps to the grid then flipping biomes
 
     // Apply noise for Cave biome
-    let mut f1 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.2);
+    let mut f1 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.2, rng);
     let bin_grid1 = f1.build();
 
     // Iterate over the grid
@@ -212,7 +235,7 @@ impl Dungeon {
     }
 
     // Apply noise for Crypt biome
-    let mut f2 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.2);
+    let mut f2 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.2, rng);
     let bin_grid2 = f2.build();
 
     for x in 0..self.width {
@@ -237,7 +260,7 @@ impl Dungeon {
     }
 
     // Apply noise for Sunken biome
-    let mut f3 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.4);
+    let mut f3 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.4, rng);
     let bin_grid3 = f3.build();
 
     for x in 0..self.width {
@@ -262,7 +285,7 @@ impl Dungeon {
     }
 
     // Apply noise for water
-    let mut f4 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.4);
+    let mut f4 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.4, rng);
     let bin_grid4 = f4.build();
 
     for x in 0..self.width {
@@ -273,6 +296,10 @@ impl Dungeon {
             _ => {
               grid[x][y].set_bg(RGB(57, 144, 255));
               grid[x][y].tiletype = tile::Type::Water;
+              // Some of it makes a bit of noise flowing past
+              if rng.gen_range(0, 100) < BABBLING_CHANCE {
+                grid[x][y].emitter = Some(Emitter::sound(emitter::BABBLING_LOUDNESS));
+              }
             }
           }
         }
@@ -280,7 +307,7 @@ impl Dungeon {
     }
 
     // Apply noise for crystal biome
-    let mut f5 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.67);
+    let mut f5 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.67, rng);
     let bin_grid5 = f5.build();
 
     for x in 0..self.width {
@@ -312,7 +339,7 @@ impl Dungeon {
     }
 
     // Apply noise for tall grass
-    let mut f6 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.5);
+    let mut f6 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.5, rng);
     let bin_grid6 = f6.build();
 
     for x in 0..self.width {
@@ -323,7 +350,7 @@ impl Dungeon {
               grid[x][y] = Tile::new(
                 "Tall Grass", 
                 '"', 
-                *rand::thread_rng().choose(&GRASS_COLORS).unwrap(), 
+                *rng.choose(&GRASS_COLORS).unwrap(), 
                 grid[x][y].get_bg(), 
                 tile::Type::TallGrass
               );
@@ -335,7 +362,7 @@ impl Dungeon {
     }
 
     // Apply noise for vines
-    let mut f7 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.55);
+    let mut f7 = Fussy::new(Dungeon::generate_grid(self.width, self.height, 0_u8), 1.55, rng);
     let bin_grid7 = f7.build();
 
     for x in 0..self.width {
@@ -345,8 +372,8 @@ impl Dungeon {
             tile::Type::Floor(_) => {
               grid[x][y] = Tile::new(
                 "Vine", 
-                *rand::thread_rng().choose(&VINE_GLYPHS).unwrap(), 
-                *rand::thread_rng().choose(&GRASS_COLORS).unwrap(), 
+                *rng.choose(&VINE_GLYPHS).unwrap(), 
+                *rng.choose(&GRASS_COLORS).unwrap(), 
                 grid[x][y].get_bg(), 
                 tile::Type::Vine
               );
@@ -364,7 +391,6 @@ impl Dungeon {
       for y in 0..self.height {
         // Basically just select 30% of walls to be 'hard walls' which are no different from normal walls
         if grid[x][y].tiletype == tile::Type::Wall(tile::Wall::Normal) {
-          let mut rng = rand::thread_rng();
           let chance = rng.gen_range(1, 100);
           if chance > 70 {
             grid[x][y].glyph = '#';
@@ -378,7 +404,6 @@ impl Dungeon {
     for x in 0..self.width {
       for y in 0..self.height {
         if grid[x][y].tiletype == tile::Type::Floor(tile::Floor::Normal) {
-          let mut rng = rand::thread_rng();
           let feature_chance = rng.gen_range(1, 100);
 
           // Create basic rock features
@@ -412,13 +437,21 @@ impl Dungeon {
       }
     }
 
+    // Maybe open up shop. This goes after all the noise so that nothing grows over the shop floor, and before the stairs
+    // and traps, which are never put in a shop
+    if rng.gen_range(0, 100) < SHOP_CHANCE {
+      let mut storefront = Storefront::new();
+      storefront.apply(&mut grid, rng);
+      self.shop = storefront.shop;
+    }
+
     // Add Stairs
 
     // So I know that get_valid_location() should be deprecated since we started adding stairs
     // but we're just gonna have to live with it for now
     
     // Downstair location
-    let loc = Dungeon::get_valid_location(&grid);
+    let loc = Dungeon::get_valid_location(&grid, rng);
     self.add_tile(
       &mut grid,
       &mut Tile::new(
@@ -432,7 +465,7 @@ impl Dungeon {
     );
 
     // Stair location
-    let loc = Dungeon::get_valid_location(&grid);
+    let loc = Dungeon::get_valid_location(&grid, rng);
     self.add_tile(
       &mut grid,
       &mut Tile::new(
@@ -446,7 +479,7 @@ impl Dungeon {
     );
 
     // Add a trap
-    let loc = Dungeon::get_valid_location(&grid);
+    let loc = Dungeon::get_valid_location(&grid, rng);
     self.add_tile(
       &mut grid,
       &mut Tile::new(
@@ -460,7 +493,7 @@ impl Dungeon {
     );
 
     // Or two
-    let loc = Dungeon::get_valid_location(&grid);
+    let loc = Dungeon::get_valid_location(&grid, rng);
     self.add_tile(
       &mut grid,
       &mut Tile::new(
@@ -474,7 +507,7 @@ impl Dungeon {
     );
 
     // Anotha one
-    let loc = Dungeon::get_valid_location(&grid);
+    let loc = Dungeon::get_valid_location(&grid, rng);
     self.add_tile(
       &mut grid,
       &mut Tile::new(
@@ -487,7 +520,7 @@ impl Dungeon {
       loc
     );
 
-    let loc = Dungeon::get_valid_location(&grid);
+    let loc = Dungeon::get_valid_location(&grid, rng);
     self.add_tile(
       &mut grid,
       &mut Tile::new(
@@ -500,6 +533,16 @@ impl Dungeon {
       loc
     );
 
+    // Light the place up a bit
+    for _ in 0..BRAZIERS {
+      let loc = Dungeon::get_valid_location(&grid, rng);
+      self.add_tile(&mut grid, &mut tile::brazier(), loc);
+    }
+
+    // And make it smell nice
+    let loc = Dungeon::get_valid_location(&grid, rng);
+    self.add_tile(&mut grid, &mut tile::incense_burner(), loc);
+
     // Spent 300 million years wondering why the map was all walls until I realized this CRUCIAL piece of code
     // suddenly vanished.
     // Don't delete.
@@ -541,9 +584,8 @@ impl Dungeon {
   ///
   /// NOTE: Should be deprecated and removed once stairs show up
   ///
-  pub fn get_valid_location(grid: &map::Grid<Tile>) -> Pos {
+  pub fn get_valid_location(grid: &map::Grid<Tile>, rng: &mut GameRng) -> Pos {
     loop {
-      let mut rng = rand::thread_rng();
       let x : usize = rng.gen_range(1, grid.len() - 2);
       let y : usize = rng.gen_range(1, grid[0].len() - 2);
 
@@ -562,7 +604,8 @@ impl Dungeon {
     return Dungeon {
       width: map_dim.x as usize,
       height: map_dim.y as usize,
-      grid: map::Grid::new()
+      grid: map::Grid::new(),
+      shop: None
     };
 
   }
@@ -1,67 +1,99 @@
-// extern crate serde_json;
-// JSON Parsing and Construction
-// https://github.com/serde-rs/json
-// use self::serde_json::Value;
-// use self::serde_json::map;
-
-// Use to read files
-// use std::fs::File;
-// use std::io::prelude::*;
-
 extern crate rand;
 use self::rand::Rng;
 
+use std::cmp;
+
 use core::tcod::map::{Map, FovAlgorithm};
 
 
-use core::creature::{ai, Actions, Actor, Creature, Stats};
+use core::creature::{ai, combat, Actions, Creature, Slot, Stats, INVENTORY_SLOTS, NORMAL_SPEED};
 
-use core::item::{Item, ItemProperty, Money};
+use core::item::{self, money_value, Effect, Item, ItemProperty, Money};
 
 use core::renderer::{Renderable, RGB};
 
+use core::init;
+
 use core::log;
 
+use core::rng::GameRng;
+
 pub mod dungeon;
-use self::dungeon::{Dungeon, map::{self, Pos, tile, Tile}};
+use self::dungeon::{Dungeon, map::{self, sound, Emitter, Pos, tile, Tile}};
+
+pub mod occupancy;
+use self::occupancy::{Collision, Occupancy, Occupant};
+
+pub mod desire;
+use self::desire::DesireMaps;
+
+pub mod scent;
+use self::scent::ScentMap;
+
+pub mod shop;
+
+mod scent_tests;
+
+// Shops are set up the same way for the engine's tests
+pub mod shop_tests;
+
+mod world_tests;
 
 ///
-/// What value the player sets the scent of nearby tiles to
+/// How long corpses stay fresh before they start to rot and smell, in ticks
 ///
-const SC_INC : u8 = 100;
+const CORPSE_FRESH : usize = 200;
 
 ///
-/// Affects bloom distance. Higher values means less bloom
+/// How long corpses last before they've rotted away completely, in ticks
 ///
-const SC_BLOOM_CUTOFF : f32 = 0.05;
+const CORPSE_LIFETIME : usize = 1500;
 
 ///
-/// Decay value applied to tiles inheriting scent from neighbors
+/// How many groups of monsters try to spawn on each new floor
 ///
-/// Currently 255/256
+const SPAWN_GROUPS : usize = 6;
+
+///
+/// How far from the first member of a group the rest of the group can spawn
+///
+const SPAWN_GROUP_RADIUS : isize = 3;
+
+///
+/// How many piles of loot lie around on each new floor
+///
+const LOOT_PILES : usize = 6;
+
+///
+/// How far the player can see without a light
 ///
-const SC_DECAY : f32 = 0.99609375;
+const SIGHT_RADIUS_DARK : usize = 6;
 
 ///
-/// Diameter of scent around creatures, should be odd for best effect
+/// How far thrown things fly before they drop
 ///
-const SC_DIAM : isize = 3;
-// Upper index for ranges
-const SC_DIAM_UPPER : isize = ((SC_DIAM / 2) + 1);
-// Lower index for ranges
-const SC_DIAM_LOWER : isize = -(SC_DIAM / 2);
+const THROW_RANGE : isize = 8;
 
 ///
 /// Represent a floor in the dungeon
 ///
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Floor {
   pub dun: Dungeon,
   // Creatures need to be boxed because they hold a trait object, which has an undefined size.
   // Whenever you create a creature, just slap it into Box::new() and it works
   pub creatures: Vec<Box<Creature>>,
   // Items on the floor
-  pub items: Vec<Item>
+  pub items: Vec<Item>,
+  // Who is standing where. Rebuilt from the creatures, so it isn't saved
+  #[serde(skip)]
+  pub occupancy: Occupancy,
+  // How far everything worth walking to is. Rebuilt every turn, so it isn't saved either
+  #[serde(skip)]
+  pub desire: DesireMaps,
+  // What every tile smells of
+  #[serde(default)]
+  pub scent: ScentMap
 }
 
 impl Floor {
@@ -69,153 +101,144 @@ impl Floor {
     Floor {
       dun: dun,
       creatures: creatures,
-      items: vec![]
+      items: vec![],
+      occupancy: Occupancy::default(),
+      desire: DesireMaps::default(),
+      scent: ScentMap::default()
     }
   }
 
 }
 
+///
+/// The world, which owns the player and every floor of the dungeon
+///
+/// This is what gets saved to disk, see `save`
+///
+#[derive(Serialize, Deserialize)]
 pub struct World {
   pub player: Creature,
   pub floor: Floor,
   pub floor_stack: Vec<Floor>,
   pub floor_num: usize,
+  // How many ticks have gone by since the game started
+  pub time: usize,
+  // What killed the player, once something has
+  #[serde(default)]
+  pub cause_of_death: Option<String>,
   // http://tomassedovic.github.io/tcod-rs/tcod/map/struct.Map.html
-  pub tcod_map: Map
+  //
+  // The tcod map can't be serialized, but it's built entirely from the current floor so it's just rebuilt by `restore()`
+  #[serde(skip, default = "World::blank_tcod_map")]
+  pub tcod_map: Map,
+  // Source of all randomness in the world
+  pub rng: GameRng
 }
 
 impl World {
 
   ///
-  /// Create a set of creatures for testing. 100% temporary
+  /// Scatter piles of loot around a new floor from the item registry's loot tables
+  ///
+  /// Like monster groups, each pile picks a random spot first, then picks what's lying there based on the spot's biome and
+  /// how deep the floor is
+  ///
+  fn create_items(g: &map::Grid<Tile>, depth: usize, rng: &mut GameRng) -> Vec<Item> {
+
+    let mut items = vec![];
+
+    for _ in 0..LOOT_PILES {
+
+      let pos = Dungeon::get_valid_location(g, rng);
+      let biome = &g[pos.x as usize][pos.y as usize].biome;
+
+      // Nothing turns up here, so there's just no pile
+      let (item, rule) = match init::items().loot_table(biome, depth).choose(rng) {
+        Some(&(item, rule)) => (item, rule),
+        None => continue
+      };
+
+      let quantity = rng.gen_range(rule.count.0, rule.count.1 + 1);
+      items.push(item.create(pos, quantity as isize));
+
+    }
+
+    return items;
+
+  }
+
+  ///
+  /// Populate a new floor from the monster registry's spawn tables
+  ///
+  /// Each group picks a random spot first, then picks what lives there based on the spot's biome and how deep the floor is.
+  /// The rest of the group is placed around the first member
   ///
-  fn create_test_creatures(g: &map::Grid<Tile>) -> Vec<Box<Creature>> {
+  fn create_creatures(g: &map::Grid<Tile>, depth: usize, rng: &mut GameRng) -> Vec<Box<Creature>> {
     
     let mut creatures = Vec::<Box<Creature>>::new();
 
-    creatures.push(
-      Box::new(
-        Creature::new(
-          "ant",
-          'a',
-          Dungeon::get_valid_location(g),
-          RGB(150, 0, 0), RGB(0, 0, 0),
-          Stats::new(
-            0,
-            0,
-            0,
-            0,
-            tile::Scent::Insectoid
-          ),
-          ai::SimpleAI::new()
-        )
-      )
-    );
+    for _ in 0..SPAWN_GROUPS {
 
-    creatures.push(
-      Box::new(
-        Creature::new(
-          "bee",
-          'b',
-          Dungeon::get_valid_location(g),
-          RGB(150, 150, 0), RGB(0, 0, 0),
-          Stats::new(
-            0,
-            0,
-            0,
-            0,
-            tile::Scent::Insectoid
-          ),
-          ai::SimpleAI::new()
-        )
-      )
-    );
+      let leader = World::find_free_location(g, &creatures, rng);
+      let biome = &g[leader.x as usize][leader.y as usize].biome;
 
-    creatures.push(
-      Box::new(
-        Creature::new(
-          "cat",
-          'c',
-          Dungeon::get_valid_location(g),
-          RGB(150, 0, 150), RGB(0, 0, 0),
-          Stats::new(
-            0,
-            0,
-            0,
-            5,
-            tile::Scent::Feline
-          ),
-          ai::TrackerAI::new()
-        )
-      )
-    );
+      // Nothing lives here, so this group just doesn't show up
+      let (monster, rule) = match init::monsters().spawn_table(biome, depth).choose(rng) {
+        Some(&(monster, rule)) => (monster, rule),
+        None => continue
+      };
 
-    creatures.push(
-      Box::new(
-        Creature::new(
-          "blink hound",
-          'd',
-          Dungeon::get_valid_location(g),
-          RGB(150, 150, 150), RGB(0, 0, 0),
-          Stats::new(
-            0,
-            0,
-            0,
-            20,
-            tile::Scent::Canine
-          ),
-          ai::BlinkAI::new()
-        )
-      )
-    );
+      creatures.push(Box::new(monster.spawn(leader)));
 
-    creatures.push(
-      Box::new(
-        Creature::new(
-          "Kurt",
-          '@',
-          Dungeon::get_valid_location(g),
-          RGB(200, 200, 200), RGB(0, 0, 0),
-          Stats::new(
-            0,
-            0,
-            0,
-            50,
-            tile::Scent::Canine
-          ),
-          ai::TalkerAI::new()
-        )
-      )
-    );
+      let count = rng.gen_range(rule.count.0, rule.count.1 + 1);
+      for _ in 1..count {
+        if let Some(pos) = World::find_spawn_near(g, &creatures, leader, rng) {
+          creatures.push(Box::new(monster.spawn(pos)));
+        }
+      }
 
-    creatures.push(
-      Box::new(
-        Creature::new(
-          "Echidna",
-          'e',
-          Dungeon::get_valid_location(g),
-          RGB(50, 50, 200), RGB(0, 0, 0),
-          Stats::new(
-            0,
-            0,
-            0,
-            15,
-            tile::Scent::Canine
-          ),
-          ai::SmellerAI::new()
-        )
-      )
-    );
+    }
 
     return creatures;
 
   }
 
+  ///
+  /// Find a valid location that none of `creatures` are standing on
+  ///
+  fn find_free_location(g: &map::Grid<Tile>, creatures: &[Box<Creature>], rng: &mut GameRng) -> Pos {
+    loop {
+      let pos = Dungeon::get_valid_location(g, rng);
+      if !creatures.iter().any(|creature| creature.actor.pos == pos) {
+        return pos;
+      }
+    }
+  }
+
+  ///
+  /// Find a spawnable tile of the same biome close to `pos` that none of `creatures` are standing on, giving up after a few tries
+  ///
+  fn find_spawn_near(g: &map::Grid<Tile>, creatures: &[Box<Creature>], pos: Pos, rng: &mut GameRng) -> Option<Pos> {
+    let biome = &g[pos.x as usize][pos.y as usize].biome;
+    for _ in 0..ai::RANDOM_TRIES {
+      let x = pos.x + rng.gen_range(-SPAWN_GROUP_RADIUS, SPAWN_GROUP_RADIUS + 1);
+      let y = pos.y + rng.gen_range(-SPAWN_GROUP_RADIUS, SPAWN_GROUP_RADIUS + 1);
+      if x < 0 || y < 0 || x as usize >= g.len() || y as usize >= g[0].len() {
+        continue;
+      }
+      let tile = &g[x as usize][y as usize];
+      if tile::spawnable(tile) && &tile.biome == biome && !creatures.iter().any(|creature| creature.actor.pos == Pos::new(x, y)) {
+        return Some(Pos::new(x, y));
+      }
+    }
+    return None;
+  }
+
   ///
   /// Create a basic dungeon for testing
   ///
-  fn create_test_dungeon(map_dim: Pos) -> Dungeon {
-    return Dungeon::new(map_dim).build();
+  fn create_test_dungeon(map_dim: Pos, rng: &mut GameRng) -> Dungeon {
+    return Dungeon::new(map_dim).build(rng);
   }
 
   ///
@@ -223,20 +246,24 @@ impl World {
   ///
   #[inline]
   fn new_player() -> Creature {
-    Creature::new(
+    let mut player = Creature::new(
       "Player",
       '@',
       Pos::new(40, 25),
       RGB(255, 255, 255), RGB(0, 0, 0),
       Stats::new(
-        0,
-        0,
-        0,
+        30,
+        3,
+        2,
+        NORMAL_SPEED,
         20,
         tile::Scent::Player
       ),
       ai::PlayerAI::new()
-    )
+    );
+    // Nobody should have to start off in the dark
+    player.equipment.light = Some(init::items().create("torch", Pos::new(0, 0), 1));
+    return player;
   }
 
 
@@ -254,6 +281,7 @@ impl World {
     let tcod_map = World::new_tcod_map(self.floor.dun.get_bounds_pos(), &self.floor.dun);
     self.tcod_map = tcod_map;
 
+    self.floor.dun.shop = None;
     self.floor.creatures = Vec::new();
     self.floor.items =     Vec::new();
 
@@ -275,11 +303,642 @@ impl World {
 
   }
 
+  ///
+  /// Let time pass until the player has paid off `cost` energy, letting creatures act as they build up their own
+  ///
+  /// Time passes in ticks. Each tick, every creature with energy to spare acts once, paying for whatever it did, and then
+  /// everyone (player included) gains their speed in energy. So a creature twice as fast as the player gets to act twice
+  /// for every one of the player's moves, and a slower one skips some.
+  ///
+  /// Creatures with more energy go first, and ties are broken randomly so that nobody always gets the jump just because they
+  /// were created first.
+  ///
+  pub fn pass_time(&mut self, cost: isize) {
+
+    self.player.energy -= cost;
+
+    // What the player has on can't change while they wait
+    let player_speed = cmp::max(1, self.player.effective_stats().speed) as isize;
+
+    while self.player.energy < 0 && self.player.state != Actions::Die {
+
+      self.time += 1;
+
+      // Figure out who is ready to act
+      let mut ready : Vec<usize> = (0..self.floor.creatures.len())
+        .filter(|&idx| self.floor.creatures[idx].energy >= 0 && self.floor.creatures[idx].state != Actions::Die)
+        .collect();
+      self.rng.shuffle(&mut ready);
+      // The sort is stable, so the shuffle settles any ties
+      ready.sort_by_key(|&idx| -self.floor.creatures[idx].energy);
+
+      for idx in ready {
+        self.creature_act(idx);
+      }
+
+      // Everyone recovers
+      self.player.energy += player_speed;
+      for creature in &mut self.floor.creatures {
+        creature.energy += cmp::max(1, creature.effective_stats().speed) as isize;
+      }
+
+    }
+
+  }
+
+  ///
+  /// Have a creature take its turn and pay for it
+  ///
+  fn creature_act(&mut self, idx: usize) {
+
+    // Anything killed earlier in the tick doesn't get a turn
+    if self.floor.creatures[idx].state == Actions::Die {
+      return;
+    }
+
+    // The creature decides what to do on a copy of itself, since the context needs to see the whole floor, itself included
+    let mut me = self.floor.creatures[idx].clone();
+    {
+      let mut ctx = ai::Context {
+        map: &self.floor.dun.grid,
+        player: &self.player,
+        creatures: &self.floor.creatures,
+        items: &self.floor.items,
+        occupancy: &self.floor.occupancy,
+        desire: &self.floor.desire,
+        scent: &self.floor.scent,
+        shop: self.floor.dun.shop.as_ref(),
+        floor_num: self.floor_num,
+        me: Some(idx),
+        rng: &mut self.rng
+      };
+      me.take_turn(&mut ctx);
+    }
+
+    // The AI moves the creature wherever it wants to go, but it's up to the world whether it actually gets there
+    let oldpos = self.floor.creatures[idx].actor.pos;
+    let newpos = me.actor.pos;
+    me.actor.pos = oldpos;
+    self.floor.creatures[idx] = me;
+
+    if newpos != oldpos {
+      self.move_creature(idx, newpos);
+    }
+
+    let cost = self.floor.creatures[idx].state.cost();
+    self.floor.creatures[idx].energy -= cost;
+
+  }
+
+  ///
+  /// Move a creature to `to`, sorting out whoever is already there
+  ///
+  /// Walking into the player is an attack. Walking into another creature depends on that creature's `Collision`. Returns
+  /// whether the creature ended up at `to`
+  ///
+  pub fn move_creature(&mut self, idx: usize, to: Pos) -> bool {
+
+    let from = self.floor.creatures[idx].actor.pos;
+
+    match self.floor.occupancy.get(to) {
+
+      // Nobody's there
+      None => {},
+
+      // Creatures can't share a tile with the player. Walking into them is an attack, as long as there's anyone left to attack
+      Some(Occupant::Player) => {
+        if self.player.state != Actions::Die {
+          let creature = &mut self.floor.creatures[idx];
+          let outcome = combat::melee(creature, &mut self.player, &mut self.rng);
+          World::log_attack_on_player(creature, &outcome);
+          if let combat::Outcome::Kill(_) = outcome {
+            self.cause_of_death = Some(format!("Killed by {}", combat::a(creature)));
+          }
+        }
+        return false;
+      },
+
+      // Already there
+      Some(Occupant::Creature(other)) if other == idx => return true,
+
+      Some(Occupant::Creature(other)) => {
+        match self.floor.creatures[other].collision {
+
+          Collision::Block => return false,
+
+          Collision::Swap => {
+            self.floor.creatures[other].actor.pos = from;
+            self.floor.occupancy.set(from, Some(Occupant::Creature(other)));
+            self.floor.creatures[idx].actor.pos = to;
+            self.floor.occupancy.set(to, Some(Occupant::Creature(idx)));
+            return true;
+          },
+
+          Collision::Push => {
+            // Shove them along the same direction
+            let dest = to + (to - from);
+            let heavy_enough = self.floor.creatures[idx].effective_stats().weight >= self.floor.creatures[other].effective_stats().weight;
+            if !heavy_enough || !self.is_valid_pos(dest.x, dest.y) || !self.floor.occupancy.is_free(dest) {
+              return false;
+            }
+            self.floor.creatures[other].actor.pos = dest;
+            self.floor.occupancy.set(dest, Some(Occupant::Creature(other)));
+          }
+
+        }
+      }
+
+    }
+
+    self.floor.occupancy.set(from, None);
+    self.floor.occupancy.set(to, Some(Occupant::Creature(idx)));
+    self.floor.creatures[idx].actor.pos = to;
+
+    return true;
+
+  }
+
+  ///
+  /// Rebuild the occupancy layer from where everyone is standing
+  ///
+  pub fn update_occupancy(&mut self) {
+    self.floor.occupancy = Occupancy::build(self.floor.dun.width, self.floor.dun.height, &self.player, &self.floor.creatures);
+  }
+
+  ///
+  /// Rebuild the desire maps from where the player and items are
+  ///
+  pub fn update_desire(&mut self) {
+    let items : Vec<Pos> = self.floor.items.iter().map(|item| item.pos).collect();
+    self.floor.desire = DesireMaps::build(&self.floor.dun.grid, self.player.actor.pos, &items);
+  }
+
+  ///
+  /// Get the index of the creature standing at `pos`, if there is one
+  ///
+  pub fn creature_at(&self, pos: Pos) -> Option<usize> {
+    match self.floor.occupancy.get(pos) {
+      Some(Occupant::Creature(idx)) => Some(idx),
+      _ => None
+    }
+  }
+
+  ///
+  /// Carry out the player's attack, if they made one
+  ///
+  fn player_attack(&mut self) {
+
+    let target = match self.player.state {
+      Actions::Attack(target) => target,
+      _ => return
+    };
+
+    // Whatever was there could have moved or died since
+    let idx = match self.creature_at(target) {
+      Some(idx) => idx,
+      None => return
+    };
+
+    let outcome = combat::melee(&mut self.player, &mut self.floor.creatures[idx], &mut self.rng);
+    let name = combat::the(&self.floor.creatures[idx]);
+
+    // The dead don't take up room
+    if let combat::Outcome::Kill(_) = outcome {
+      self.floor.occupancy.set(target, None);
+    }
+
+    match outcome {
+      combat::Outcome::Miss => log!((Box::leak(format!("You miss {}.", name).into_boxed_str()), RGB(200, 200, 200))),
+      combat::Outcome::Hit(damage) => log!((Box::leak(format!("You hit {} for {}.", name, damage).into_boxed_str()), RGB(255, 255, 255))),
+      combat::Outcome::Kill(_) => log!((Box::leak(format!("You kill {}!", name).into_boxed_str()), RGB(255, 255, 100)))
+    }
+
+  }
+
+  ///
+  /// Tell the player what a creature did to them
+  ///
+  fn log_attack_on_player(creature: &Creature, outcome: &combat::Outcome) {
+
+    // Start the sentence with a capital
+    let mut name = combat::the(creature);
+    if let Some(first) = name.get_mut(0..1) {
+      first.make_ascii_uppercase();
+    }
+
+    match *outcome {
+      combat::Outcome::Miss => log!((Box::leak(format!("{} misses you.", name).into_boxed_str()), RGB(200, 200, 200))),
+      combat::Outcome::Hit(damage) => log!((Box::leak(format!("{} hits you for {}.", name, damage).into_boxed_str()), RGB(255, 100, 100))),
+      combat::Outcome::Kill(_) => log!((Box::leak(format!("{} kills you!", name).into_boxed_str()), RGB(255, 0, 0)))
+    }
+
+  }
+
   ///
   /// Check for dead creatures
   /// 
   pub fn check_death(&mut self) {
-    self.floor.creatures.retain( |creature| creature.state != Actions::Die )
+    // The dead leave a body behind, along with everything they were carrying
+    let mut remains = vec![];
+    for creature in self.floor.creatures.iter().filter(|creature| creature.state == Actions::Die) {
+      remains.append(&mut World::remains(creature, self.time));
+    }
+    self.floor.items.append(&mut remains);
+
+    // The shopkeeper moves down past everyone who died before them, unless they died too
+    if let Some(ref mut shop) = self.floor.dun.shop {
+      let creatures = &self.floor.creatures;
+      shop.keeper = match shop.keeper {
+        Some(keeper) if keeper < creatures.len() && creatures[keeper].state != Actions::Die => {
+          Some(keeper - creatures[..keeper].iter().filter(|creature| creature.state == Actions::Die).count())
+        },
+        _ => None
+      };
+    }
+
+    self.floor.creatures.retain( |creature| creature.state != Actions::Die );
+    // Everyone's index may have changed
+    self.update_occupancy();
+  }
+
+  ///
+  /// Get what's left of `creature` after it died at `time`
+  ///
+  fn remains(creature: &Creature, time: usize) -> Vec<Item> {
+    let pos = creature.actor.pos;
+    let mut remains = vec![Item::corpse(creature.actor.get_id(), creature.actor.get_glyph(), creature.actor.get_fg(), pos, time)];
+
+    for item in &creature.inventory {
+      let mut item = item.clone();
+      item.pos = pos;
+      remains.push(item);
+    }
+
+    // Whatever's in their wallet gets dropped as gold, and anything less than a whole piece is lost
+    let gold = (creature.wallet / money_value(&Money::Gold)) as isize;
+    if gold > 0 {
+      remains.push(init::items().create("gold", pos, gold));
+    }
+
+    return remains;
+  }
+
+  ///
+  /// Let corpses rot, and clear away the ones that have rotted away completely
+  ///
+  pub fn update_corpses(&mut self) {
+    let time = self.time;
+
+    for item in &mut self.floor.items {
+      if let ItemProperty::Corpse(died) = item.property {
+        // Start to smell, and look a bit worse for wear
+        if time.saturating_sub(died) >= CORPSE_FRESH && item.emitter.is_none() {
+          let fg = item.get_fg();
+          item.set_fg(RGB(fg.0 / 2, fg.1 / 2, fg.2 / 2));
+          item.emitter = Some(Emitter::smell(tile::Scent::Decay));
+        }
+      }
+    }
+
+    self.floor.items.retain(|item| match item.property {
+      ItemProperty::Corpse(died) => time.saturating_sub(died) < CORPSE_LIFETIME,
+      _ => true
+    });
+  }
+
+  ///
+  /// Pick up everything at the player's feet that they can. Money goes straight into their wallet, and anything too
+  /// heavy or that there's no room for stays where it is
+  ///
+  /// Returns whether anything was picked up, since trying to pick up nothing doesn't take any time
+  ///
+  pub fn player_pick_up(&mut self) -> bool {
+
+    let pos = self.player.actor.pos;
+    let (here, elsewhere) : (Vec<Item>, Vec<Item>) = self.floor.items.drain(..).partition(|item| item.pos == pos);
+    self.floor.items = elsewhere;
+
+    if here.is_empty() {
+      log!(("There's nothing here to pick up", RGB(150, 150, 150)));
+      return false;
+    }
+
+    let mut picked_up = false;
+
+    for item in here {
+
+      // Money doesn't take up any room
+      let fits = match item.property {
+        ItemProperty::Money(_) => Ok(()),
+        _ => self.player.can_carry(&item)
+      };
+
+      if let Err(reason) = fits {
+        log!((Box::leak(reason.into_boxed_str()), RGB(150, 150, 150)));
+        self.floor.items.push(item);
+        continue;
+      }
+
+      log!((Box::leak(format!("You pick up {}", item.describe()).into_boxed_str()), item.get_fg()));
+      picked_up = true;
+      self.quote_pick_up(&item);
+
+      match item.property {
+        ItemProperty::Money(ref tender) => self.player.wallet += money_value(tender) * item.quantity as f32,
+        _ => self.player.pick_up(item)
+      }
+
+    }
+
+    return picked_up;
+
+  }
+
+  ///
+  /// Drop whatever is in the `index`th slot of the player's inventory. Returns whether there was anything there to drop
+  ///
+  pub fn player_drop(&mut self, index: usize) -> bool {
+    match self.player.drop_item(index) {
+      Some(item) => {
+        log!((Box::leak(format!("You drop {}", item.describe()).into_boxed_str()), item.get_fg()));
+        self.floor.items.push(item);
+        return true;
+      },
+      None => log!(("You don't have that", RGB(150, 150, 150)))
+    }
+    return false;
+  }
+
+  ///
+  /// How far the player can see, which depends on how bright the light they're holding up is, if it hasn't burned out
+  ///
+  pub fn sight_radius(&self) -> usize {
+    match self.player.equipment.light.as_ref().map(|light| &light.property) {
+      Some(&ItemProperty::Light(radius, charges)) if charges > 0 => cmp::max(radius, SIGHT_RADIUS_DARK),
+      _ => SIGHT_RADIUS_DARK
+    }
+  }
+
+  ///
+  /// Burn a turn's worth of the player's light
+  ///
+  fn burn_light(&mut self) {
+    if let Some(ref mut light) = self.player.equipment.light {
+      if let ItemProperty::Light(radius, charges) = light.property {
+        if charges > 0 {
+          light.property = ItemProperty::Light(radius, charges - 1);
+          if charges == 1 {
+            log!((Box::leak(format!("Your {} burns out.", light.get_id()).into_boxed_str()), RGB(150, 150, 150)));
+          }
+        }
+      }
+    }
+  }
+
+  ///
+  /// Put on one of whatever is in the `index`th slot of the player's inventory, putting whatever it replaces back. Wielding
+  /// only works for weapons, and wearing works for everything else. Returns whether the player put anything on
+  ///
+  pub fn player_equip(&mut self, index: usize, wield: bool) -> bool {
+
+    let slot = match self.player.inventory.get(index) {
+      Some(item) if item.unpaid => {
+        log!(("You'll have to pay for that first.", RGB(150, 150, 150)));
+        return false;
+      },
+      Some(item) => Slot::of(item),
+      None => {
+        log!(("You don't have that", RGB(150, 150, 150)));
+        return false;
+      }
+    };
+
+    let verb = if wield { "wield" } else { "wear" };
+    let slot = match slot {
+      Some(slot) if (slot == Slot::Weapon) == wield => slot,
+      _ => {
+        log!((Box::leak(format!("You can't {} that.", verb).into_boxed_str()), RGB(150, 150, 150)));
+        return false;
+      }
+    };
+
+    // Whatever comes off needs somewhere to go
+    if let Some(old) = self.player.equipment.get(slot) {
+      let leaves_gap = self.player.inventory[index].quantity <= 1;
+      if !leaves_gap && self.player.inventory.len() >= INVENTORY_SLOTS && !self.player.inventory.iter().any(|held| held.stacks_with(old)) {
+        log!((Box::leak(format!("You have no room to take off {}.", old.describe()).into_boxed_str()), RGB(150, 150, 150)));
+        return false;
+      }
+    }
+
+    let mut item = self.player.inventory[index].clone();
+    item.quantity = 1;
+    self.player.inventory[index].quantity -= 1;
+    if self.player.inventory[index].quantity <= 0 {
+      self.player.inventory.remove(index);
+    }
+
+    log!((Box::leak(format!("You {} the {}.", slot.verb(), item.get_id()).into_boxed_str()), item.get_fg()));
+
+    if let Ok(Some(old)) = self.player.equipment.equip(item) {
+      self.player.pick_up(old);
+    }
+
+    return true;
+
+  }
+
+  ///
+  /// Take off whatever the player has in `slot`, putting it back in their inventory. Returns whether they took anything off
+  ///
+  pub fn player_remove(&mut self, slot: Slot) -> bool {
+
+    let fits = match self.player.equipment.get(slot) {
+      Some(item) => self.player.inventory.len() < INVENTORY_SLOTS || self.player.inventory.iter().any(|held| held.stacks_with(item)),
+      None => {
+        log!(("You don't have anything on there", RGB(150, 150, 150)));
+        return false;
+      }
+    };
+
+    if !fits {
+      log!(("You have no room to put that away", RGB(150, 150, 150)));
+      return false;
+    }
+
+    let item = self.player.equipment.remove(slot).unwrap();
+    log!((Box::leak(format!("You take off the {}.", item.get_id()).into_boxed_str()), item.get_fg()));
+    self.player.pick_up(item);
+
+    return true;
+
+  }
+
+  ///
+  /// Use whatever is in the `index`th slot of the player's inventory. Returns whether that took any time, which it
+  /// doesn't if the item can't be used like that
+  ///
+  pub fn player_apply(&mut self, index: usize) -> bool {
+
+    let property = match self.player.inventory.get(index) {
+      Some(item) if item.unpaid => {
+        log!(("You'll have to pay for that first.", RGB(150, 150, 150)));
+        return false;
+      },
+      Some(item) => item.property.clone(),
+      None => {
+        log!(("You don't have that", RGB(150, 150, 150)));
+        return false;
+      }
+    };
+
+    let name = self.player.inventory[index].get_id();
+
+    match property {
+
+      ItemProperty::Food(heals) => {
+        self.heal_player(heals);
+        log!((Box::leak(format!("You eat the {}.", name).into_boxed_str()), RGB(200, 160, 100)));
+      },
+
+      ItemProperty::Potion(effect) => {
+        log!((Box::leak(format!("You drink the {}.", name).into_boxed_str()), RGB(200, 200, 255)));
+        match effect {
+          Effect::Healing(dice) => {
+            let heals = dice.roll(&mut self.rng);
+            self.heal_player(heals);
+            log!(("You feel better.", RGB(255, 80, 80)));
+          },
+          Effect::Teleport => {
+            self.player.actor.pos = World::find_free_location(&self.floor.dun.grid, &self.floor.creatures, &mut self.rng);
+            self.update_occupancy();
+            log!(("Everything around you shifts.", RGB(50, 127, 200)));
+          },
+          Effect::Clairvoyance => {
+            for column in self.floor.dun.grid.iter_mut() {
+              for t in column.iter_mut() {
+                t.seen = true;
+              }
+            }
+            log!(("You see the whole floor in your mind's eye.", RGB(200, 200, 255)));
+          }
+        }
+      },
+
+      ItemProperty::Light(_, charges) => {
+        log!((Box::leak(format!("Your {} has {} turns of light left.", name, charges).into_boxed_str()), RGB(255, 160, 0)));
+        return false;
+      },
+
+      ItemProperty::Key => {
+        log!(("There's nothing here to unlock.", RGB(150, 150, 150)));
+        return false;
+      },
+
+      ItemProperty::Throwable(_) => {
+        log!(("That's for throwing.", RGB(150, 150, 150)));
+        return false;
+      },
+
+      _ => {
+        log!((Box::leak(format!("You can't use the {} like that.", name).into_boxed_str()), RGB(150, 150, 150)));
+        return false;
+      }
+
+    }
+
+    // Anything that was used got used up
+    self.player.inventory[index].quantity -= 1;
+    if self.player.inventory[index].quantity <= 0 {
+      self.player.inventory.remove(index);
+    }
+
+    return true;
+
+  }
+
+  ///
+  /// Heal the player, though not past their max hp
+  ///
+  fn heal_player(&mut self, heals: usize) {
+    self.player.stats.hp = cmp::min(self.player.stats.hp + heals, self.player.stats.max_hp);
+  }
+
+  ///
+  /// Throw one of whatever is in the `index`th slot of the player's inventory one step at a time in `direction`, until it
+  /// hits something or runs out of steam. Returns whether anything got thrown
+  ///
+  pub fn player_throw(&mut self, index: usize, direction: Pos) -> bool {
+
+    let mut thrown = match self.player.inventory.get(index) {
+      Some(item) if item.unpaid => {
+        log!(("You'll have to pay for that first.", RGB(150, 150, 150)));
+        return false;
+      },
+      Some(item) => item.clone(),
+      None => {
+        log!(("You don't have that", RGB(150, 150, 150)));
+        return false;
+      }
+    };
+    thrown.quantity = 1;
+
+    self.player.inventory[index].quantity -= 1;
+    if self.player.inventory[index].quantity <= 0 {
+      self.player.inventory.remove(index);
+    }
+
+    // Fly until something's in the way. Walls stop it short, but creatures get hit
+    let mut pos = self.player.actor.pos;
+    let mut target = None;
+    for _ in 0..THROW_RANGE {
+      let next = pos + direction;
+      if !self.is_valid_pos(next.x, next.y) {
+        break;
+      }
+      pos = next;
+      if let Some(idx) = self.creature_at(pos) {
+        target = Some(idx);
+        break;
+      }
+    }
+
+    thrown.pos = pos;
+    let name = thrown.get_id();
+
+    if let Some(idx) = target {
+
+      // Things that aren't meant to be thrown still hurt a little
+      let damage = match thrown.property {
+        ItemProperty::Throwable(ref damage) | ItemProperty::Weapon(ref damage) => *damage,
+        _ => item::Dice::new(1, 2, 0)
+      };
+
+      // Only the target's actual health takes the damage
+      let mut defense = self.floor.creatures[idx].effective_stats();
+      let outcome = combat::hurl(&self.player.effective_stats(), &damage, &mut defense, &mut self.rng);
+      self.floor.creatures[idx].stats.hp = defense.hp;
+      let creature = combat::the(&self.floor.creatures[idx]);
+
+      match outcome {
+        combat::Outcome::Miss => log!((Box::leak(format!("The {} misses {}.", name, creature).into_boxed_str()), RGB(200, 200, 200))),
+        combat::Outcome::Hit(damage) => log!((Box::leak(format!("The {} hits {} for {}.", name, creature, damage).into_boxed_str()), RGB(255, 255, 255))),
+        combat::Outcome::Kill(_) => {
+          log!((Box::leak(format!("The {} kills {}!", name, creature).into_boxed_str()), RGB(255, 255, 100)));
+          self.floor.creatures[idx].state = Actions::Die;
+          // The dead don't take up room
+          self.floor.occupancy.set(pos, None);
+        }
+      }
+
+    } else {
+      log!((Box::leak(format!("You throw the {}.", name).into_boxed_str()), thrown.get_fg()));
+    }
+
+    self.floor.items.push(thrown);
+
+    return true;
+
   }
 
   ///
@@ -289,7 +948,8 @@ impl World {
 
     // Don't repeat if we already know what's under foot
     match self.player.state {
-      Actions::Wait | Actions::Die | Actions::Unknown | Actions::UpStair | Actions::DownStair => return,
+      Actions::Wait | Actions::Die | Actions::Unknown | Actions::UpStair | Actions::DownStair | Actions::Attack(_) |
+      Actions::PickUp | Actions::Drop | Actions::Apply | Actions::Throw | Actions::Equip | Actions::Unequip => return,
       _ => ()
     }
 
@@ -314,10 +974,15 @@ impl World {
     // }
 
     for item in items_at_feet {
+      // Anything for sale has a price tag on it
+      let tag = match self.quote(item) {
+        Some(price) if item.unpaid => format!(" ({} gold)", price),
+        _ => String::new()
+      };
       if item.quantity > 1 {
-        log!( (Box::leak(format!("You see {} {}s here", item.quantity, item.get_id()).into_boxed_str()), item.get_fg()) );
+        log!( (Box::leak(format!("You see {} {}s here{}", item.quantity, item.get_id(), tag).into_boxed_str()), item.get_fg()) );
       } else {
-        log!( (Box::leak(format!("You see a {} here", item.get_id()).into_boxed_str()), item.get_fg()) );
+        log!( (Box::leak(format!("You see a {} here{}", item.get_id(), tag).into_boxed_str()), item.get_fg()) );
       }
     }
 
@@ -356,7 +1021,7 @@ impl World {
 
             log!(("You fall down a shaft!", RGB(200, 50, 20)));
             
-            for _floors in 0..rand::thread_rng().gen_range(1, 4) {
+            for _floors in 0..self.rng.gen_range(1, 4) {
               self.go_down();
             }
 
@@ -365,9 +1030,7 @@ impl World {
           // Turn creature a new color
           tile::Trap::PaintBomb => {
 
-            let mut rng = rand::thread_rng();
-
-            let col = RGB(rng.gen_range(1, 255), rng.gen_range(1, 255), rng.gen_range(1, 255));
+            let col = RGB(self.rng.gen_range(1, 255), self.rng.gen_range(1, 255), self.rng.gen_range(1, 255));
 
             log!(("It's a paint bomb!", RGB(100, 100, 100)));
 
@@ -382,7 +1045,8 @@ impl World {
 
             log!(("It's a teleporter!", RGB(50, 127, 200)));
 
-            self.player.actor.pos = Dungeon::get_valid_location(&self.floor.dun.grid);
+            self.player.actor.pos = World::find_free_location(&self.floor.dun.grid, &self.floor.creatures, &mut self.rng);
+            self.update_occupancy();
 
           }
 
@@ -392,8 +1056,11 @@ impl World {
       _ => {}
     }
 
+    // Creatures that stepped on teleporters, who get moved once everyone's been checked
+    let mut teleported = vec![];
+
     // Did a creature step on a trap
-    for creature in &mut self.floor.creatures {
+    for (idx, creature) in self.floor.creatures.iter_mut().enumerate() {
       match &self.floor.dun[creature.actor.pos].tiletype.clone() {
         // We only care about traps, and this matches every trap
         tile::Type::Trap(trap) => {
@@ -416,9 +1083,7 @@ impl World {
             // Turn creature a new color
             tile::Trap::PaintBomb => {
 
-              let mut rng = rand::thread_rng();
-
-              let col = RGB(rng.gen_range(1, 255), rng.gen_range(1, 255), rng.gen_range(1, 255));
+              let col = RGB(self.rng.gen_range(1, 255), self.rng.gen_range(1, 255), self.rng.gen_range(1, 255));
 
               log!(("You hear an explosion!", RGB(100, 100, 100)));
 
@@ -431,7 +1096,7 @@ impl World {
 
               log!(("You hear the hum of a teleporter!", RGB(50, 127, 200)));
 
-              creature.actor.pos = Dungeon::get_valid_location(&self.floor.dun.grid);
+              teleported.push(idx);
 
             }
           }
@@ -440,6 +1105,18 @@ impl World {
       }
     }
 
+    // Never onto anyone else, the player included
+    for idx in teleported {
+      let pos = loop {
+        let pos = World::find_free_location(&self.floor.dun.grid, &self.floor.creatures, &mut self.rng);
+        if pos != self.player.actor.pos {
+          break pos;
+        }
+      };
+      self.floor.creatures[idx].actor.pos = pos;
+      self.update_occupancy();
+    }
+
   }
 
   ///
@@ -483,13 +1160,16 @@ impl World {
   ///
   /// See if the player is able to go up on the current tile and draw some stuff to the log
   /// 
-  pub fn player_go_up(&mut self) {
+  /// Returns whether the player actually went anywhere
+  /// 
+  pub fn player_go_up(&mut self) -> bool {
 
     match self.get_tile_at(self.player.actor.pos.x, self.player.actor.pos.y).tiletype {
       tile::Type::Stair(tile::Stair::UpStair(_)) => {
         if self.floor_num != 0 {
           self.go_up();
           log!(("You bravely venture forth...", RGB(255, 255, 200)));
+          return true;
         } else {
           log!(("You are not allowed to turn back now...", RGB(100, 50, 25)));
         }
@@ -497,19 +1177,25 @@ impl World {
       _ => log!(("You can't go up here", RGB(150, 150, 150)))
     }
 
+    return false;
+
   }
 
   ///
   /// See if the player is able to go down on the current tile and draw some stuff to the log
   /// 
-  pub fn player_go_down(&mut self) {
+  /// Returns whether the player actually went anywhere
+  /// 
+  pub fn player_go_down(&mut self) -> bool {
     match self.get_tile_at(self.player.actor.pos.x, self.player.actor.pos.y).tiletype {
       tile::Type::Stair(tile::Stair::DownStair(_)) => {
         self.go_down();
         log!(("You bravely venture forth...", RGB(255, 255, 200)));
+        return true;
       },
       _ => log!(("You can't go down here", RGB(150, 150, 150)))
     }
+    return false;
   }
 
   ///
@@ -523,15 +1209,13 @@ impl World {
     // If the floor number that we are on is not a floor in the stack,
     // we need to add a new floor to the stack
     if self.floor_num > self.floor_stack.len() - 1 {
-      let dun = World::create_test_dungeon(self.floor.dun.get_bounds_pos());
+      let dun = World::create_test_dungeon(self.floor.dun.get_bounds_pos(), &mut self.rng);
       let grid = dun.grid.clone();
-      let creatures = World::create_test_creatures(&grid);
+      let creatures = World::create_creatures(&grid, self.floor_num, &mut self.rng);
       floor = Floor::new(dun, creatures);
-      // Create n gold coins at a valid location
-      let gold_loc = Dungeon::get_valid_location(&floor.dun.grid);
-      floor.items.push(
-        Item::new("gold piece", '$', gold_loc, RGB(238, 232, 170), RGB(0, 0, 0), rand::thread_rng().gen_range(10, 40), ItemProperty::Money(Money::Gold))
-      );
+      floor.items = World::create_items(&grid, self.floor_num, &mut self.rng);
+      floor.scent.wind = ScentMap::draught(&mut self.rng);
+      World::open_shop(&mut floor, self.floor_num, &mut self.rng);
       self.floor_stack.push(floor.clone());
     // Otherwise the floor already exists in the stack and can be brought out
     } else {
@@ -542,12 +1226,31 @@ impl World {
 
     self.tcod_map = World::new_tcod_map(self.floor.dun.get_bounds_pos(), &self.floor.dun);
 
-    let start_loc = Dungeon::get_valid_location(&self.floor.dun.grid);
+    let start_loc = World::find_free_location(&self.floor.dun.grid, &self.floor.creatures, &mut self.rng);
     self.player.actor.pos.x = start_loc.x;
     self.player.actor.pos.y = start_loc.y;
 
     self.update_fov();
+    self.update_occupancy();
+    self.update_desire();
+
+  }
 
+  ///
+  /// Return an empty tcod map to be filled in later
+  ///
+  fn blank_tcod_map() -> Map {
+    Map::new(0, 0)
+  }
+
+  ///
+  /// Rebuild everything about the world that isn't saved
+  ///
+  pub fn restore(&mut self) {
+    self.tcod_map = World::new_tcod_map(self.floor.dun.get_bounds_pos(), &self.floor.dun);
+    self.update_fov();
+    self.update_occupancy();
+    self.update_desire();
   }
 
   ///
@@ -575,21 +1278,22 @@ impl World {
   ///
   /// Return a new `World`
   ///
-  pub fn new(map_dim: Pos) -> Self {
+  /// Worlds made with the same `seed` are identical
+  ///
+  pub fn new(map_dim: Pos, seed: usize) -> Self {
+
+    let mut rng = GameRng::new(seed);
 
     // Create a basic dungeon, tcod map from that dungeon, and a grid we can
     // put test creatures on.
-    let dun = World::create_test_dungeon(map_dim);
+    let dun = World::create_test_dungeon(map_dim, &mut rng);
     let grid = dun.grid.clone();
     let tcod_map =  World::new_tcod_map(map_dim, &dun);
 
-    let mut floor = Floor::new(dun, World::create_test_creatures(&grid));
-
-    // Create n gold coins at a valid location
-    let gold_loc = Dungeon::get_valid_location(&floor.dun.grid);
-    floor.items.push(
-      Item::new("gold piece", '$', gold_loc, RGB(238, 232, 170), RGB(0, 0, 0), rand::thread_rng().gen_range(10, 40), ItemProperty::Money(Money::Gold))
-    );
+    let mut floor = Floor::new(dun, World::create_creatures(&grid, 0, &mut rng));
+    floor.items = World::create_items(&grid, 0, &mut rng);
+    floor.scent.wind = ScentMap::draught(&mut rng);
+    World::open_shop(&mut floor, 0, &mut rng);
 
     let mut floor_stack = Vec::new();
     floor_stack.push(floor.clone());
@@ -599,11 +1303,16 @@ impl World {
       floor: floor,
       floor_stack: floor_stack,
       floor_num: 0,
-      tcod_map: tcod_map
+      time: 0,
+      cause_of_death: None,
+      tcod_map: tcod_map,
+      rng: rng
     };
 
-    world.player.actor.pos = Dungeon::get_valid_location(&world.floor.dun.grid);
+    world.player.actor.pos = World::find_free_location(&world.floor.dun.grid, &world.floor.creatures, &mut world.rng);
     world.update_fov();
+    world.update_occupancy();
+    world.update_desire();
 
     return world;
 
@@ -624,165 +1333,99 @@ impl World {
   ///
   fn update_scent(&mut self) {
 
-    // Create initial bloom around player
-    let player_x = self.player.actor.pos.x;
-    let player_y = self.player.actor.pos.y;
-
-    for x in SC_DIAM_LOWER..SC_DIAM_UPPER {
-      for y in SC_DIAM_LOWER..SC_DIAM_UPPER {
-        if self.is_valid_pos(player_x - x, player_y - y) {
-          for scent in &mut self.get_mut_tile_at(player_x - x, player_y - y).scents {
-            match scent.scent_type {
-              tile::Scent::Player => {
-                scent.val = SC_INC
-              }
-              _ => {}
-            }
-          }
-        }
-      }
+    // Everyone leaves their scent where they're standing
+    let mut sources = vec![(self.player.actor.pos, self.player.stats.scent_type.clone())];
+    for creature in &self.floor.creatures {
+      sources.push((creature.actor.pos, creature.stats.scent_type.clone()));
     }
 
-    // Save information about creatures
-    let mut creature_information = vec![];
-    for creature in &self.floor.creatures {
-      let creature_x = creature.actor.pos.x;
-      let creature_y = creature.actor.pos.y;
-      let scent_type = creature.stats.scent_type.clone();
-      creature_information.push((creature_x, creature_y, scent_type));
-    }
-
-    // For tuple in creature information
-    for tuple in &creature_information {
-      // Unpack
-      let creature_x = tuple.0;
-      let creature_y = tuple.1;
-      let scent_type = &tuple.2;
-
-      for x in SC_DIAM_LOWER..SC_DIAM_UPPER {
-        for y in SC_DIAM_LOWER..SC_DIAM_UPPER {
-          if self.is_valid_pos(creature_x - x, creature_y - y) {
-            for scent in &mut self.get_mut_tile_at(creature_x - x, creature_y - y).scents {
-              if &scent.scent_type == scent_type {
-                scent.val = SC_INC
-              }
-            }
-          }
-        }
+    // And so does anything else that smells
+    for (pos, emitter) in self.find_emitters() {
+      if let Some(scent) = emitter.scent {
+        sources.push((pos, scent));
       }
     }
 
-    // Create individual averages for each scent
-
-    // Create buffer for scent updating, only create one
-    // because we never change it
-    let buffer = self.floor.dun.grid.clone();
-
-    for scent_type_idx in 0..tile::Scent::Num as usize {
-
-      let filter = |tile: &Tile| -> f32 {
-        // So, interestingly, if a tile has no scent and is given 0.0 scent after the filter,
-        // it creates square scents that travel further, though for some reason a 0.1 value there creates
-        // very nice circular scents... I assume this is due to averages now being fuzzy in terms of accuracy?
-        if tile.scents[scent_type_idx].val == 0 { 0.1 } else { 1.0 }
-      };
-
-      // Return an f32 value that is the average value of `Scent`s surrounding the desired position, with a slight decay factor
-      // This is not a "true" average of all neighboring `Scent`s.
-      let avg_of_neighbors = |x: usize, y: usize| -> f32 {
-
-        // Add all tile values
-        (
-          buffer[x - 1][  y  ].scents[scent_type_idx].val as f32 +
-          buffer[x + 1][  y  ].scents[scent_type_idx].val as f32 +
-          buffer[  x  ][y - 1].scents[scent_type_idx].val as f32 +
-          buffer[  x  ][y + 1].scents[scent_type_idx].val as f32 +
-          buffer[x + 1][y + 1].scents[scent_type_idx].val as f32 +
-          buffer[x - 1][y - 1].scents[scent_type_idx].val as f32 +
-          buffer[x + 1][y - 1].scents[scent_type_idx].val as f32 +
-          buffer[x - 1][y + 1].scents[scent_type_idx].val as f32
-        ) /
-
-        // Divide by num tiles present, to get the average
-        // Add some value to reduce size of bloom
-        (((
-          filter(&buffer[x - 1][  y  ]) +
-          filter(&buffer[x + 1][  y  ]) +
-          filter(&buffer[  x  ][y - 1]) +
-          filter(&buffer[  x  ][y + 1]) +
-          filter(&buffer[x + 1][y + 1]) +
-          filter(&buffer[x - 1][y - 1]) +
-          filter(&buffer[x + 1][y - 1]) +
-          filter(&buffer[x - 1][y + 1]
-        )) + SC_BLOOM_CUTOFF)
-
-        // Decay factor
-        * SC_DECAY)
-      };
+    self.floor.scent.update(&self.floor.dun.grid, &sources);
+  }
 
-      // Change values of map based on averages from the buffer
-      for x in 0..self.floor.dun.width {
-        for y in 0..self.floor.dun.height {
-          if self.is_valid_pos(x as isize, y as isize) {
-            self.floor.dun[x][y].scents[scent_type_idx].val = avg_of_neighbors(x, y) as u8;
-          }
+  ///
+  /// Find every tile and item on the floor that smells or makes noise by itself, and where it is
+  ///
+  fn find_emitters(&self) -> Vec<(Pos, Emitter)> {
+    let mut emitters = vec![];
+    for (x, column) in self.floor.dun.grid.iter().enumerate() {
+      for (y, t) in column.iter().enumerate() {
+        if let Some(ref emitter) = t.emitter {
+          emitters.push((Pos::from_usize(x, y), emitter.clone()));
         }
       }
     }
+    for item in &self.floor.items {
+      if let Some(ref emitter) = item.emitter {
+        emitters.push((item.pos, emitter.clone()));
+      }
+    }
+    return emitters;
   }
 
   ///
   /// Update the sound map
-  /// 
+  ///
+  /// Every noise made this turn spreads out from where it was made, muffled by whatever it passes through. See
+  /// `map::sound` for how
+  ///
   pub fn update_sound(&mut self) {
-    let dist = |pos: Pos, x: isize, y: isize| -> usize {
-      (((pos.x - x).pow(2) + (pos.y - y).pow(2)) as f32).sqrt().floor() as usize
-    };
     let mut sounds : Vec<(Pos, usize)> = vec![];
 
     sounds.append(&mut self.find_movement_sounds());
+    sounds.extend(self.find_emitters().into_iter()
+      .filter(|&(_, ref emitter)| emitter.loudness > 0)
+      .map(|(pos, emitter)| (pos, emitter.loudness)));
     // Other sound generators go here
 
-    // Reset sound to 0
-    for x in 0..self.floor.dun.width {
-        for y in 0..self.floor.dun.height {
-          let mut tile = self.get_mut_tile_at(x as isize, y as isize);
-          tile.sound = 0;
-        }
-    }
-
-    // Expand each sound point-source
-    // Sound decrases in intensity proportional
-    // to the inverse of distance squared
-    for sound in sounds {
-      for x in 0..self.floor.dun.width {
-        for y in 0..self.floor.dun.height {
-          let mut tile = self.get_mut_tile_at(x as isize, y as isize);
-          if true {
-            tile.sound = tile.sound + (sound.1 / ((dist(sound.0, x as isize, y as isize) + 1).pow(2)));
-          }
-        }
-      }
-    }
+    sound::propagate(&mut self.floor.dun.grid, &sounds);
   }
 
   ///
-  /// Update the fov map from the player's perspective
+  /// Update the fov map from the player's perspective, marking everything in it as seen
+  /// 
+  /// Seen tiles used to be marked by the renderer, but that meant a game played without a window remembered less
+  /// of the map than the same game played with one
   /// 
   pub fn update_fov(&mut self) {
-    self.tcod_map.compute_fov(self.player.actor.pos.x as i32, self.player.actor.pos.y as i32, 20, true, FovAlgorithm::Shadow);
+    let radius = self.sight_radius() as i32;
+    self.tcod_map.compute_fov(self.player.actor.pos.x as i32, self.player.actor.pos.y as i32, radius, true, FovAlgorithm::Shadow);
+    for x in 0..self.floor.dun.width {
+      for y in 0..self.floor.dun.height {
+        if self.tcod_map.is_in_fov(x as i32, y as i32) {
+          self.floor.dun[x][y].seen = true;
+        }
+      }
+    }
   }
 
   ///
   /// Update the game world
   ///
   pub fn update(&mut self) {
+    self.burn_light();
     self.update_fov();
+    self.update_corpses();
     self.update_scent();
-    for creature in &mut self.floor.creatures {
-      creature.take_turn(&self.floor.dun.grid, &self.player)
+    // The player may have moved since the last update
+    self.update_occupancy();
+    self.update_desire();
+    self.player_attack();
+    self.check_assault();
+    let mut cost = self.player.state.cost();
+    // Sneaking takes half again as long as walking normally
+    if self.player.sneaking && self.player.state == Actions::Move {
+      cost += cost / 2;
     }
+    self.pass_time(cost);
     self.check_traps();
+    self.check_theft();
     self.check_items();
     self.update_sound();
     self.check_death();
@@ -793,14 +1436,14 @@ impl World {
     let mut sounds : Vec<(Pos, usize)> = vec![];
     // Determine if the player made sound by moving
     match &self.player.state {
-      Actions::Move => sounds.push((self.player.actor.pos, self.player.stats.weight)),
+      Actions::Move => sounds.push((self.player.actor.pos, self.player.footsteps())),
       Actions::Talk => sounds.push((self.player.actor.pos, 25)),
       _ => {}
     }
     // Determine if any creatures made sound by moving
     for creature in &self.floor.creatures {
       match &creature.state {
-        Actions::Move => sounds.push((creature.actor.pos, creature.stats.weight)),
+        Actions::Move => sounds.push((creature.actor.pos, creature.footsteps())),
         Actions::Talk => sounds.push((creature.actor.pos, 25)),
         _ => {}
       }
@@ -0,0 +1,120 @@
+//!
+//! Keep track of who is standing where
+//!
+//! The dungeon only knows about terrain, so without this nothing stops two creatures from standing on the same tile. The
+//! occupancy layer is a grid the size of the floor that says whether the player or a creature is standing on each tile,
+//! which lets AIs avoid each other and lets the world sort out what happens when they bump into one another.
+//!
+//! It's built from creature positions and rebuilt whenever creatures come or go, so it never gets saved.
+//!
+
+use core::creature::{Actions, Creature};
+use core::world::dungeon::map::Pos;
+
+///
+/// What happens when something walks into a creature (other than the player, who always attacks)
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Collision {
+  // Nobody gets past
+  Block,
+  // Trade places with whoever walked in
+  Swap,
+  // Get shoved one tile further along, as long as whoever is pushing weighs at least as much and there's room
+  Push
+}
+
+impl Default for Collision {
+  fn default() -> Self {
+    Collision::Block
+  }
+}
+
+///
+/// Who is standing on a tile
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Occupant {
+  Player,
+  // Index into the floor's creatures
+  Creature(usize)
+}
+
+///
+/// Grid of who is standing where
+///
+#[derive(Clone, Debug, Default)]
+pub struct Occupancy {
+  width: usize,
+  height: usize,
+  cells: Vec<Option<Occupant>>
+}
+
+impl Occupancy {
+
+  ///
+  /// Return a new `Occupancy` with nobody in it
+  ///
+  pub fn new(width: usize, height: usize) -> Self {
+    Occupancy {
+      width: width,
+      height: height,
+      cells: vec![None; width * height]
+    }
+  }
+
+  ///
+  /// Build the occupancy of a floor. The dead don't take up any room
+  ///
+  pub fn build(width: usize, height: usize, player: &Creature, creatures: &[Box<Creature>]) -> Self {
+
+    let mut occupancy = Occupancy::new(width, height);
+
+    for (idx, creature) in creatures.iter().enumerate() {
+      if creature.state != Actions::Die {
+        occupancy.set(creature.actor.pos, Some(Occupant::Creature(idx)));
+      }
+    }
+
+    // Set the player last, so they win if anything is already on their tile
+    occupancy.set(player.actor.pos, Some(Occupant::Player));
+
+    return occupancy;
+
+  }
+
+  ///
+  /// Turn a position into an index into the cells, if it's on the floor
+  ///
+  fn idx(&self, pos: Pos) -> Option<usize> {
+    if pos.x < 0 || pos.y < 0 || pos.x as usize >= self.width || pos.y as usize >= self.height {
+      None
+    } else {
+      Some(pos.x as usize * self.height + pos.y as usize)
+    }
+  }
+
+  ///
+  /// Get who is standing at `pos`. Nobody is standing off the edge of the floor
+  ///
+  pub fn get(&self, pos: Pos) -> Option<Occupant> {
+    self.idx(pos).and_then(|idx| self.cells[idx])
+  }
+
+  ///
+  /// Check if nobody is standing at `pos`
+  ///
+  pub fn is_free(&self, pos: Pos) -> bool {
+    self.get(pos).is_none()
+  }
+
+  ///
+  /// Set who is standing at `pos`. Positions off the floor are ignored
+  ///
+  pub fn set(&mut self, pos: Pos, occupant: Option<Occupant>) {
+    if let Some(idx) = self.idx(pos) {
+      self.cells[idx] = occupant;
+    }
+  }
+
+}
@@ -0,0 +1,245 @@
+//!
+//! Scent, and how it spreads
+//!
+//! Scent used to live on every `Tile` as a list of values, which meant cloning the whole map each turn just to spread it.
+//! Now it's kept in a `ScentMap` alongside the floor instead, with one dense layer of values per `tile::Scent`. Each turn,
+//! creatures leave their scent on the tiles around them, and then every tile that can hold scent takes on the average of
+//! itself and its neighbours before losing a little to `tile::scent_decay()`. Walls don't hold scent at all, so it follows
+//! corridors around instead of soaking into the rock, and water washes it away quickly.
+//!
+//! If there's wind, scent drifts along with it, since tiles pull more from whichever neighbour is upwind of them. Some new
+//! floors get a draught blowing through them from `ScentMap::draught()`, and the rest are still.
+//!
+//! Layers that have nothing in them and nobody adding to them are skipped entirely, so floors that only smell of a couple
+//! of things only pay for a couple of layers.
+//!
+
+extern crate rand;
+use self::rand::Rng;
+
+use core::world::dungeon::map::{tile, Grid, Pos, Tile};
+use core::world::dungeon::map::path::DIRECTIONS;
+
+///
+/// How strong scent is right where it's left
+///
+pub const SCENT_STRENGTH : u8 = 100;
+
+///
+/// How far around a creature it leaves its scent
+///
+const SCENT_RADIUS : isize = 1;
+
+///
+/// How much more a tile pulls from its upwind neighbour than from the others when there's wind
+///
+const WIND_WEIGHT : f32 = 3.0;
+
+///
+/// How many floors in ten have a draught blowing through them
+///
+const DRAUGHT_CHANCE : u32 = 3;
+
+///
+/// Every scent on a floor
+///
+/// Values are stored column by column, the same way a `Grid` is, so position `(x, y)` is at `x * height + y` in every layer.
+/// They're whole numbers, and rounding down every turn is what keeps faint trails from spreading across the whole floor
+/// forever
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScentMap {
+  width: usize,
+  height: usize,
+  layers: Vec<Vec<u8>>,
+  // Which way scent drifts, as a step in that direction. None when the air is still
+  pub wind: Option<Pos>,
+  // How much of its scent each tile keeps every turn, or 0 if it can't hold any. Worked out from the map when updating
+  #[serde(skip)]
+  decay: Vec<f32>,
+  // Where the next values for a layer are worked out before being swapped in
+  #[serde(skip)]
+  scratch: Vec<u8>
+}
+
+impl ScentMap {
+
+  ///
+  /// Return a new `ScentMap` for a `width` by `height` floor that doesn't smell of anything
+  ///
+  pub fn new(width: usize, height: usize) -> Self {
+    ScentMap {
+      width: width,
+      height: height,
+      layers: vec![vec![0; width * height]; tile::Scent::Num as usize],
+      wind: None,
+      decay: vec![],
+      scratch: vec![]
+    }
+  }
+
+  ///
+  /// Pick the wind for a new floor, which is usually still but sometimes has a draught blowing one way through it
+  ///
+  pub fn draught<R: Rng>(rng: &mut R) -> Option<Pos> {
+    if rng.gen_range(0, 10) < DRAUGHT_CHANCE {
+      return rng.choose(&DIRECTIONS).cloned();
+    }
+    return None;
+  }
+
+  ///
+  /// Get the index of `pos` in a layer, if it's on the map
+  ///
+  #[inline]
+  fn index(&self, pos: Pos) -> Option<usize> {
+    if pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.width && (pos.y as usize) < self.height {
+      Some(pos.x as usize * self.height + pos.y as usize)
+    } else {
+      None
+    }
+  }
+
+  ///
+  /// How strongly `pos` smells of `scent`. Nothing can be smelled off the map
+  ///
+  pub fn get(&self, pos: Pos, scent: &tile::Scent) -> u8 {
+    match self.index(pos) {
+      Some(i) => self.layers[scent.clone() as usize][i],
+      None => 0
+    }
+  }
+
+  ///
+  /// How strongly `pos` smells of everything but `scent`, all together
+  ///
+  /// This is a `usize` since it can add up to far more than any one scent can hold
+  ///
+  pub fn others(&self, pos: Pos, scent: &tile::Scent) -> usize {
+    return tile::Scent::iterator()
+      .filter(|other| *other != scent)
+      .map(|other| self.get(pos, other) as usize)
+      .sum();
+  }
+
+  ///
+  /// Make `pos` smell of `scent` at least as strongly as `strength`
+  ///
+  pub fn leave(&mut self, pos: Pos, scent: &tile::Scent, strength: u8) {
+    if let Some(i) = self.index(pos) {
+      let value = &mut self.layers[scent.clone() as usize][i];
+      if strength > *value {
+        *value = strength;
+      }
+    }
+  }
+
+  ///
+  /// Spread every scent for a turn, after leaving each of `sources` on the tiles around it
+  ///
+  /// Starts afresh if `map` isn't the size the scent map was made for, which is the case for old saves and new floors.
+  /// Returns how many times a tile's scent was worked out, which is all the work spreading does
+  ///
+  pub fn update(&mut self, map: &Grid<Tile>, sources: &[(Pos, tile::Scent)]) -> usize {
+
+    let width = map.len();
+    let height = if width > 0 { map[0].len() } else { 0 };
+
+    if width != self.width || height != self.height || self.layers.len() != tile::Scent::Num as usize {
+      let wind = self.wind;
+      *self = ScentMap::new(width, height);
+      self.wind = wind;
+    }
+
+    // Work out what each tile does to scent once, rather than once per layer
+    self.decay.clear();
+    for column in map.iter() {
+      for t in column.iter() {
+        self.decay.push(tile::scent_decay(t).unwrap_or(0.0));
+      }
+    }
+
+    let mut active = vec![false; tile::Scent::Num as usize];
+
+    for &(pos, ref scent) in sources {
+      for dx in -SCENT_RADIUS..(SCENT_RADIUS + 1) {
+        for dy in -SCENT_RADIUS..(SCENT_RADIUS + 1) {
+          let at = pos + Pos::new(dx, dy);
+          if self.index(at).map_or(false, |i| self.decay[i] > 0.0) {
+            self.leave(at, scent, SCENT_STRENGTH);
+          }
+        }
+      }
+      active[scent.clone() as usize] = true;
+    }
+
+    let mut work = 0;
+    for layer in 0..self.layers.len() {
+      if active[layer] || self.layers[layer].iter().any(|value| *value > 0) {
+        work += self.spread(layer);
+      }
+    }
+
+    return work;
+
+  }
+
+  ///
+  /// Spread a single layer for a turn, returning how many tiles it was worked out for
+  ///
+  fn spread(&mut self, layer: usize) -> usize {
+
+    let width = self.width as isize;
+    let height = self.height as isize;
+    let upwind = self.wind.map(|wind| Pos::new(-wind.x, -wind.y));
+
+    let mut next = ::std::mem::replace(&mut self.scratch, vec![]);
+    next.clear();
+    next.resize(self.layers[layer].len(), 0);
+    let mut work = 0;
+
+    {
+      let values = &self.layers[layer];
+      let decay = &self.decay;
+
+      for x in 0..width {
+        for y in 0..height {
+
+          let i = (x * height + y) as usize;
+          if decay[i] == 0.0 {
+            continue;
+          }
+
+          let mut total = values[i] as f32;
+          let mut weight = 1.0;
+
+          for dx in -1..2 {
+            for dy in -1..2 {
+              let (nx, ny) = (x + dx, y + dy);
+              if (dx == 0 && dy == 0) || nx < 0 || ny < 0 || nx >= width || ny >= height {
+                continue;
+              }
+              let n = (nx * height + ny) as usize;
+              if decay[n] == 0.0 {
+                continue;
+              }
+              let pull = if upwind == Some(Pos::new(dx, dy)) { 1.0 + WIND_WEIGHT } else { 1.0 };
+              total += values[n] as f32 * pull;
+              weight += pull;
+            }
+          }
+
+          next[i] = (total / weight * decay[i]) as u8;
+          work += 1;
+
+        }
+      }
+    }
+
+    self.scratch = ::std::mem::replace(&mut self.layers[layer], next);
+
+    return work;
+
+  }
+
+}
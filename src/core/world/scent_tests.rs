@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests {
+
+  use core::rng::GameRng;
+  use core::world::World;
+  use core::world::dungeon::map::{tile, Grid, Pos, Tile};
+  use core::world::dungeon::map::grid_tests::{self, room};
+  use core::world::scent::{ScentMap, SCENT_STRENGTH};
+
+  // Let `scent` spread for `turns` turns with the player standing at `pos` the whole time
+  fn linger(scent: &mut ScentMap, map: &Grid<Tile>, pos: Pos, turns: usize) {
+    for _ in 0..turns {
+      scent.update(map, &[(pos, tile::Scent::Player)]);
+    }
+  }
+
+  // Scent is strongest where it's left and fades out from there
+  #[test]
+  fn scent_spreads() {
+    let map = room(20, 20);
+    let mut scent = ScentMap::default();
+    linger(&mut scent, &map, Pos::new(10, 10), 10);
+
+    let player = |x, y| scent.get(Pos::new(x, y), &tile::Scent::Player);
+    assert!(player(10, 10) > 0 && player(10, 10) <= SCENT_STRENGTH);
+    assert!(player(10, 10) >= player(12, 10));
+    assert!(player(12, 10) > player(14, 10));
+    assert!(player(14, 10) > 0);
+    assert_eq!(scent.get(Pos::new(10, 10), &tile::Scent::Canine), 0);
+    assert_eq!(scent.get(Pos::new(-1, 10), &tile::Scent::Player), 0);
+
+    // And fades away once nobody is around to leave it
+    for _ in 0..200 {
+      scent.update(&map, &[]);
+    }
+    assert_eq!(scent.get(Pos::new(10, 10), &tile::Scent::Player), 0);
+  }
+
+  // Walls don't hold scent, or let it through
+  #[test]
+  fn walls_block_scent() {
+    let mut map = room(20, 20);
+    grid_tests::wall_down(&mut map, 12, &tile::generic_wall());
+    let mut scent = ScentMap::default();
+    linger(&mut scent, &map, Pos::new(11, 10), 30);
+
+    assert!(scent.get(Pos::new(11, 10), &tile::Scent::Player) > 0);
+    assert_eq!(scent.get(Pos::new(12, 10), &tile::Scent::Player), 0);
+    assert_eq!(scent.get(Pos::new(13, 10), &tile::Scent::Player), 0);
+  }
+
+  // Scent drifts downwind
+  #[test]
+  fn wind_carries_scent() {
+    let map = room(20, 20);
+    let mut scent = ScentMap::default();
+    scent.wind = Some(Pos::new(1, 0));
+    linger(&mut scent, &map, Pos::new(10, 10), 10);
+
+    assert!(scent.get(Pos::new(14, 10), &tile::Scent::Player) > scent.get(Pos::new(6, 10), &tile::Scent::Player));
+  }
+
+  // Water washes scent away, and damp biomes don't hold it as long
+  #[test]
+  fn water_washes_scent_away() {
+    let mut water = room(20, 20);
+    for x in 12..20 {
+      grid_tests::wall_down(&mut water, x, &grid_tests::plain(tile::Type::Water));
+    }
+    let mut sunken = room(20, 20);
+    for column in sunken.iter_mut() {
+      for t in column.iter_mut() {
+        t.biome = tile::Biome::Sunken;
+      }
+    }
+
+    let mut dry = ScentMap::default();
+    let mut wet = ScentMap::default();
+    let mut damp = ScentMap::default();
+    linger(&mut dry, &room(20, 20), Pos::new(10, 10), 10);
+    linger(&mut wet, &water, Pos::new(10, 10), 10);
+    linger(&mut damp, &sunken, Pos::new(10, 10), 10);
+
+    let at = Pos::new(13, 10);
+    assert!(wet.get(at, &tile::Scent::Player) < dry.get(at, &tile::Scent::Player));
+    assert!(damp.get(at, &tile::Scent::Player) < dry.get(at, &tile::Scent::Player));
+  }
+
+  // New floors are sometimes draughty, and the draught lasts as long as the floor does
+  #[test]
+  fn floors_get_draughts() {
+    let mut rng = GameRng::new(1234);
+    let winds : Vec<Option<Pos>> = (0..100).map(|_| ScentMap::draught(&mut rng)).collect();
+    let draughty = winds.iter().filter(|wind| wind.is_some()).count();
+    assert!(draughty > 10 && draughty < 60);
+    for wind in winds.iter().filter_map(|wind| *wind) {
+      assert!(wind != Pos::new(0, 0) && wind.x.abs() <= 1 && wind.y.abs() <= 1);
+    }
+
+    // Whatever the first floor got is kept on it, and through spreading
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    let wind = world.floor.scent.wind;
+    world.update_scent();
+    assert_eq!(world.floor.scent.wind, wind);
+    assert_eq!(world.floor_stack[0].scent.wind, wind);
+  }
+
+  // Everything a tile smells of is added up without overflowing, which a u8 total used to when drawing the panel
+  #[test]
+  fn other_scents_add_up() {
+    let mut scent = ScentMap::new(5, 5);
+    for scent_type in tile::Scent::iterator() {
+      scent.leave(Pos::new(2, 2), scent_type, 255);
+    }
+    assert_eq!(scent.others(Pos::new(2, 2), &tile::Scent::Player), 255 * (tile::Scent::Num as usize - 1));
+    assert_eq!(scent.others(Pos::new(2, 3), &tile::Scent::Player), 0);
+    assert_eq!(scent.others(Pos::new(-1, 2), &tile::Scent::Player), 0);
+
+    scent.leave(Pos::new(2, 3), &tile::Scent::Player, 255);
+    assert_eq!(scent.others(Pos::new(2, 3), &tile::Scent::Player), 0);
+    assert_eq!(scent.others(Pos::new(2, 3), &tile::Scent::Canine), 255);
+  }
+
+  // Spreading costs one pass over the floor for each scent that's around, and nothing for the ones that aren't, so a busy
+  // 200x200 floor costs a hundred times what a 20x20 one does and no more
+  #[test]
+  fn spreading_scales_with_floor_size() {
+    let smells = |size: isize| -> Vec<(Pos, tile::Scent)> {
+      (0..200)
+        .map(|i| (Pos::new((i * 37) % size, (i * 91) % size), tile::Scent::iterator().nth(i as usize % 4).unwrap().clone()))
+        .collect()
+    };
+    let work = |size: usize, turns: usize| -> usize {
+      let map = room(size, size);
+      let sources = smells(size as isize);
+      let mut scent = ScentMap::default();
+      return (0..turns).map(|_| scent.update(&map, &sources)).sum();
+    };
+
+    let small = work(20, 10);
+    let big = work(200, 10);
+    assert_eq!(small, 10 * 4 * 20 * 20);
+    assert_eq!(big, 100 * small);
+    assert!(big < 10 * tile::Scent::Num as usize * 200 * 200);
+
+    // Walls aren't worked out at all
+    let mut map = room(40, 40);
+    for x in 20..40 {
+      grid_tests::wall_down(&mut map, x, &tile::generic_wall());
+    }
+    let mut scent = ScentMap::default();
+    assert_eq!(scent.update(&map, &smells(20)), 4 * 20 * 40);
+
+    // And once everyone's gone and the scent has faded, it's free
+    for _ in 0..200 {
+      scent.update(&map, &[]);
+    }
+    assert_eq!(scent.update(&map, &[]), 0);
+  }
+
+}
@@ -0,0 +1,374 @@
+//!
+//! Shops, and what gold is good for
+//!
+//! The dungeon builds the room (see `construct::Shop`), and the world stocks it and puts a shopkeeper in it. Anything in
+//! the shop marked `unpaid` belongs to the shopkeeper. The player can pick it up and carry it around the shop for free, but
+//! it's only theirs once it's paid for. Walking out with it is stealing, and so is hurting the shopkeeper, and either one
+//! turns them hostile for good.
+//!
+//! Shops buy things too, for a fraction of what they're worth, which then go up for sale like everything else.
+//!
+//! # Prices
+//!
+//! Everything has a `value` in gold. Shopkeepers charge that times their markup, which they each pick for themselves when
+//! the shop opens. The shop keeps the markup, along with everything else about how the shopkeeper feels about the player.
+//! Haggling can talk a shopkeeper's markup down, but failing to makes it go up, and they only have so much patience for
+//! it.
+//!
+
+extern crate rand;
+use self::rand::Rng;
+
+use core::creature::Actions;
+use core::creature::ai::ShopkeeperAI;
+use core::init;
+use core::item::{Item, ItemProperty};
+use core::renderer::RGB;
+use core::log;
+use core::rng::GameRng;
+
+use super::{Floor, World};
+use super::dungeon::map::construct::Shop;
+use super::dungeon::map::tile;
+
+///
+/// Least a shopkeeper marks things up when they open shop, in percent
+///
+const MARKUP_MIN : usize = 110;
+
+///
+/// Most a shopkeeper marks things up when they open shop, in percent
+///
+const MARKUP_MAX : usize = 150;
+
+///
+/// Haggling can't talk a shopkeeper's markup below this, in percent
+///
+const MARKUP_FLOOR : usize = 90;
+
+///
+/// How much a round of haggling changes the markup, either way
+///
+const HAGGLE_STEP : usize = 10;
+
+///
+/// Least the player has to roll on a d20 to haggle successfully
+///
+const HAGGLE_DC : usize = 12;
+
+///
+/// How much of what something is worth shops pay for it, in percent
+///
+const SELL_RATE : usize = 50;
+
+///
+/// Least and most gold a shopkeeper has to buy things with when they open shop
+///
+const PURSE : (usize, usize) = (100, 300);
+
+///
+/// How many times to look for something worth selling to put on each spot in the shop
+///
+const STOCK_TRIES : usize = 5;
+
+///
+/// How much a shopkeeper charging `markup` percent wants for `item`, never less than a gold piece for something worth
+/// anything at all
+///
+pub fn buy_price(item: &Item, markup: usize) -> usize {
+  let worth = item.value * item.quantity.max(0) as usize;
+  (worth * markup + 99) / 100
+}
+
+///
+/// How much a shop pays for `item`
+///
+pub fn sell_price(item: &Item) -> usize {
+  item.value * item.quantity.max(0) as usize * SELL_RATE / 100
+}
+
+impl World {
+
+  ///
+  /// Stock the floor's shop and put a shopkeeper in it, if the floor has a shop
+  ///
+  /// Stock comes from the same loot tables as everything else on the floor, minus money and anything worthless
+  ///
+  pub fn open_shop(floor: &mut Floor, depth: usize, rng: &mut GameRng) {
+
+    let shop = match floor.dun.shop {
+      Some(ref shop) => shop.clone(),
+      None => return
+    };
+
+    let mut keeper = init::monsters().get("shopkeeper").expect("No monster is defined as shopkeeper").spawn(shop.post);
+    keeper.ai = Box::new(ShopkeeperAI::at(shop.post));
+    keeper.wallet = rng.gen_range(PURSE.0, PURSE.1 + 1) as f32;
+
+    if let Some(ref mut shop) = floor.dun.shop {
+      shop.keeper = Some(floor.creatures.len());
+      shop.markup = rng.gen_range(MARKUP_MIN, MARKUP_MAX + 1);
+    }
+    floor.creatures.push(Box::new(keeper));
+
+    for pos in shop.stock {
+
+      let biome = floor.dun[pos].biome.clone();
+      let table = init::items().loot_table(&biome, depth);
+
+      for _ in 0..STOCK_TRIES {
+        match table.choose(rng) {
+          Some(&(def, rule)) if def.value > 0 => match def.property {
+            ItemProperty::Money(_) => continue,
+            _ => {
+              let mut item = def.create(pos, rng.gen_range(rule.count.0, rule.count.1 + 1) as isize);
+              item.unpaid = true;
+              floor.items.push(item);
+              break;
+            }
+          },
+          _ => continue
+        }
+      }
+
+    }
+
+  }
+
+  ///
+  /// Check if the player is in a shop
+  ///
+  pub fn in_shop(&self) -> bool {
+    tile::in_shop(&self.floor.dun[self.player.actor.pos])
+  }
+
+  ///
+  /// Get the index of the shopkeeper on this floor along with their shop, if they're still alive
+  ///
+  pub fn shopkeeper(&self) -> Option<(usize, &Shop)> {
+    let shop = match self.floor.dun.shop {
+      Some(ref shop) => shop,
+      None => return None
+    };
+    match shop.keeper {
+      Some(keeper) if self.floor.creatures.get(keeper).map_or(false, |creature| creature.state != Actions::Die) => {
+        Some((keeper, shop))
+      },
+      _ => None
+    }
+  }
+
+  ///
+  /// Get the shopkeeper the player can trade with right now and their shop, or why they can't
+  ///
+  pub fn open_for_business(&self) -> Result<(usize, &Shop), &'static str> {
+    if !self.in_shop() {
+      return Err("There's no shop here.");
+    }
+    match self.shopkeeper() {
+      Some((_, shop)) if shop.angry => Err("The shopkeeper won't deal with the likes of you!"),
+      Some(keeper) => Ok(keeper),
+      None => Err("There's nobody minding the shop.")
+    }
+  }
+
+  ///
+  /// What the shopkeeper the player is trading with would charge for `item` if it's unpaid, or pay for it if it isn't.
+  /// `None` if there's nobody to trade with
+  ///
+  pub fn quote(&self, item: &Item) -> Option<usize> {
+    match self.open_for_business() {
+      Ok((_, shop)) => Some(if item.unpaid { buy_price(item, shop.markup) } else { sell_price(item) }),
+      Err(_) => None
+    }
+  }
+
+  ///
+  /// Pay for the unpaid item in the `index`th slot of the player's inventory. Returns whether it was bought
+  ///
+  pub fn player_buy(&mut self, index: usize) -> bool {
+
+    let (keeper, markup) = match self.open_for_business() {
+      Ok((keeper, shop)) => (keeper, shop.markup),
+      Err(reason) => {
+        log!((reason, RGB(150, 150, 150)));
+        return false;
+      }
+    };
+
+    let price = match self.player.inventory.get(index) {
+      Some(item) if item.unpaid => buy_price(item, markup),
+      Some(_) => {
+        log!(("You already own that.", RGB(150, 150, 150)));
+        return false;
+      },
+      None => {
+        log!(("You don't have that", RGB(150, 150, 150)));
+        return false;
+      }
+    };
+
+    if self.player.wallet < price as f32 {
+      log!((Box::leak(format!("You can't afford that, it costs {} gold.", price).into_boxed_str()), RGB(150, 150, 150)));
+      return false;
+    }
+
+    self.player.wallet -= price as f32;
+    self.floor.creatures[keeper].wallet += price as f32;
+
+    // It might go on top of a stack the player already owns
+    let mut item = self.player.inventory.remove(index);
+    item.unpaid = false;
+    log!((Box::leak(format!("You buy {} for {} gold.", item.describe(), price).into_boxed_str()), RGB(238, 232, 170)));
+    self.player.pick_up(item);
+
+    return true;
+
+  }
+
+  ///
+  /// Sell whatever is in the `index`th slot of the player's inventory to the shop. Returns whether it was sold
+  ///
+  pub fn player_sell(&mut self, index: usize) -> bool {
+
+    let keeper = match self.open_for_business() {
+      Ok((keeper, _)) => keeper,
+      Err(reason) => {
+        log!((reason, RGB(150, 150, 150)));
+        return false;
+      }
+    };
+
+    let price = match self.player.inventory.get(index) {
+      Some(item) if item.unpaid => {
+        log!(("You can't sell what you haven't paid for.", RGB(150, 150, 150)));
+        return false;
+      },
+      Some(item) => sell_price(item),
+      None => {
+        log!(("You don't have that", RGB(150, 150, 150)));
+        return false;
+      }
+    };
+
+    if price == 0 {
+      log!(("The shopkeeper isn't interested in that.", RGB(150, 150, 150)));
+      return false;
+    }
+
+    if self.floor.creatures[keeper].wallet < price as f32 {
+      log!(("The shopkeeper can't afford that.", RGB(150, 150, 150)));
+      return false;
+    }
+
+    self.floor.creatures[keeper].wallet -= price as f32;
+    self.player.wallet += price as f32;
+
+    // It goes up for sale right where the player is standing
+    let mut item = self.player.drop_item(index).unwrap();
+    item.unpaid = true;
+    log!((Box::leak(format!("You sell {} for {} gold.", item.describe(), price).into_boxed_str()), RGB(238, 232, 170)));
+    self.floor.items.push(item);
+
+    return true;
+
+  }
+
+  ///
+  /// Try to talk the shopkeeper's markup down. Returns whether they were willing to haggle at all
+  ///
+  pub fn player_haggle(&mut self) -> bool {
+
+    let patience = match self.open_for_business() {
+      Ok((_, shop)) => shop.patience,
+      Err(reason) => {
+        log!((reason, RGB(150, 150, 150)));
+        return false;
+      }
+    };
+
+    if patience == 0 {
+      log!(("The shopkeeper won't hear another word about prices.", RGB(150, 150, 150)));
+      return false;
+    }
+
+    let agreed = self.rng.gen_range(1, 21) >= HAGGLE_DC;
+    let shop = self.floor.dun.shop.as_mut().unwrap();
+    shop.patience -= 1;
+
+    if agreed {
+      shop.markup = (shop.markup - HAGGLE_STEP).max(MARKUP_FLOOR);
+      log!((Box::leak(format!("The shopkeeper grudgingly agrees to charge {}%.", shop.markup).into_boxed_str()), RGB(238, 232, 170)));
+    } else {
+      shop.markup += HAGGLE_STEP;
+      log!((Box::leak(format!("The shopkeeper is insulted, and now charges {}%.", shop.markup).into_boxed_str()), RGB(255, 150, 100)));
+    }
+
+    return true;
+
+  }
+
+  ///
+  /// Tell the player what something they just picked up costs, if it isn't theirs
+  ///
+  pub fn quote_pick_up(&self, item: &Item) {
+    if !item.unpaid {
+      return;
+    }
+    if let Some(price) = self.quote(item) {
+      log!((Box::leak(format!("\"For you, {} gold,\" says the shopkeeper.", price).into_boxed_str()), RGB(238, 232, 170)));
+    }
+  }
+
+  ///
+  /// Check if the player has left the shop with something they haven't paid for, and make the shopkeeper hostile if they
+  /// have. Whatever they walked out with is theirs now, one way or another
+  ///
+  pub fn check_theft(&mut self) {
+
+    if self.in_shop() || !self.player.inventory.iter().any(|item| item.unpaid) {
+      return;
+    }
+
+    for item in &mut self.player.inventory {
+      item.unpaid = false;
+    }
+
+    if self.anger_shopkeeper() {
+      log!(("You leave without paying! The shopkeeper is furious!", RGB(255, 0, 0)));
+    }
+
+  }
+
+  ///
+  /// Check if the shopkeeper has been hurt, which they blame on the player whoever did it
+  ///
+  pub fn check_assault(&mut self) {
+
+    let hurt = match self.shopkeeper() {
+      Some((keeper, _)) => self.floor.creatures[keeper].stats.hp < self.floor.creatures[keeper].stats.max_hp,
+      None => false
+    };
+
+    if hurt {
+      self.anger_shopkeeper();
+    }
+
+  }
+
+  ///
+  /// Turn the shopkeeper hostile for good. Returns whether there was a shopkeeper who wasn't already
+  ///
+  fn anger_shopkeeper(&mut self) -> bool {
+
+    // Nobody left to mind
+    if self.shopkeeper().map_or(true, |(_, shop)| shop.angry) {
+      return false;
+    }
+
+    self.floor.dun.shop.as_mut().unwrap().angry = true;
+    return true;
+
+  }
+
+}
@@ -0,0 +1,200 @@
+#[cfg(test)]
+pub mod tests {
+
+  use core::world::World;
+  use core::world::shop::{buy_price, sell_price};
+  use core::world::dungeon::map::{tile, Pos};
+  use core::world::dungeon::map::construct::Shop;
+  use core::world::dungeon::map::construct::shop::PATIENCE_NORMAL;
+  use core::creature::Actions;
+  use core::init;
+  use core::item::ItemProperty;
+
+  // Wall in a shop below the player, who's left standing in its door, then stock it and put a shopkeeper in it
+  pub fn shop_world() -> World {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.test_empty();
+    world.player.inventory.clear();
+
+    let door = world.player.actor.pos;
+    for x in door.x - 2..door.x + 4 {
+      for y in door.y..door.y + 5 {
+        let inside = x > door.x - 2 && x < door.x + 3 && y > door.y && y < door.y + 4;
+        world.floor.dun[x as usize][y as usize] = if inside { tile::shop_floor() } else { tile::generic_wall() };
+      }
+    }
+    world.floor.dun[door] = tile::shop_floor();
+
+    let entrance = door + Pos::new(0, 1);
+    let post = entrance + Pos::new(1, 0);
+    let mut stock = vec![];
+    for x in door.x - 1..door.x + 3 {
+      for y in door.y + 1..door.y + 4 {
+        let pos = Pos::new(x, y);
+        if pos != entrance && pos != post {
+          stock.push(pos);
+        }
+      }
+    }
+    world.floor.dun.shop = Some(Shop::new(door, post, stock));
+
+    World::open_shop(&mut world.floor, 0, &mut world.rng);
+    world.update_occupancy();
+    return world;
+  }
+
+  // Step into the shop and pick up whatever's for sale on the first stocked spot
+  pub fn pick_up_stock(world: &mut World) {
+    world.player.actor.pos = world.floor.items[0].pos;
+    assert!(world.player_pick_up());
+    world.update_occupancy();
+  }
+
+  // Shops get a shopkeeper and a full stock of things worth buying, and nothing else turns up in them
+  #[test]
+  fn shops_are_stocked() {
+    let world = shop_world();
+    let shop = world.floor.dun.shop.clone().unwrap();
+
+    let (keeper, open) = world.shopkeeper().unwrap();
+    assert_eq!(world.floor.creatures[keeper].actor.pos, shop.post);
+    assert!(!open.angry);
+    assert!(world.floor.creatures[keeper].wallet > 0.0);
+
+    assert_eq!(world.floor.items.len(), shop.stock.len());
+    for item in &world.floor.items {
+      assert!(shop.stock.contains(&item.pos));
+      assert!(item.unpaid && item.value > 0);
+      match item.property {
+        ItemProperty::Money(_) => panic!("Shops don't sell money"),
+        _ => {}
+      }
+    }
+
+    assert!(!tile::spawnable(&world.floor.dun[shop.post]));
+  }
+
+  // Paying for something makes it the player's, and selling it puts it back up for sale
+  #[test]
+  fn buy_and_sell() {
+    let mut world = shop_world();
+    pick_up_stock(&mut world);
+    assert!(world.player.inventory[0].unpaid);
+
+    // Nothing can be bought without the gold for it, or sold before it's paid for
+    let price = world.quote(&world.player.inventory[0]).unwrap();
+    assert!(price > 0);
+    assert!(!world.player_buy(0));
+    assert!(!world.player_sell(0));
+
+    world.player.wallet = price as f32;
+    let (keeper, markup) = world.shopkeeper().map(|(keeper, shop)| (keeper, shop.markup)).unwrap();
+    let purse = world.floor.creatures[keeper].wallet;
+    assert_eq!(price, buy_price(&world.player.inventory[0], markup));
+    assert!(world.player_buy(0));
+    assert!(!world.player.inventory[0].unpaid);
+    assert_eq!(world.player.wallet, 0.0);
+    assert_eq!(world.floor.creatures[keeper].wallet, purse + price as f32);
+    assert!(!world.player_buy(0));
+
+    // Shops pay less than they charge
+    let offer = sell_price(&world.player.inventory[0]);
+    assert!(offer < price);
+    let stock = world.floor.items.len();
+    assert!(world.player_sell(0));
+    assert!(world.player.inventory.is_empty());
+    assert_eq!(world.player.wallet, offer as f32);
+    assert_eq!(world.floor.items.len(), stock + 1);
+    assert!(world.floor.items[stock].unpaid);
+
+    // Worthless things aren't wanted
+    world.player.pick_up(init::items().create("torch", Pos::new(0, 0), 1));
+    world.player.inventory[0].value = 0;
+    assert!(!world.player_sell(0));
+  }
+
+  // Haggling moves the markup one way or the other, until the shopkeeper runs out of patience
+  #[test]
+  fn haggling() {
+    let mut world = shop_world();
+    world.player.actor.pos = world.floor.items[0].pos;
+
+    for left in (0..PATIENCE_NORMAL).rev() {
+      assert!(world.player_haggle());
+      assert_eq!(world.shopkeeper().unwrap().1.patience, left);
+    }
+
+    let before = world.shopkeeper().unwrap().1.markup;
+    assert!(!world.player_haggle());
+    assert_eq!(world.shopkeeper().unwrap().1.markup, before);
+  }
+
+  // Unpaid things can't be used, and walking out with them is stealing
+  #[test]
+  fn theft_angers_shopkeeper() {
+    let mut world = shop_world();
+    pick_up_stock(&mut world);
+    assert!(!world.player_apply(0));
+    assert!(!world.player_equip(0, true) && !world.player_equip(0, false));
+    assert!(!world.player_throw(0, Pos::new(1, 0)));
+
+    // Still inside, so nobody minds yet
+    world.player.state = Actions::Wait;
+    world.update();
+    assert!(!world.shopkeeper().unwrap().1.angry);
+
+    world.player.actor.pos = world.floor.dun.shop.clone().unwrap().door + Pos::new(0, -1);
+    world.update();
+    assert!(world.shopkeeper().unwrap().1.angry);
+    assert!(!world.player.inventory[0].unpaid);
+    assert!(world.open_for_business().is_err());
+  }
+
+  // Shopkeepers stay by the door and leave the player alone, until they get hurt
+  #[test]
+  fn shopkeeper_minds_the_shop() {
+    let mut world = shop_world();
+    let shop = world.floor.dun.shop.clone().unwrap();
+    world.player.actor.pos = shop.post + Pos::new(1, 0);
+    world.player.stats.hp = 1000;
+    world.player.stats.max_hp = 1000;
+
+    world.player.state = Actions::Wait;
+    for _ in 0..20 {
+      world.update();
+    }
+    let (keeper, _) = world.shopkeeper().unwrap();
+    assert_eq!(world.floor.creatures[keeper].actor.pos, shop.post);
+    assert_eq!(world.player.stats.hp, 1000);
+
+    world.floor.creatures[keeper].stats.hp -= 1;
+    for _ in 0..20 {
+      world.update();
+    }
+    assert!(world.shopkeeper().unwrap().1.angry);
+    assert!(world.player.stats.hp < 1000);
+  }
+
+  // The shop keeps track of its shopkeeper as the creatures before them die, and stops once they die too
+  #[test]
+  fn shop_follows_its_shopkeeper() {
+    let mut world = shop_world();
+    let post = world.floor.dun.shop.clone().unwrap().post;
+    let (keeper, _) = world.shopkeeper().unwrap();
+
+    let ant = init::monsters().get("ant").unwrap().spawn(Pos::new(1, 1));
+    world.floor.creatures.insert(0, Box::new(ant));
+    world.floor.dun.shop.as_mut().unwrap().keeper = Some(keeper + 1);
+
+    world.floor.creatures[0].state = Actions::Die;
+    world.check_death();
+    let (keeper, _) = world.shopkeeper().unwrap();
+    assert_eq!(world.floor.creatures[keeper].actor.pos, post);
+
+    world.floor.creatures[keeper].state = Actions::Die;
+    world.check_death();
+    assert!(world.shopkeeper().is_none());
+    assert!(world.open_for_business().is_err());
+  }
+
+}
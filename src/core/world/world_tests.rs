@@ -0,0 +1,638 @@
+#[cfg(test)]
+mod tests {
+
+  extern crate serde_json;
+
+  use core::world::{Floor, World};
+  use core::world::desire::{Desire, DesireMaps};
+  use core::world::occupancy::{Collision, Occupancy, Occupant};
+  use core::creature::{Actions, Creature, Slot, Stats, CARRY_LIMIT, INVENTORY_SLOTS};
+  use core::creature::ai::{ListenerAI, PlayerAI, SimpleAI};
+  use core::creature::ai::listener::Awareness;
+  use core::renderer::{Renderable, RGB};
+  use core::world::dungeon::map::{tile, Emitter, Pos};
+  use core::init;
+  use core::item::{Item, ItemProperty, Money};
+
+  // Two worlds with the same seed should stay byte-identical as they are simulated
+  #[test]
+  fn same_seed_same_world() {
+    let mut a = World::new(Pos::new(89, 39), 1234);
+    let mut b = World::new(Pos::new(89, 39), 1234);
+
+    for _ in 0..100 {
+      a.update();
+      b.update();
+    }
+
+    assert_eq!(serde_json::to_string(&a).unwrap(), serde_json::to_string(&b).unwrap());
+  }
+
+  // A saved world should pick up exactly where it left off, rng included
+  #[test]
+  fn save_round_trip() {
+    let mut a = World::new(Pos::new(89, 39), 1234);
+    for _ in 0..10 {
+      a.update();
+    }
+
+    let mut b : World = serde_json::from_str(&serde_json::to_string(&a).unwrap()).unwrap();
+    b.restore();
+
+    for _ in 0..10 {
+      a.update();
+      b.update();
+    }
+
+    assert_eq!(serde_json::to_string(&a).unwrap(), serde_json::to_string(&b).unwrap());
+  }
+
+  // Saving and loading keeps every floor, what the player has seen on them, what the player carries and what each creature
+  // was thinking, and rebuilds everything that isn't saved
+  #[test]
+  fn save_keeps_everything() {
+    let mut a = World::new(Pos::new(89, 39), 1234);
+    a.floor_stack[0].dun[3][3].seen = true;
+    a.go_down();
+    a.floor.dun[5][5].seen = true;
+
+    a.player.wallet = 42.0;
+    a.player.inventory.clear();
+    a.player.pick_up(init::items().create("torch", Pos::new(0, 0), 2));
+
+    let pos = a.player.actor.pos + Pos::new(1, 0);
+    let mut ant = init::monsters().get("ant").unwrap().spawn(pos);
+    ant.ai = Box::new(ListenerAI::with_hearing(3, 7, Awareness::Asleep));
+    a.floor.creatures.push(Box::new(ant));
+    let ant = a.floor.creatures.len() - 1;
+
+    let mut b : World = serde_json::from_str(&serde_json::to_string(&a).unwrap()).unwrap();
+    b.restore();
+
+    let seen = |floor: &Floor| -> Vec<(usize, usize)> {
+      let mut seen = vec![];
+      for x in 0..floor.dun.width {
+        for y in 0..floor.dun.height {
+          if floor.dun[x][y].seen {
+            seen.push((x, y));
+          }
+        }
+      }
+      return seen;
+    };
+
+    assert_eq!(b.floor_num, 1);
+    assert_eq!(b.floor_stack.len(), a.floor_stack.len());
+    assert!(seen(&b.floor_stack[0]).contains(&(3, 3)));
+    assert_eq!(seen(&b.floor_stack[0]), seen(&a.floor_stack[0]));
+    assert!(seen(&b.floor).contains(&(5, 5)));
+    assert_eq!(seen(&b.floor), seen(&a.floor));
+
+    assert_eq!(b.player.wallet, 42.0);
+    assert_eq!(b.player.inventory.len(), 1);
+    assert_eq!(b.player.inventory[0].describe(), a.player.inventory[0].describe());
+
+    assert_eq!(serde_json::to_string(&b.floor.creatures[ant].ai.save()).unwrap(), serde_json::to_string(&a.floor.creatures[ant].ai.save()).unwrap());
+    assert_eq!(b.floor.occupancy.get(pos), Some(Occupant::Creature(ant)));
+  }
+
+  // Creatures only spawn where their spawn rules allow
+  #[test]
+  fn spawns_follow_tables() {
+    for seed in 0..10 {
+      let world = World::new(Pos::new(89, 39), seed);
+      for creature in &world.floor.creatures {
+        let pos = creature.actor.pos;
+        match creature.actor.get_id() {
+          // Echidnas only live in sunken areas
+          "Echidna" => assert_eq!(world.floor.dun[pos].biome, tile::Biome::Sunken),
+          // Blink hounds don't show up on the first floors
+          "blink hound" => panic!("Blink hound on floor 0 with seed {}", seed),
+          _ => {}
+        }
+      }
+    }
+  }
+
+  // Attacking a creature until it dies takes it off the floor
+  #[test]
+  fn player_kills_creature() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.floor.creatures.truncate(1);
+
+    // Pin the victim in place right next to the player
+    let target = world.player.actor.pos + Pos::new(1, 0);
+    world.floor.creatures[0].actor.pos = target;
+    world.floor.creatures[0].ai = Box::new(PlayerAI::new());
+    world.player.stats.attack = 100;
+
+    world.player.state = Actions::Attack(target);
+    world.update();
+
+    assert!(world.floor.creatures.is_empty());
+  }
+
+  // Faster creatures get more turns for the same amount of time
+  #[test]
+  fn speed_decides_turns() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.floor.creatures.truncate(2);
+    for creature in &mut world.floor.creatures {
+      creature.ai = Box::new(PlayerAI::new());
+    }
+    world.floor.creatures[0].stats.speed = 20;
+    world.floor.creatures[1].stats.speed = 5;
+
+    // A normal speed move takes 10 ticks. The fast creature acts twice in that time, the slow one once, and is still
+    // halfway to its next turn
+    world.pass_time(Actions::Move.cost());
+
+    assert_eq!(world.time, 10);
+    assert_eq!(world.player.energy, 0);
+    assert_eq!(world.floor.creatures[0].energy, 0);
+    assert_eq!(world.floor.creatures[1].energy, -50);
+  }
+
+
+  ///
+  /// A world walled in apart from a corridor along y = 5 from x = 1 to 20, with the player off in a room of their own and
+  /// a still creature standing at each of `spots`
+  ///
+  fn corridor(spots: &[(isize, Collision, usize)]) -> World {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+
+    for x in 0..world.floor.dun.width {
+      for y in 0..world.floor.dun.height {
+        world.floor.dun.grid[x][y] = tile::generic_wall();
+      }
+    }
+    for x in 1..21 {
+      world.floor.dun.grid[x][5] = tile::generic_floor();
+    }
+    world.floor.dun.grid[40][20] = tile::generic_floor();
+    world.player.actor.pos = Pos::new(40, 20);
+
+    world.floor.creatures.clear();
+    for &(x, collision, weight) in spots {
+      let mut creature = Creature::new("ant", 'a', Pos::new(x, 5), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(1, 0, 0, 10, weight, tile::Scent::Insectoid), PlayerAI::new());
+      creature.collision = collision;
+      world.floor.creatures.push(Box::new(creature));
+    }
+
+    world.update_occupancy();
+    return world;
+  }
+
+  // Blocking creatures stay put
+  #[test]
+  fn collision_block() {
+    let mut world = corridor(&[(1, Collision::Block, 10), (2, Collision::Block, 10)]);
+    assert!(!world.move_creature(0, Pos::new(2, 5)));
+    assert_eq!(world.floor.creatures[0].actor.pos, Pos::new(1, 5));
+    assert_eq!(world.floor.creatures[1].actor.pos, Pos::new(2, 5));
+    assert_eq!(world.creature_at(Pos::new(1, 5)), Some(0));
+    assert_eq!(world.creature_at(Pos::new(2, 5)), Some(1));
+  }
+
+  // Swapping creatures trade places
+  #[test]
+  fn collision_swap() {
+    let mut world = corridor(&[(1, Collision::Block, 10), (2, Collision::Swap, 10)]);
+    assert!(world.move_creature(0, Pos::new(2, 5)));
+    assert_eq!(world.floor.creatures[0].actor.pos, Pos::new(2, 5));
+    assert_eq!(world.floor.creatures[1].actor.pos, Pos::new(1, 5));
+    assert_eq!(world.creature_at(Pos::new(1, 5)), Some(1));
+    assert_eq!(world.creature_at(Pos::new(2, 5)), Some(0));
+  }
+
+  // Pushed creatures get shoved along, but only by something heavy enough and only if there's room
+  #[test]
+  fn collision_push() {
+    let mut world = corridor(&[(1, Collision::Block, 10), (2, Collision::Push, 10), (5, Collision::Push, 20), (6, Collision::Block, 10)]);
+
+    assert!(world.move_creature(0, Pos::new(2, 5)));
+    assert_eq!(world.floor.creatures[0].actor.pos, Pos::new(2, 5));
+    assert_eq!(world.floor.creatures[1].actor.pos, Pos::new(3, 5));
+    assert!(world.floor.occupancy.is_free(Pos::new(1, 5)));
+    assert_eq!(world.creature_at(Pos::new(3, 5)), Some(1));
+
+    // Too heavy
+    world.floor.creatures[1].actor.pos = Pos::new(4, 5);
+    world.update_occupancy();
+    assert!(!world.move_creature(1, Pos::new(5, 5)));
+
+    // Nowhere to go, since there's someone behind
+    world.floor.creatures[1].stats.weight = 30;
+    assert!(!world.move_creature(1, Pos::new(5, 5)));
+    assert_eq!(world.floor.creatures[2].actor.pos, Pos::new(5, 5));
+
+    // Nowhere to go, since there's a wall behind
+    world.floor.creatures[2].actor.pos = Pos::new(20, 5);
+    world.floor.creatures[1].actor.pos = Pos::new(19, 5);
+    world.update_occupancy();
+    assert!(!world.move_creature(1, Pos::new(20, 5)));
+    assert_eq!(world.floor.creatures[2].actor.pos, Pos::new(20, 5));
+    assert_eq!(world.floor.creatures[1].actor.pos, Pos::new(19, 5));
+  }
+
+  // A corridor full of wandering creatures never ends up with two of them on the same tile
+  #[test]
+  fn crowded_corridor() {
+    let mut world = corridor(&[]);
+    for x in 1..15 {
+      let collision = if x % 2 == 0 { Collision::Swap } else { Collision::Push };
+      world.floor.creatures.push(Box::new(Creature::new("ant", 'a', Pos::new(x, 5), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(1, 0, 0, 10, 10, tile::Scent::Insectoid), SimpleAI::new())));
+      world.floor.creatures.last_mut().unwrap().collision = collision;
+    }
+    world.update_occupancy();
+
+    for _ in 0..200 {
+      world.player.state = Actions::Wait;
+      world.update();
+
+      let mut spots : Vec<Pos> = world.floor.creatures.iter().map(|creature| creature.actor.pos).collect();
+      for pos in &spots {
+        assert!(tile::walkable(&world.floor.dun.grid[pos.x as usize][pos.y as usize]));
+      }
+      spots.sort_by_key(|pos| (pos.x, pos.y));
+      spots.dedup();
+      assert_eq!(spots.len(), 14);
+    }
+  }
+
+
+  // Teleporters never put anyone on a tile someone else is already standing on
+  #[test]
+  fn teleport_to_free_tiles() {
+    let spots : Vec<(isize, Collision, usize)> = (1..21).filter(|&x| x != 10 && x != 11).map(|x| (x, Collision::Block, 10)).collect();
+    let mut world = corridor(&spots);
+    let teleporter = || tile::Tile::new("Teleporter", '^', RGB(0, 0, 0), RGB(0, 0, 0), tile::Type::Trap(tile::Trap::Teleport));
+
+    world.floor.dun.grid[40][20] = teleporter();
+    world.check_traps();
+    let pos = world.player.actor.pos;
+    assert!(pos == Pos::new(10, 5) || pos == Pos::new(11, 5));
+    assert_eq!(world.creature_at(pos), None);
+
+    // Creatures can't land on the player either
+    world.floor.dun.grid[40][21] = teleporter();
+    world.floor.creatures[0].actor.pos = Pos::new(40, 21);
+    world.update_occupancy();
+    world.check_traps();
+    let moved = world.floor.creatures[0].actor.pos;
+    assert!(moved != world.player.actor.pos && moved.y == 5);
+    assert_eq!(world.creature_at(moved), Some(0));
+    assert_eq!(world.creature_at(world.player.actor.pos), None);
+
+    let mut spots : Vec<Pos> = world.floor.creatures.iter().map(|creature| creature.actor.pos).collect();
+    spots.sort_by_key(|pos| (pos.x, pos.y));
+    spots.dedup();
+    assert_eq!(spots.len(), world.floor.creatures.len());
+  }
+
+  // Teleport potions don't put the player on top of anyone either
+  #[test]
+  fn teleport_potion_to_free_tile() {
+    let spots : Vec<(isize, Collision, usize)> = (1..21).filter(|&x| x != 10).map(|x| (x, Collision::Block, 10)).collect();
+    let mut world = corridor(&spots);
+    world.player.inventory.clear();
+
+    for _ in 0..20 {
+      world.player.pick_up(init::items().create("teleport_potion", Pos::new(0, 0), 1));
+      assert!(world.player_apply(0));
+      let pos = world.player.actor.pos;
+      assert!(pos == Pos::new(10, 5) || pos == Pos::new(40, 20));
+      assert_eq!(world.creature_at(pos), None);
+    }
+  }
+
+  // Desire maps lead towards what's wanted, and away from what's feared
+  #[test]
+  fn desire_maps() {
+    let map = vec![vec![tile::generic_floor(); 10]; 10];
+    let player = Creature::new("hero", '@', Pos::new(8, 1), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(1, 0, 0, 10, 0, tile::Scent::Player), PlayerAI::new());
+    let blocker = Creature::new("ant", 'a', Pos::new(2, 0), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(1, 0, 0, 10, 0, tile::Scent::Insectoid), PlayerAI::new());
+    let occupancy = Occupancy::build(10, 10, &player, &[Box::new(blocker)]);
+    let desire = DesireMaps::build(&map, Pos::new(8, 1), &[Pos::new(1, 8)]);
+
+    assert_eq!(desire.get(Desire::Player)[8][1], 0);
+    assert_eq!(desire.get(Desire::Items)[1][8], 0);
+
+    // Straight at the player, and right into them once next to them
+    let step = desire.downhill(&map, &occupancy, Pos::new(5, 1), &[(Desire::Player, 1.0)]).unwrap();
+    assert_eq!(desire.get(Desire::Player)[step.x as usize][step.y as usize], 2 * tile::MOVE_COST_NORMAL as isize);
+    assert_eq!(desire.downhill(&map, &occupancy, Pos::new(7, 1), &[(Desire::Player, 1.0)]), Some(Pos::new(8, 1)));
+
+    // Scavengers care more about the item
+    let step = desire.downhill(&map, &occupancy, Pos::new(4, 4), &[(Desire::Player, 1.0), (Desire::Items, 2.0)]).unwrap();
+    assert!(step.x <= 4 && step.y >= 4);
+
+    // Fleeing gets further from the player
+    let step = desire.downhill(&map, &occupancy, Pos::new(5, 3), &[(Desire::Flee, 1.0)]).unwrap();
+    assert!(desire.get(Desire::Player)[step.x as usize][step.y as usize] > desire.get(Desire::Player)[5][3]);
+
+    // Nobody walks into anyone else, and nothing moves when there's nothing to want
+    assert_eq!(desire.downhill(&map, &occupancy, Pos::new(1, 1), &[(Desire::Player, 1.0)]), Some(Pos::new(2, 1)));
+    assert_eq!(desire.downhill(&map, &occupancy, Pos::new(1, 1), &[(Desire::Stairs, 1.0)]), None);
+  }
+
+
+  // Sneaking around makes less noise
+  #[test]
+  fn sneaking_is_quiet() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.floor.creatures.clear();
+    let pos = world.player.actor.pos;
+
+    world.player.state = Actions::Move;
+    world.update_sound();
+    let loud = world.floor.dun[pos].sound;
+
+    world.player.sneaking = true;
+    world.update_sound();
+    let quiet = world.floor.dun[pos].sound;
+
+    assert!(quiet > 0 && quiet < loud);
+  }
+
+
+  // Tiles and items that smell or make noise get picked up every turn without anything else having to know about them
+  #[test]
+  fn emitters_are_picked_up() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.floor.creatures.clear();
+
+    // Every floor gets some
+    let fixtures = world.floor.dun.grid.iter()
+      .flat_map(|column| column.iter())
+      .filter(|t| t.emitter.is_some())
+      .count();
+    assert!(fixtures >= 3);
+
+    // Somewhere quiet, away from the player and anything else making noise
+    let mut brazier = None;
+    let mut meat = None;
+    for (x, column) in world.floor.dun.grid.iter().enumerate() {
+      for (y, t) in column.iter().enumerate() {
+        let pos = Pos::from_usize(x, y);
+        if t.tiletype == tile::Type::Floor(tile::Floor::Normal) && (pos ^ world.player.actor.pos) > 10.0 && t.sound == 0 {
+          if brazier.is_none() {
+            brazier = Some(pos);
+          } else if meat.is_none() && (pos ^ brazier.unwrap()) > 10.0 {
+            meat = Some(pos);
+          }
+        }
+      }
+    }
+    let (brazier, meat) = (brazier.unwrap(), meat.unwrap());
+
+    world.floor.dun[brazier] = tile::brazier();
+    let mut item = Item::new("rotten meat", '%', meat, RGB(0, 0, 0), RGB(0, 0, 0), 1, ItemProperty::Money(Money::Copper));
+    item.emitter = Some(Emitter::new(Some(tile::Scent::Decay), 5));
+    world.floor.items.push(item);
+
+    world.update_scent();
+    world.update_sound();
+
+    assert!(world.floor.scent.get(brazier, &tile::Scent::Smoke) > 0);
+    assert!(world.floor.scent.get(meat, &tile::Scent::Decay) > 0);
+    assert_eq!(world.floor.scent.get(meat, &tile::Scent::Smoke), 0);
+    assert!(world.floor.dun[brazier].sound > 0);
+    assert!(world.floor.dun[meat].sound >= 5);
+  }
+
+
+  // The dead leave their body and belongings behind, and the body rots away over time
+  #[test]
+  fn corpses_rot() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.floor.creatures.clear();
+    world.floor.items.clear();
+
+    let pos = world.player.actor.pos + Pos::new(1, 0);
+    world.floor.dun[pos] = tile::generic_floor();
+    let mut rat = Creature::new("rat", 'r', pos, RGB(200, 100, 50), RGB(0, 0, 0), Stats::new(1, 0, 0, 10, 5, tile::Scent::Decay), SimpleAI::new());
+    rat.wallet = 3.5;
+    rat.inventory.push(Item::new("cheese", '%', Pos::new(0, 0), RGB(255, 255, 0), RGB(0, 0, 0), 1, ItemProperty::Money(Money::Copper)));
+    rat.state = Actions::Die;
+    world.floor.creatures.push(Box::new(rat));
+
+    world.check_death();
+    assert!(world.floor.creatures.is_empty());
+    assert_eq!(world.floor.items.len(), 3);
+    assert!(world.floor.items.iter().all(|item| item.pos == pos));
+
+    let corpse = world.floor.items.iter().find(|item| item.get_id() == "rat corpse").unwrap().clone();
+    assert_eq!(corpse.get_glyph(), 'r');
+    assert_eq!(corpse.get_fg(), RGB(200, 100, 50));
+    assert!(corpse.emitter.is_none());
+    assert_eq!(world.floor.items.iter().find(|item| item.get_id() == "gold piece").unwrap().quantity, 3);
+    assert!(world.floor.items.iter().any(|item| item.get_id() == "cheese"));
+
+    // Fresh bodies don't smell, but rotting ones do
+    world.time += 200;
+    world.update_corpses();
+    let corpse = world.floor.items.iter().find(|item| item.get_id() == "rat corpse").unwrap().clone();
+    assert_eq!(corpse.emitter, Some(Emitter::smell(tile::Scent::Decay)));
+    assert_eq!(corpse.get_fg(), RGB(100, 50, 25));
+    world.update_scent();
+    assert!(world.floor.scent.get(pos, &tile::Scent::Decay) > 0);
+
+    // Until there's nothing left, but the rest of what it dropped stays
+    world.time += 1500;
+    world.update_corpses();
+    assert_eq!(world.floor.items.len(), 2);
+    assert!(world.floor.items.iter().all(|item| item.get_id() != "rat corpse"));
+  }
+
+
+  // Creatures can kill the player, and the world remembers what did it
+  #[test]
+  fn player_can_die() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.floor.creatures.clear();
+
+    let pos = world.player.actor.pos;
+    world.player.stats.hp = 1;
+    world.floor.creatures.push(Box::new(Creature::new("ogre", 'O', pos + Pos::new(1, 0), RGB(0, 0, 0), RGB(0, 0, 0), Stats::new(10, 100, 0, 10, 50, tile::Scent::Canine), PlayerAI::new())));
+    world.update_occupancy();
+
+    assert_eq!(world.cause_of_death, None);
+    assert!(!world.move_creature(0, pos));
+    assert_eq!(world.player.state, Actions::Die);
+    assert_eq!(world.cause_of_death, Some("Killed by an ogre".to_string()));
+  }
+
+  // Picking up fills the inventory up to what the player can carry, and dropping puts things back
+  #[test]
+  fn pick_up_and_drop() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.floor.creatures.clear();
+    world.floor.items.clear();
+    world.player.inventory.clear();
+    world.player.equipment.light = None;
+    let pos = world.player.actor.pos;
+
+    // Small enough to carry, and the same mouse twice stacks
+    let mut mouse = Item::corpse("mouse", 'r', RGB(150, 150, 150), pos, 0);
+    mouse.weight = 1;
+    world.floor.items.push(mouse.clone());
+    world.floor.items.push(mouse);
+    world.floor.items.push(Item::corpse("ogre", 'O', RGB(0, 150, 0), pos, 0));
+    world.floor.items.push(Item::new("gold piece", '$', pos, RGB(238, 232, 170), RGB(0, 0, 0), 5, ItemProperty::Money(Money::Gold)));
+
+    assert!(world.player_pick_up());
+    assert_eq!(world.player.wallet, 5.0);
+    assert_eq!(world.player.inventory.len(), 1);
+    assert_eq!(world.player.inventory[0].quantity, 2);
+    assert_eq!(world.player.carried_weight(), 2);
+
+    // The ogre is far too heavy, so it stays where it is
+    assert_eq!(world.floor.items.len(), 1);
+    assert_eq!(world.floor.items[0].get_id(), "ogre corpse");
+    assert!(!world.player_pick_up());
+
+    assert!(world.player_drop(0));
+    assert!(!world.player_drop(0));
+    assert!(world.player.inventory.is_empty());
+    assert_eq!(world.floor.items.len(), 2);
+    assert!(world.floor.items.iter().all(|item| item.pos == pos));
+  }
+
+  // There's only so much room in a pack
+  #[test]
+  fn inventory_fills_up() {
+    let mut player = World::new(Pos::new(89, 39), 1234).player;
+    player.inventory.clear();
+    for i in 0..INVENTORY_SLOTS {
+      let mut thing = Item::corpse(Box::leak(format!("thing {}", i).into_boxed_str()), 't', RGB(0, 0, 0), Pos::new(0, 0), 0);
+      thing.weight = 0;
+      assert!(player.can_carry(&thing).is_ok());
+      player.pick_up(thing);
+    }
+
+    let mut another = Item::corpse("another thing", 't', RGB(0, 0, 0), Pos::new(0, 0), 0);
+    another.weight = 0;
+    assert!(player.can_carry(&another).is_err());
+
+    let mut more = Item::corpse("thing 0", 't', RGB(0, 0, 0), Pos::new(0, 0), 0);
+    more.weight = 0;
+    assert!(player.can_carry(&more).is_ok());
+
+    more.weight = CARRY_LIMIT + 1;
+    assert!(player.can_carry(&more).is_err());
+  }
+
+  // Using things does what they're for, and uses them up
+  #[test]
+  fn apply_items() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.player.inventory.clear();
+
+    world.player.pick_up(init::items().create("ration", Pos::new(0, 0), 1));
+    world.player.pick_up(init::items().create("ration", Pos::new(0, 0), 1));
+    world.player.pick_up(init::items().create("healing_potion", Pos::new(0, 0), 1));
+    world.player.pick_up(init::items().create("iron_key", Pos::new(0, 0), 1));
+
+    world.player.stats.hp = 1;
+    assert!(world.player_apply(0));
+    assert_eq!(world.player.stats.hp, 6);
+    assert_eq!(world.player.inventory[0].quantity, 1);
+
+    assert!(world.player_apply(1));
+    assert!(world.player.stats.hp >= 10);
+    assert_eq!(world.player.inventory.len(), 2);
+
+    // Keys don't have anything to open, and nothing can be used that isn't there
+    assert!(!world.player_apply(1));
+    assert!(!world.player_apply(5));
+    assert_eq!(world.player.inventory.len(), 2);
+
+    // Healing never goes past max hp
+    world.player.stats.hp = world.player.stats.max_hp;
+    assert!(world.player_apply(0));
+    assert_eq!(world.player.stats.hp, world.player.stats.max_hp);
+  }
+
+  // Lights burn down, and the player can't see as far once they go out
+  #[test]
+  fn lights_burn_out() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    let lit = world.sight_radius();
+
+    world.player.equipment.light.as_mut().unwrap().property = ItemProperty::Light(lit, 2);
+    world.update();
+    world.update();
+
+    assert_eq!(world.player.equipment.light.as_ref().unwrap().property, ItemProperty::Light(lit, 0));
+    assert!(world.sight_radius() < lit);
+  }
+
+  // Thrown things fly until they hit something, and land wherever they stop
+  #[test]
+  fn throw_items() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.floor.creatures.clear();
+    world.floor.items.clear();
+    world.player.inventory.clear();
+
+    // Clear a row for the dart to fly down
+    let start = Pos::new(10, 10);
+    for x in 10..25 {
+      world.floor.dun[x][10] = tile::generic_floor();
+    }
+    world.floor.dun[25][10] = tile::generic_wall();
+    world.player.actor.pos = start;
+
+    let mut darts = init::items().create("dart", Pos::new(0, 0), 1);
+    darts.quantity = 2;
+    world.player.pick_up(darts);
+
+    // Nothing in the way, so it goes as far as it can
+    assert!(world.player_throw(0, Pos::new(1, 0)));
+    assert_eq!(world.player.inventory[0].quantity, 1);
+    assert_eq!(world.floor.items[0].pos, Pos::new(18, 10));
+    assert_eq!(world.floor.items[0].quantity, 1);
+
+    // Something in the way gets hit, or at least aimed at
+    let rat = Creature::new("rat", 'r', Pos::new(13, 10), RGB(200, 100, 50), RGB(0, 0, 0), Stats::new(1, 0, 0, 10, 5, tile::Scent::Decay), SimpleAI::new());
+    world.floor.creatures.push(Box::new(rat));
+    world.update_occupancy();
+    assert!(world.player_throw(0, Pos::new(1, 0)));
+    assert!(world.player.inventory.is_empty());
+    assert_eq!(world.floor.items[1].pos, Pos::new(13, 10));
+
+    assert!(!world.player_throw(0, Pos::new(1, 0)));
+  }
+
+  // Wielding and wearing move things from the pack onto the player, and taking them off puts them back
+  #[test]
+  fn equip_and_remove() {
+    let mut world = World::new(Pos::new(89, 39), 1234);
+    world.player.inventory.clear();
+
+    world.player.pick_up(init::items().create("dagger", Pos::new(0, 0), 1));
+    world.player.pick_up(init::items().create("leather_armor", Pos::new(0, 0), 1));
+    let attack = world.player.effective_stats().attack;
+
+    // Armor can't be wielded, and daggers can't be worn
+    assert!(!world.player_equip(1, true));
+    assert!(!world.player_equip(0, false));
+
+    assert!(world.player_equip(0, true));
+    assert!(world.player_equip(0, false));
+    assert!(world.player.inventory.is_empty());
+    assert_eq!(world.player.effective_stats().attack, attack + 2);
+    assert_eq!(world.player.effective_stats().defense, world.player.stats.defense + 1);
+
+    // Lights come off too, and the player can't see as far without one
+    let lit = world.sight_radius();
+    assert!(world.player_remove(Slot::Light));
+    assert!(!world.player_remove(Slot::Light));
+    assert!(world.sight_radius() < lit);
+    assert_eq!(world.player.inventory.len(), 1);
+    assert!(world.player_equip(0, false));
+    assert_eq!(world.sight_radius(), lit);
+  }
+
+}